@@ -0,0 +1,15 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Use a vendored `protoc`, so that building doesn't require it to be installed
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/externalscaler.proto"], &["proto"])?;
+
+    tonic_build::configure()
+        .build_client(false)
+        .build_server(false)
+        .compile_protos(&["proto/remote_write.proto"], &["proto"])?;
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum AlertRulesError {
+    /// Reading the rules file failed.
+    #[error("Alert rules file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content of the rules file could not be parsed.
+    #[error("Alert rules parsing failed: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    /// A rule has an invalid regular expression.
+    #[error("Alert rule regex is invalid: {0}")]
+    Regex(#[from] regex::Error),
+
+    /// More than one rule has the same name.
+    #[error("Alert rule name '{0}' is not unique")]
+    DuplicateName(String),
+
+    /// A rule requires a threshold, but none was given.
+    #[error("Alert rule '{0}' is missing a 'threshold'")]
+    MissingThreshold(String),
+
+    /// A rule has a `for` duration too long to be represented.
+    #[error("Alert rule '{0}' has an out of range 'for': {1}")]
+    InvalidFor(String, u64),
+}
+
+pub type AlertRulesResult<T> = Result<T, AlertRulesError>;
@@ -0,0 +1,72 @@
+//! Alerting on lag, for setups without Prometheus and Alertmanager.
+//!
+//! Alert rules are evaluated periodically against the [`LagRegister`] and the [`DataLossRegister`],
+//! and alerts that begin firing (or are resolved) are notified to webhooks.
+//! Rules can be replaced at runtime, via the channel created by [`init_rules`].
+
+// Inner modules
+mod errors;
+mod register;
+mod rules;
+mod webhook;
+
+// Exports
+pub use register::AlertsRegister;
+pub use rules::AlertRules;
+
+// Imports
+use std::{path::Path, sync::Arc};
+
+use reqwest::Url;
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::cluster_status::ClusterStatusRegister;
+use crate::data_loss::DataLossRegister;
+use crate::lag_register::LagRegister;
+
+/// Read the [`AlertRules`] from their `inline` definition or `rules_path` (if any), into a channel
+/// that [`AlertsRegister`]s receive the latest rules from.
+///
+/// # Panics
+///
+/// If the rules are given, but they're not valid [`AlertRules`].
+pub fn init_rules(
+    rules_path: Option<&Path>,
+    inline: Option<&str>,
+) -> (watch::Sender<Arc<AlertRules>>, watch::Receiver<Arc<AlertRules>>) {
+    let rules = AlertRules::load(rules_path, inline)
+        .unwrap_or_else(|e| panic!("Failed to load alert rules: {e}"));
+    info!("Loaded {} alert rules", rules.rules.len());
+
+    watch::channel(Arc::new(rules))
+}
+
+/// Create an [`AlertsRegister`] evaluating the latest `rules` (once the replay of `__consumer_offsets`
+/// has caught up), and spawn the task notifying the `webhooks`.
+pub fn init(
+    rules: watch::Receiver<Arc<AlertRules>>,
+    webhooks: Vec<Url>,
+    lag_reg: Arc<LagRegister>,
+    dl_reg: Arc<DataLossRegister>,
+    cs_reg: Arc<ClusterStatusRegister>,
+    replay_caught_up: watch::Receiver<bool>,
+    shutdown_token: CancellationToken,
+) -> (AlertsRegister, JoinHandle<()>, JoinHandle<()>) {
+    let (notifications_tx, notifications_rx) = mpsc::channel(webhook::NOTIFICATIONS_QUEUE_CAPACITY);
+    let wh_join = webhook::spawn(webhooks, notifications_rx, cs_reg, shutdown_token.clone());
+    let (alerts_reg, ar_join) = AlertsRegister::new(
+        rules,
+        lag_reg,
+        dl_reg,
+        notifications_tx,
+        replay_caught_up,
+        shutdown_token,
+    );
+
+    debug!("Initialized");
+    (alerts_reg, ar_join, wh_join)
+}
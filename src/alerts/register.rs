@@ -0,0 +1,385 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tokio::{
+    sync::{mpsc, watch, RwLock},
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::data_loss::DataLossRegister;
+use crate::lag_register::LagRegister;
+
+use super::rules::{AlertKind, AlertRule, AlertRules};
+
+/// How often the alert rules are evaluated.
+const EVALUATION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    /// The condition is met, but not yet for the `for` duration of the rule.
+    Pending,
+
+    /// The condition is met for at least the `for` duration of the rule.
+    Firing,
+}
+
+/// An alert of an [`AlertRule`], for a Topic consumed by a Consumer Group.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub rule: String,
+    pub kind: AlertKind,
+    pub group: String,
+    pub topic: String,
+    pub state: AlertState,
+
+    /// Latest value measured, in the unit of the `threshold`.
+    pub value: f64,
+    pub threshold: f64,
+
+    /// When the condition began to be met.
+    pub active_since: DateTime<Utc>,
+
+    /// When the alert began firing.
+    pub fired_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationStatus {
+    Firing,
+    Resolved,
+}
+
+/// Notification of an [`Alert`] beginning to fire, or being resolved.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub status: NotificationStatus,
+
+    #[serde(flatten)]
+    pub alert: Alert,
+
+    /// When the condition stopped being met.
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Measurements of a Topic consumed by a Consumer Group, that alert rules are evaluated against.
+#[derive(Debug, Default)]
+struct Observation {
+    offset_lag: Option<u64>,
+    time_lag: Option<Duration>,
+    last_commit: Option<DateTime<Utc>>,
+    offsets_lost: u64,
+}
+
+impl Observation {
+    /// Value of the measurement checked by the given [`AlertKind`], if known.
+    fn value(&self, kind: AlertKind, now: DateTime<Utc>) -> Option<f64> {
+        match kind {
+            AlertKind::OffsetLag => self.offset_lag.map(|ol| ol as f64),
+            AlertKind::TimeLag => self.time_lag.map(|tl| tl.num_milliseconds() as f64 / 1000.0),
+            AlertKind::NoCommits => {
+                self.last_commit.map(|lc| (now - lc).num_milliseconds() as f64 / 1000.0)
+            },
+            AlertKind::DataLoss => Some(self.offsets_lost as f64),
+        }
+    }
+}
+
+/// Key of an [`Alert`]: rule, Consumer Group and Topic.
+type AlertKey = (String, String, String);
+
+#[derive(Debug, Default)]
+struct AlertsState {
+    /// Alerts currently pending or firing.
+    active: BTreeMap<AlertKey, Alert>,
+}
+
+impl AlertsState {
+    /// Evaluate the `rules` against the latest [`Observation`]s, replacing the active alerts.
+    ///
+    /// Returns a [`Notification`] for each alert that began firing, or was resolved:
+    /// each alert notifies at most once that it's firing, and once that it's resolved.
+    fn update(
+        &mut self,
+        rules: &[AlertRule],
+        observations: &HashMap<(String, String), Observation>,
+        now: DateTime<Utc>,
+    ) -> Vec<Notification> {
+        let mut previous = std::mem::take(&mut self.active);
+        let mut notifications = Vec::new();
+
+        for rule in rules.iter() {
+            for ((g, t), o) in observations.iter().filter(|((g, t), _)| rule.matches(g, t)) {
+                let Some(value) = o.value(rule.kind, now).filter(|v| rule.is_met(*v)) else {
+                    continue;
+                };
+
+                let key = (rule.name.clone(), g.clone(), t.clone());
+                let mut alert = previous.remove(&key).unwrap_or_else(|| Alert {
+                    rule: rule.name.clone(),
+                    kind: rule.kind,
+                    group: g.clone(),
+                    topic: t.clone(),
+                    state: AlertState::Pending,
+                    value,
+                    threshold: rule.threshold,
+                    active_since: now,
+                    fired_at: None,
+                });
+                alert.value = value;
+                alert.threshold = rule.threshold;
+
+                if alert.state == AlertState::Pending
+                    && now - alert.active_since >= rule.for_duration
+                {
+                    alert.state = AlertState::Firing;
+                    alert.fired_at = Some(now);
+                    notifications.push(Notification {
+                        status: NotificationStatus::Firing,
+                        alert: alert.clone(),
+                        resolved_at: None,
+                    });
+                }
+                self.active.insert(key, alert);
+            }
+        }
+
+        // Alerts no longer active: if they were firing, they are now resolved
+        for alert in previous.into_values().filter(|a| a.state == AlertState::Firing) {
+            notifications.push(Notification {
+                status: NotificationStatus::Resolved,
+                alert,
+                resolved_at: Some(now),
+            });
+        }
+
+        notifications
+    }
+}
+
+/// Evaluates [`AlertRules`] against the [`LagRegister`] and the [`DataLossRegister`],
+/// tracking the active [`Alert`]s.
+pub struct AlertsRegister {
+    state: Arc<RwLock<AlertsState>>,
+}
+
+impl AlertsRegister {
+    /// Create a new [`Self`], and spawn the task that periodically evaluates the rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The [`AlertRules`] to evaluate: the latest received are used at every evaluation
+    /// * `lag_reg` - The [`LagRegister`] providing the lag
+    /// * `dl_reg` - The [`DataLossRegister`] providing the ongoing data loss
+    /// * `notifications_tx` - Where to send the [`Notification`]s of alerts firing and resolved
+    /// * `replay_caught_up` - Whether the replay of `__consumer_offsets` has caught up: until then,
+    ///   commits are stale and lag inflated, so nothing is evaluated
+    /// * `shutdown_token` - A [`CancellationToken`] that, when cancelled, will make the evaluation task terminate
+    pub fn new(
+        rules: watch::Receiver<Arc<AlertRules>>,
+        lag_reg: Arc<LagRegister>,
+        dl_reg: Arc<DataLossRegister>,
+        notifications_tx: mpsc::Sender<Notification>,
+        replay_caught_up: watch::Receiver<bool>,
+        shutdown_token: CancellationToken,
+    ) -> (Self, JoinHandle<()>) {
+        let ar = Self {
+            state: Arc::new(RwLock::new(AlertsState::default())),
+        };
+
+        let state_clone = ar.state.clone();
+        let join_handle = tokio::spawn(async move {
+            let mut interval = interval(EVALUATION_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if !*replay_caught_up.borrow() {
+                            continue;
+                        }
+
+                        // Evaluate even without rules, if some were removed while their alerts were active
+                        let rules = rules.borrow().clone();
+                        if rules.rules.is_empty() && state_clone.read().await.active.is_empty() {
+                            continue;
+                        }
+
+                        let observations = observe(&lag_reg, &dl_reg).await;
+                        let notifications = state_clone.write().await.update(&rules.rules, &observations, Utc::now());
+                        for n in notifications {
+                            info!("Alert '{}' {:?}: group '{}' on topic '{}' (value {}, threshold {})", n.alert.rule, n.status, n.alert.group, n.alert.topic, n.alert.value, n.alert.threshold);
+
+                            // Never block evaluation on notifications delivery
+                            if let Err(e) = notifications_tx.try_send(n) {
+                                warn!("Dropping alert notification: {e}");
+                            }
+                        }
+                    },
+                    _ = shutdown_token.cancelled() => {
+                        info!("Shutting down");
+                        break;
+                    },
+                }
+            }
+        });
+
+        (ar, join_handle)
+    }
+
+    /// All the [`Alert`]s currently pending or firing, sorted by rule, Consumer Group and Topic.
+    pub async fn active(&self) -> Vec<Alert> {
+        self.state.read().await.active.values().cloned().collect()
+    }
+}
+
+/// Observe all the Topics consumed by the Consumer Groups in the [`LagRegister`],
+/// alongside the data loss ongoing on them.
+async fn observe(
+    lag_reg: &LagRegister,
+    dl_reg: &DataLossRegister,
+) -> HashMap<(String, String), Observation> {
+    let mut observations: HashMap<(String, String), Observation> = HashMap::new();
+
+    {
+        let lag_by_group = lag_reg.lag_by_group.read().await;
+        for (g, gwl) in lag_by_group.iter() {
+            for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+                let o = observations.entry((g.clone(), tp.topic.clone())).or_default();
+                if let Some(l) = lwo.lag.as_ref() {
+                    o.offset_lag = Some(o.offset_lag.unwrap_or_default() + l.offset_lag);
+                    o.time_lag = o.time_lag.max(Some(l.time_lag));
+                    o.last_commit = o.last_commit.max(Some(l.offset_timestamp));
+                }
+            }
+        }
+    }
+
+    for dl in dl_reg.ongoing().await {
+        if let Some(o) = observations.get_mut(&(dl.group, dl.topic_partition.topic)) {
+            o.offsets_lost += dl.offsets_lost;
+        }
+    }
+
+    observations
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::{Duration, Utc};
+
+    use super::{AlertState, AlertsState, NotificationStatus, Observation};
+    use crate::alerts::rules::AlertRules;
+
+    const RULES: &str = r#"
+        [[rule]]
+        name = "offset-lag"
+        kind = "offset_lag"
+        group = "^g$"
+        threshold = 100
+        for = 60
+
+        [[rule]]
+        name = "no-commits"
+        kind = "no_commits"
+        threshold = 300
+    "#;
+
+    fn observations(offset_lag: u64) -> HashMap<(String, String), Observation> {
+        HashMap::from([(
+            ("g".to_string(), "t".to_string()),
+            Observation {
+                offset_lag: Some(offset_lag),
+                time_lag: Some(Duration::zero()),
+                last_commit: Some(Utc::now()),
+                offsets_lost: 0,
+            },
+        )])
+    }
+
+    #[test]
+    fn alert_fires_after_for_and_resolves_once() {
+        let rules = AlertRules::parse(RULES).unwrap().rules;
+        let mut state = AlertsState::default();
+        let t0 = Utc::now();
+
+        // Condition met: pending, until it's been met for 60s
+        assert!(state.update(&rules, &observations(150), t0).is_empty());
+        assert_eq!(state.active.len(), 1);
+        assert_eq!(state.active.values().next().unwrap().state, AlertState::Pending);
+        let t1 = t0 + Duration::seconds(30);
+        assert!(state.update(&rules, &observations(150), t1).is_empty());
+
+        // Firing: notified only once
+        let t2 = t0 + Duration::seconds(60);
+        let notifications = state.update(&rules, &observations(200), t2);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].status, NotificationStatus::Firing);
+        assert_eq!(notifications[0].alert.value, 200.0);
+        assert_eq!(notifications[0].alert.active_since, t0);
+        let t3 = t2 + Duration::seconds(5);
+        assert!(state.update(&rules, &observations(200), t3).is_empty());
+
+        // Resolved: notified only once
+        let t4 = t3 + Duration::seconds(5);
+        let notifications = state.update(&rules, &observations(50), t4);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].status, NotificationStatus::Resolved);
+        assert_eq!(notifications[0].resolved_at, Some(t4));
+        assert!(state.active.is_empty());
+        assert!(state.update(&rules, &observations(50), t4).is_empty());
+    }
+
+    #[test]
+    fn pending_alert_resolves_silently() {
+        let rules = AlertRules::parse(RULES).unwrap().rules;
+        let mut state = AlertsState::default();
+        let t0 = Utc::now();
+
+        assert!(state.update(&rules, &observations(150), t0).is_empty());
+        assert!(state.update(&rules, &observations(50), t0 + Duration::seconds(5)).is_empty());
+        assert!(state.active.is_empty());
+    }
+
+    #[test]
+    fn active_alert_follows_reloaded_threshold() {
+        let rules = AlertRules::parse(RULES).unwrap().rules;
+        let mut state = AlertsState::default();
+        let t0 = Utc::now();
+
+        state.update(&rules, &observations(150), t0);
+        let notifications = state.update(&rules, &observations(150), t0 + Duration::seconds(60));
+        assert_eq!(notifications[0].alert.threshold, 100.0);
+
+        let reloaded =
+            AlertRules::parse(&RULES.replace("threshold = 100", "threshold = 120")).unwrap().rules;
+        let t1 = t0 + Duration::seconds(65);
+        assert!(state.update(&reloaded, &observations(150), t1).is_empty());
+        assert_eq!(state.active.values().next().unwrap().threshold, 120.0);
+
+        let notifications = state.update(&reloaded, &observations(110), t1 + Duration::seconds(5));
+        assert_eq!(notifications[0].status, NotificationStatus::Resolved);
+        assert_eq!(notifications[0].alert.threshold, 120.0);
+    }
+
+    #[test]
+    fn no_commits_fires_without_for() {
+        let rules = AlertRules::parse(RULES).unwrap().rules;
+        let mut state = AlertsState::default();
+        let now = Utc::now() + Duration::minutes(10);
+
+        let notifications = state.update(&rules, &observations(0), now);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].alert.rule, "no-commits");
+        assert!(notifications[0].alert.value > 300.0);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use chrono::Duration;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::errors::{AlertRulesError, AlertRulesResult};
+
+/// What an [`AlertRule`] checks, for each Topic consumed by a Consumer Group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// Sum of the offset lag across the Topic Partitions is above `threshold` (offsets).
+    OffsetLag,
+
+    /// Maximum time lag across the Topic Partitions is above `threshold` (seconds).
+    TimeLag,
+
+    /// No offset was committed to any of the Topic Partitions for more than `threshold` (seconds).
+    NoCommits,
+
+    /// Data loss is ongoing on any of the Topic Partitions: `threshold` is not used.
+    DataLoss,
+}
+
+/// A rule, as it appears in the rules file.
+#[derive(Debug, Deserialize)]
+struct RawRule {
+    name: String,
+    kind: AlertKind,
+    group: Option<String>,
+    topic: Option<String>,
+    threshold: Option<f64>,
+    #[serde(rename = "for", default)]
+    for_secs: u64,
+}
+
+/// Content of the rules file.
+#[derive(Debug, Default, Deserialize)]
+struct RawRules {
+    #[serde(default)]
+    rule: Vec<RawRule>,
+}
+
+/// A rule, evaluated for each Topic consumed by each Consumer Group it matches.
+#[derive(Debug)]
+pub struct AlertRule {
+    pub name: String,
+    pub kind: AlertKind,
+    group: Option<Regex>,
+    topic: Option<Regex>,
+    pub threshold: f64,
+
+    /// How long the condition has to hold, before the alert fires.
+    pub for_duration: Duration,
+}
+
+impl TryFrom<RawRule> for AlertRule {
+    type Error = AlertRulesError;
+
+    fn try_from(raw: RawRule) -> AlertRulesResult<Self> {
+        let threshold = match (raw.kind, raw.threshold) {
+            (AlertKind::DataLoss, t) => t.unwrap_or_default(),
+            (_, Some(t)) => t,
+            (_, None) => return Err(AlertRulesError::MissingThreshold(raw.name)),
+        };
+        let for_duration = i64::try_from(raw.for_secs)
+            .ok()
+            .and_then(Duration::try_seconds)
+            .ok_or_else(|| AlertRulesError::InvalidFor(raw.name.clone(), raw.for_secs))?;
+
+        Ok(AlertRule {
+            group: raw.group.as_deref().map(Regex::new).transpose()?,
+            topic: raw.topic.as_deref().map(Regex::new).transpose()?,
+            name: raw.name,
+            kind: raw.kind,
+            threshold,
+            for_duration,
+        })
+    }
+}
+
+impl AlertRule {
+    /// Does the rule apply to the given Topic, consumed by the given Consumer Group?
+    pub fn matches(&self, group: &str, topic: &str) -> bool {
+        self.group.as_ref().is_none_or(|r| r.is_match(group))
+            && self.topic.as_ref().is_none_or(|r| r.is_match(topic))
+    }
+
+    /// Does the `value` measured for the rule's [`AlertKind`] meet the alert condition?
+    pub fn is_met(&self, value: f64) -> bool {
+        match self.kind {
+            AlertKind::DataLoss => value > 0.0,
+            _ => value > self.threshold,
+        }
+    }
+}
+
+/// Alert rules, defined in TOML as an array of rules:
+///
+/// ```toml
+/// [[rule]]
+/// name = "payments-time-lag"
+/// kind = "time_lag"
+/// group = "^payments-"
+/// threshold = 300
+/// for = 120
+/// ```
+///
+/// `group` and `topic` are regular expressions, matching all names when omitted.
+/// The unit of `threshold` depends on the [`AlertKind`], and `for` is in seconds (default `0`).
+#[derive(Debug, Default)]
+pub struct AlertRules {
+    pub rules: Vec<AlertRule>,
+}
+
+impl AlertRules {
+    /// Parse [`AlertRules`] from their TOML definition.
+    pub fn parse(toml_str: &str) -> AlertRulesResult<Self> {
+        let raw: RawRules = toml::from_str(toml_str)?;
+
+        let mut names = HashSet::new();
+        for r in raw.rule.iter() {
+            if !names.insert(r.name.as_str()) {
+                return Err(AlertRulesError::DuplicateName(r.name.clone()));
+            }
+        }
+
+        Ok(AlertRules {
+            rules: raw.rule.into_iter().map(AlertRule::try_from).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Read and parse [`AlertRules`] from the file at `path`.
+    pub fn read(path: &Path) -> AlertRulesResult<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parse [`AlertRules`] from their `inline` definition if given, or read them from the file
+    /// at `path` if given: no rules otherwise.
+    pub fn load(path: Option<&Path>, inline: Option<&str>) -> AlertRulesResult<Self> {
+        match (inline, path) {
+            (Some(toml_str), _) => Self::parse(toml_str),
+            (None, Some(p)) => Self::read(p),
+            (None, None) => Ok(Self::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RULES: &str = r#"
+        [[rule]]
+        name = "payments-time-lag"
+        kind = "time_lag"
+        group = "^payments-"
+        threshold = 300
+        for = 120
+
+        [[rule]]
+        name = "data-loss"
+        kind = "data_loss"
+    "#;
+
+    #[test]
+    fn should_parse_rules() {
+        let rules = AlertRules::parse(RULES).unwrap().rules;
+        assert_eq!(rules.len(), 2);
+
+        assert_eq!(rules[0].kind, AlertKind::TimeLag);
+        assert_eq!(rules[0].for_duration, Duration::minutes(2));
+        assert!(rules[0].matches("payments-eu", "orders"));
+        assert!(!rules[0].matches("billing", "orders"));
+        assert!(!rules[0].is_met(300.0));
+        assert!(rules[0].is_met(300.5));
+
+        assert_eq!(rules[1].kind, AlertKind::DataLoss);
+        assert_eq!(rules[1].for_duration, Duration::zero());
+        assert!(rules[1].matches("billing", "orders"));
+        assert!(!rules[1].is_met(0.0));
+        assert!(rules[1].is_met(1.0));
+    }
+
+    #[test]
+    fn should_reject_invalid_rules() {
+        assert!(matches!(
+            AlertRules::parse("[[rule]]\nname = \"a\"\nkind = \"offset_lag\""),
+            Err(AlertRulesError::MissingThreshold(_))
+        ));
+        assert!(matches!(
+            AlertRules::parse(
+                "[[rule]]\nname = \"a\"\nkind = \"data_loss\"\n[[rule]]\nname = \"a\"\nkind = \"data_loss\""
+            ),
+            Err(AlertRulesError::DuplicateName(_))
+        ));
+        assert!(matches!(
+            AlertRules::parse("[[rule]]\nname = \"a\"\nkind = \"data_loss\"\ngroup = \"(\""),
+            Err(AlertRulesError::Regex(_))
+        ));
+        assert!(matches!(
+            AlertRules::parse("[[rule]]\nname = \"a\"\nkind = \"lag\""),
+            Err(AlertRulesError::Toml(_))
+        ));
+        assert!(matches!(
+            AlertRules::parse(
+                "[[rule]]\nname = \"a\"\nkind = \"data_loss\"\nfor = 9223372036854775807"
+            ),
+            Err(AlertRulesError::InvalidFor(_, _))
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use reqwest::{Client, Url};
+use serde::Serialize;
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::cluster_status::ClusterStatusRegister;
+
+use super::register::Notification;
+
+/// Amount of [`Notification`]s queued for delivery, overall and to each webhook:
+/// when full, new ones are dropped.
+pub(super) const NOTIFICATIONS_QUEUE_CAPACITY: usize = 1024;
+
+/// Attempts to deliver a [`Notification`] to a webhook, before giving up.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry: it doubles at every retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of the `POST` request to the webhooks.
+#[derive(Debug, Serialize)]
+struct Payload {
+    cluster_id: String,
+
+    #[serde(flatten)]
+    notification: Notification,
+}
+
+/// Spawn the task delivering [`Notification`]s to all the `webhooks`.
+///
+/// Each webhook has its own queue and task, delivering in order: one that is slow or down
+/// doesn't hold up the others. Each delivery that fails (connection error, or non-2xx response)
+/// is retried with exponential backoff.
+pub(super) fn spawn(
+    webhooks: Vec<Url>,
+    mut notifications_rx: mpsc::Receiver<Notification>,
+    cs_reg: Arc<ClusterStatusRegister>,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to build webhooks HTTP client (fatal)");
+
+        let (queues, joins): (Vec<_>, Vec<_>) = webhooks
+            .into_iter()
+            .map(|url| {
+                let (tx, rx) = mpsc::channel(NOTIFICATIONS_QUEUE_CAPACITY);
+                let join = spawn_webhook(url.clone(), rx, client.clone(), shutdown_token.clone());
+                ((tx, url), join)
+            })
+            .unzip();
+
+        loop {
+            let notification = tokio::select! {
+                Some(n) = notifications_rx.recv() => n,
+                _ = shutdown_token.cancelled() => break,
+                else => break,
+            };
+
+            let payload = Arc::new(Payload {
+                cluster_id: cs_reg.get_cluster_id().await,
+                notification,
+            });
+            for (tx, url) in queues.iter() {
+                if let Err(e) = tx.try_send(payload.clone()) {
+                    warn!("Dropping alert notification to '{url}': {e}");
+                }
+            }
+        }
+
+        drop(queues);
+        for j in joins {
+            if let Err(e) = j.await {
+                error!("Webhook task failed: {e}");
+            }
+        }
+        info!("Shutting down");
+    })
+}
+
+/// Spawn the task delivering the [`Payload`]s queued for the webhook at `url`, in order.
+fn spawn_webhook(
+    url: Url,
+    mut payloads_rx: mpsc::Receiver<Arc<Payload>>,
+    client: Client,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let payload = tokio::select! {
+                Some(p) = payloads_rx.recv() => p,
+                _ = shutdown_token.cancelled() => break,
+                else => break,
+            };
+
+            tokio::select! {
+                _ = deliver(&client, &url, &payload) => {},
+                _ = shutdown_token.cancelled() => break,
+            }
+        }
+    })
+}
+
+async fn deliver(client: &Client, url: &Url, payload: &Payload) {
+    let mut backoff = INITIAL_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let res =
+            client.post(url.clone()).json(payload).send().await.and_then(|r| r.error_for_status());
+        match res {
+            Ok(_) => {
+                debug!("Delivered alert notification to '{url}'");
+                return;
+            },
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                debug!("Failed to deliver alert notification to '{url}' (attempt {attempt}): {e}");
+                sleep(backoff).await;
+                backoff *= 2;
+            },
+            Err(e) => {
+                error!("Failed to deliver alert notification to '{url}', giving up after {MAX_ATTEMPTS} attempts: {e}");
+            },
+        }
+    }
+}
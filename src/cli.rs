@@ -1,10 +1,35 @@
-use std::net::{IpAddr, SocketAddr};
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    time::Duration,
+};
 
 use clap::{ArgGroup, Parser};
 use rdkafka::ClientConfig;
+use reqwest::Url;
+
+use crate::clusters::ClusterConfig;
+use crate::file_sink::{FileSinkConfig, FileSinkFormat};
+use crate::influx::InfluxConfig;
+use crate::kafka_sink::{KafkaSinkConfig, SinkEncoding, SinkMode};
+use crate::otlp::{OtlpConfig, OtlpProtocol};
+use crate::prometheus_metrics::{
+    ConsumerMetricsLevel, MetricsProfile, OwnerLabels, SeriesDropPolicy,
+};
+use crate::remote_write::{RemoteWriteAuth, RemoteWriteConfig};
+use crate::statsd::{StatsdConfig, StatsdFormat};
 
 use crate::constants::{
-    DEFAULT_HTTP_HOST, DEFAULT_HTTP_PORT, DEFAULT_OFFSETS_HISTORY, DEFAULT_OFFSETS_HISTORY_READY_AT,
+    DEFAULT_CONSUMER_METRICS, DEFAULT_FILE_SINK_FORMAT, DEFAULT_FILE_SINK_RETENTION,
+    DEFAULT_HTTP_HOST, DEFAULT_HTTP_PORT, DEFAULT_INFLUX_INTERVAL, DEFAULT_KAFKA_SINK_ENCODING,
+    DEFAULT_KAFKA_SINK_INTERVAL, DEFAULT_KAFKA_SINK_MODE, DEFAULT_LAG_HISTORY_MAX_SAMPLES,
+    DEFAULT_LAG_HISTORY_RESOLUTION, DEFAULT_LAG_HISTORY_RETENTION, DEFAULT_METRICS_PROFILES,
+    DEFAULT_OFFSETS_HISTORY, DEFAULT_OFFSETS_HISTORY_READY_AT, DEFAULT_OTLP_INTERVAL,
+    DEFAULT_OTLP_PROTOCOL, DEFAULT_OWNER_LABELS, DEFAULT_REMOTE_WRITE_INTERVAL,
+    DEFAULT_REMOTE_WRITE_QUEUE_CAPACITY, DEFAULT_SERIES_DROP_POLICY, DEFAULT_SNAPSHOT_INTERVAL,
+    DEFAULT_SNAPSHOT_MAX_AGE, DEFAULT_STATSD_FORMAT, DEFAULT_STATSD_INTERVAL,
+    DEFAULT_STATSD_MAX_PACKET_SIZE, DEFAULT_STATSD_PREFIX, DEFAULT_STATUS_MIN_DISTANCE,
+    DEFAULT_STATUS_WINDOW,
 };
 
 /// Command Line Interface, defined via the declarative,
@@ -18,12 +43,29 @@ use crate::constants::{
         .args(["verbose", "quiet"]),
 ))]
 pub struct Cli {
+    /// YAML or TOML file setting any of the other arguments, by long name (e.g. 'brokers: kafka:9092').
+    ///
+    /// Arguments given on the command line take precedence. Arguments taking 'K:V' pairs can be
+    /// set as tables (e.g. 'kafka-conf: { security.protocol: SASL_SSL }'), while '--labels-mapping'
+    /// and '--alert-rules' can be set inline as sections, in place of the files they point to.
+    ///
+    /// On SIGHUP, or when the file changes, the log level, '--labels-mapping' and '--alert-rules'
+    /// are applied again: other changes require a restart.
+    #[arg(long = "config", value_name = "FILE", verbatim_doc_comment)]
+    pub config: Option<PathBuf>,
+
     // ------------------------------------------------------------------ Admin Client configuration
     /// Initial Kafka Brokers to connect to (format: 'HOST:PORT,...').
     ///
     /// Equivalent to '--kafka-conf bootstrap.servers:host:port,...'.
-    #[arg(short, long = "brokers", value_name = "BOOTSTRAP_BROKERS")]
-    pub bootstrap_brokers: String,
+    #[arg(
+        short,
+        long = "brokers",
+        value_name = "BOOTSTRAP_BROKERS",
+        required_unless_present = "clusters",
+        conflicts_with = "clusters"
+    )]
+    pub bootstrap_brokers: Option<String>,
 
     /// Client identifier used by the internal Kafka (Admin) Client.
     ///
@@ -47,9 +89,17 @@ pub struct Cli {
     ///
     /// If set, it replaces the value `cluster.id` from the Brokers' configuration.
     /// This can be useful when `cluster.id` is not actually set.
-    #[arg(long = "cluster-id", value_name = "CLUSTER_ID")]
+    #[arg(long = "cluster-id", value_name = "CLUSTER_ID", conflicts_with = "clusters")]
     pub cluster_id: Option<String>,
 
+    /// TOML file listing multiple Kafka Clusters to monitor, instead of '--brokers'.
+    ///
+    /// Each '[[cluster]]' has an 'id' (its 'cluster_id'), 'brokers' and optional 'kafka_conf',
+    /// applied on top of '--kafka-conf'. All other arguments apply to every cluster.
+    /// Each cluster is served under '/clusters/ID/', and all together at '/metrics'.
+    #[arg(long = "clusters", value_name = "FILE", verbatim_doc_comment)]
+    pub clusters: Option<PathBuf>,
+
     /// For each Topic Partition, how much history of offsets to track in memory.
     ///
     /// Offsets data points are collected every 500ms, on average: so, on average,
@@ -81,6 +131,505 @@ pub struct Cli {
     )]
     pub offsets_history_ready_at: f64,
 
+    /// Naming and labelling conventions of the metrics to produce (format: 'PROFILE,...').
+    ///
+    /// Profiles other than 'kommitted' produce metrics compatible with the dashboards and alerts
+    /// built for other tools: enable more than one to produce them side by side.
+    #[arg(
+        long = "metrics-profiles",
+        value_name = "PROFILE,...",
+        value_enum,
+        value_delimiter = ',',
+        default_value = DEFAULT_METRICS_PROFILES,
+        verbatim_doc_comment
+    )]
+    pub metrics_profiles: Vec<MetricsProfile>,
+
+    /// Levels of detail at which consumer metrics are produced (format: 'LEVEL,...').
+    ///
+    /// Metrics for each Topic Partition carry the most information, but are also the most
+    /// numerous: aggregated metrics save having to aggregate them at query time.
+    #[arg(
+        long = "consumer-metrics",
+        value_name = "LEVEL,...",
+        value_enum,
+        value_delimiter = ',',
+        default_value = DEFAULT_CONSUMER_METRICS,
+        verbatim_doc_comment
+    )]
+    pub consumer_metrics: Vec<ConsumerMetricsLevel>,
+
+    /// Granularity of the labels identifying the owner (Member) of a consumed Topic Partition.
+    ///
+    /// Label `member_id` changes at every rebalance of the Consumer Group,
+    /// making the series of consumer partition metrics churn.
+    #[arg(
+        long = "owner-labels",
+        value_name = "GRANULARITY",
+        value_enum,
+        default_value = DEFAULT_OWNER_LABELS,
+        verbatim_doc_comment
+    )]
+    pub owner_labels: OwnerLabels,
+
+    /// Maximum amount of consumed Topic Partitions to produce consumer partition metrics for.
+    ///
+    /// This limits Topic Partitions, not series: each consumed Topic Partition results in one series
+    /// per consumer partition metric, of every metrics profile.
+    /// When exceeded, Topic Partitions are dropped according to `--series-drop-policy`.
+    #[arg(long = "consumer-partitions-limit", value_name = "COUNT", verbatim_doc_comment)]
+    pub consumer_partitions_limit: Option<usize>,
+
+    /// What consumed Topic Partitions to drop first, when `--consumer-partitions-limit` is exceeded.
+    #[arg(
+        long = "series-drop-policy",
+        value_name = "POLICY",
+        value_enum,
+        default_value = DEFAULT_SERIES_DROP_POLICY,
+        verbatim_doc_comment
+    )]
+    pub series_drop_policy: SeriesDropPolicy,
+
+    /// Static label to apply to all metrics and REST data (format: 'LABEL_NAME:LABEL_VAL').
+    ///
+    /// To set multiple labels, use this argument multiple times.
+    #[arg(
+        long = "label",
+        value_name = "LABEL_NAME:LABEL_VAL",
+        value_parser = kv_clap_value_parser,
+        verbatim_doc_comment
+    )]
+    pub static_labels: Vec<KVPair>,
+
+    /// Path to a file mapping Consumer Group and Topic names to extra labels (TOML).
+    ///
+    /// Each rule matches names by regex, and applies its labels to the metrics and REST data:
+    ///
+    ///   [[group]]
+    ///   regex = "^payments-"
+    ///   labels = { team = "payments", tier = "1" }
+    ///
+    /// The file is reloaded whenever it changes.
+    #[arg(long = "labels-mapping", value_name = "FILE", verbatim_doc_comment)]
+    pub labels_mapping: Option<PathBuf>,
+
+    /// Labels mapping set inline, as a section of the configuration file (see '--config').
+    #[arg(skip)]
+    pub labels_mapping_inline: Option<String>,
+
+    /// Path to a file with alert rules to evaluate (TOML).
+    ///
+    /// Each rule matches Consumer Groups and Topics by regex, and alerts when
+    /// its condition is met for at least 'for' seconds:
+    ///
+    ///   [[rule]]
+    ///   name = "payments-time-lag"
+    ///   kind = "time_lag"         # offset_lag, time_lag, no_commits or data_loss
+    ///   group = "^payments-"
+    ///   threshold = 300           # offsets for offset_lag, seconds otherwise
+    ///   for = 120
+    ///
+    /// Active alerts are listed at '/api/v1/alerts'.
+    #[arg(long = "alert-rules", value_name = "FILE", verbatim_doc_comment)]
+    pub alert_rules: Option<PathBuf>,
+
+    /// Alert rules set inline, as a section of the configuration file (see '--config').
+    #[arg(skip)]
+    pub alert_rules_inline: Option<String>,
+
+    /// Webhook URL to POST a JSON payload to, when an alert begins firing or is resolved.
+    ///
+    /// To notify multiple webhooks, use this argument multiple times.
+    /// Failed deliveries are retried, with exponential backoff.
+    #[arg(long = "alert-webhook", value_name = "URL", verbatim_doc_comment)]
+    pub alert_webhooks: Vec<Url>,
+
+    /// Path to a file with Service Level Objectives on the lag of Consumer Groups (TOML).
+    ///
+    /// Each objective matches Consumer Groups (and Topics) by regex: the lag is sampled every
+    /// 10 seconds, and each sample is an SLI event, good if within either 'time_lag' (seconds)
+    /// or 'offset_lag' and the Consumer Group has committed in the last minute (or caught up):
+    ///
+    ///   [[slo]]
+    ///   name = "payments-time-lag"
+    ///   group = "^payments-"
+    ///   time_lag = 30
+    ///   objective = 99.9          # percentage of good events
+    ///   window = 28               # days
+    ///
+    /// SLI events are persisted in the snapshot (see '--snapshot-path').
+    #[arg(long = "slo-objectives", value_name = "FILE", verbatim_doc_comment)]
+    pub slo_objectives: Option<PathBuf>,
+
+    /// For each Topic Partition consumed by a Consumer Group, how many offset commits to evaluate its status on.
+    ///
+    /// The status (OK, WARN, ERR, STOP, STALL) follows the Burrow consumer lag evaluation rules,
+    /// applied to this sliding window of the most recent offset commits.
+    #[arg(
+        long = "status-window",
+        value_name = "COMMITS",
+        default_value = DEFAULT_STATUS_WINDOW,
+        value_parser = clap::value_parser!(u64).range(2..),
+        verbatim_doc_comment
+    )]
+    pub status_window: u64,
+
+    /// Minimum time between the offset commits in the status window, in seconds.
+    ///
+    /// The latest commit in the window gets replaced by the next one, until it's this far from the one
+    /// before it: the window of a consumer committing often then still spans a meaningful time.
+    #[arg(
+        long = "status-min-distance",
+        value_name = "SECONDS",
+        default_value = DEFAULT_STATUS_MIN_DISTANCE,
+        verbatim_doc_comment
+    )]
+    pub status_min_distance: u64,
+
+    /// For each Consumer Group, how much history of lag to keep in memory, in seconds.
+    ///
+    /// The history is sampled every `--lag-history-resolution`, for each Topic Partition
+    /// consumed and as an aggregate for the whole Consumer Group, and can be queried at
+    /// `/api/v1/groups/{group}/history`.
+    #[arg(
+        long = "lag-history-retention",
+        value_name = "SECONDS",
+        default_value = DEFAULT_LAG_HISTORY_RETENTION,
+        verbatim_doc_comment
+    )]
+    pub lag_history_retention: u64,
+
+    /// How often to sample the lag history, in seconds.
+    #[arg(
+        long = "lag-history-resolution",
+        value_name = "SECONDS",
+        default_value = DEFAULT_LAG_HISTORY_RESOLUTION,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub lag_history_resolution: u64,
+
+    /// Maximum amount of samples to keep in memory for the lag history, across all Consumer Groups.
+    ///
+    /// Each sample takes approximately 40 bytes: this limits the memory used by the lag history.
+    /// Once the limit is reached, the history of additional Topic Partitions is not tracked.
+    #[arg(
+        long = "lag-history-max-samples",
+        value_name = "SAMPLES",
+        default_value = DEFAULT_LAG_HISTORY_MAX_SAMPLES,
+        verbatim_doc_comment
+    )]
+    pub lag_history_max_samples: usize,
+
+    /// File where to periodically persist a snapshot of the internal state.
+    ///
+    /// The snapshot contains the offsets history of each Topic Partition, the lag of each
+    /// Consumer Group and the cluster status. If the file exists at launch, and it is not
+    /// older than `--snapshot-max-age`, the internal state is restored from it: this avoids
+    /// having to wait for `--history-ready-at` again after a restart.
+    ///
+    /// The file is written atomically: it's never left partially written.
+    #[arg(long = "snapshot-path", value_name = "FILE", verbatim_doc_comment)]
+    pub snapshot_path: Option<PathBuf>,
+
+    /// How often to write a snapshot to `--snapshot-path`, in seconds.
+    ///
+    /// A last snapshot is also written at shutdown.
+    #[arg(
+        long = "snapshot-interval",
+        value_name = "SECONDS",
+        default_value = DEFAULT_SNAPSHOT_INTERVAL,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub snapshot_interval: u64,
+
+    /// Maximum age of the snapshot at `--snapshot-path`, in seconds, for it to be restored at launch.
+    ///
+    /// An older snapshot is ignored, as it would provide stale data.
+    #[arg(
+        long = "snapshot-max-age",
+        value_name = "SECONDS",
+        default_value = DEFAULT_SNAPSHOT_MAX_AGE,
+        verbatim_doc_comment
+    )]
+    pub snapshot_max_age: u64,
+
+    /// Kafka topic where to produce the lag of each Consumer Group, for each Topic Partition.
+    ///
+    /// Records are keyed by Consumer Group, and carry offset, lag, time lag, owner
+    /// and watermarks. If not set, lag is not produced to Kafka.
+    #[arg(long = "kafka-sink-topic", value_name = "TOPIC", verbatim_doc_comment)]
+    pub kafka_sink_topic: Option<String>,
+
+    /// Kafka Brokers to produce the lag records to (format: 'HOST:PORT,...').
+    ///
+    /// Defaults to the monitored Kafka Cluster ('--brokers').
+    #[arg(
+        long = "kafka-sink-brokers",
+        value_name = "BOOTSTRAP_BROKERS",
+        requires = "kafka_sink_topic",
+        verbatim_doc_comment
+    )]
+    pub kafka_sink_brokers: Option<String>,
+
+    /// Additional configuration used by the Kafka sink Producer (format: 'CONF_KEY:CONF_VAL').
+    ///
+    /// These are applied on top of '--kafka-conf': to set multiple configurations keys,
+    /// use this argument multiple times.
+    #[arg(
+        long = "kafka-sink-conf",
+        value_name = "CONF_KEY:CONF_VAL",
+        value_parser = kv_clap_value_parser,
+        requires = "kafka_sink_topic",
+        verbatim_doc_comment
+    )]
+    pub kafka_sink_config: Vec<KVPair>,
+
+    /// Encoding of the lag records produced to '--kafka-sink-topic'.
+    #[arg(
+        long = "kafka-sink-encoding",
+        value_name = "ENCODING",
+        value_enum,
+        default_value = DEFAULT_KAFKA_SINK_ENCODING,
+        verbatim_doc_comment
+    )]
+    pub kafka_sink_encoding: SinkEncoding,
+
+    /// When to produce lag records to '--kafka-sink-topic'.
+    #[arg(
+        long = "kafka-sink-mode",
+        value_name = "MODE",
+        value_enum,
+        default_value = DEFAULT_KAFKA_SINK_MODE,
+        verbatim_doc_comment
+    )]
+    pub kafka_sink_mode: SinkMode,
+
+    /// How often to produce lag records to '--kafka-sink-topic', in seconds, in 'periodic' mode.
+    #[arg(
+        long = "kafka-sink-interval",
+        value_name = "SECONDS",
+        default_value = DEFAULT_KAFKA_SINK_INTERVAL,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub kafka_sink_interval: u64,
+
+    /// Directory where to write every lag update, for offline analysis.
+    ///
+    /// Each offset commit is written as a record carrying offset, lag, time lag, owner
+    /// and watermarks. Files are named 'kommitted-lag-TIMESTAMP.FORMAT', with a '.partial'
+    /// suffix while being written. If not set, lag is not written to files.
+    #[arg(long = "file-sink-dir", value_name = "DIR", verbatim_doc_comment)]
+    pub file_sink_dir: Option<PathBuf>,
+
+    /// Format of the files written to '--file-sink-dir'.
+    #[arg(
+        long = "file-sink-format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = DEFAULT_FILE_SINK_FORMAT,
+        verbatim_doc_comment
+    )]
+    pub file_sink_format: FileSinkFormat,
+
+    /// Rotate the file being written to '--file-sink-dir' once it reaches this size, in bytes.
+    #[arg(
+        long = "file-sink-max-size",
+        value_name = "BYTES",
+        requires = "file_sink_dir",
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub file_sink_max_size: Option<u64>,
+
+    /// Rotate the file being written to '--file-sink-dir' once it's this old, in seconds.
+    #[arg(
+        long = "file-sink-max-age",
+        value_name = "SECONDS",
+        requires = "file_sink_dir",
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub file_sink_max_age: Option<u64>,
+
+    /// Amount of complete files to keep in '--file-sink-dir': the oldest are deleted.
+    #[arg(
+        long = "file-sink-retention",
+        value_name = "FILES",
+        default_value = DEFAULT_FILE_SINK_RETENTION,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub file_sink_retention: u64,
+
+    /// OpenTelemetry collector endpoint to push all metrics to, over OTLP.
+    ///
+    /// For the 'http' protocol, this is the full URL of the metrics endpoint
+    /// (e.g. 'http://collector:4318/v1/metrics'); for 'grpc', the collector address
+    /// (e.g. 'http://collector:4317'). If not set, metrics are not pushed over OTLP.
+    #[arg(long = "otlp-endpoint", value_name = "URL", verbatim_doc_comment)]
+    pub otlp_endpoint: Option<Url>,
+
+    /// Transport protocol used to push metrics to '--otlp-endpoint'.
+    #[arg(
+        long = "otlp-protocol",
+        value_name = "PROTOCOL",
+        value_enum,
+        default_value = DEFAULT_OTLP_PROTOCOL,
+        verbatim_doc_comment
+    )]
+    pub otlp_protocol: OtlpProtocol,
+
+    /// How often to push metrics to '--otlp-endpoint', in seconds.
+    #[arg(
+        long = "otlp-interval",
+        value_name = "SECONDS",
+        default_value = DEFAULT_OTLP_INTERVAL,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub otlp_interval: u64,
+
+    /// Header sent with every push to '--otlp-endpoint' (format: 'NAME:VALUE').
+    ///
+    /// To send multiple headers (e.g. for authentication), use this argument multiple times.
+    #[arg(
+        long = "otlp-header",
+        value_name = "NAME:VALUE",
+        value_parser = kv_clap_value_parser,
+        requires = "otlp_endpoint",
+        verbatim_doc_comment
+    )]
+    pub otlp_headers: Vec<KVPair>,
+
+    /// Prometheus remote write endpoint to push all metrics to.
+    ///
+    /// Useful when Prometheus can't reach the HTTP Service to scrape '/metrics'.
+    /// If not set, metrics are not pushed via remote write.
+    #[arg(long = "remote-write-url", value_name = "URL", verbatim_doc_comment)]
+    pub remote_write_url: Option<Url>,
+
+    /// How often to push metrics to '--remote-write-url', in seconds.
+    #[arg(
+        long = "remote-write-interval",
+        value_name = "SECONDS",
+        default_value = DEFAULT_REMOTE_WRITE_INTERVAL,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub remote_write_interval: u64,
+
+    /// Basic authentication to '--remote-write-url' (format: 'USERNAME:PASSWORD').
+    #[arg(
+        long = "remote-write-basic-auth",
+        value_name = "USERNAME:PASSWORD",
+        value_parser = kv_clap_value_parser,
+        requires = "remote_write_url",
+        conflicts_with = "remote_write_bearer_token",
+        verbatim_doc_comment
+    )]
+    pub remote_write_basic_auth: Option<KVPair>,
+
+    /// Bearer token authentication to '--remote-write-url'.
+    #[arg(
+        long = "remote-write-bearer-token",
+        value_name = "TOKEN",
+        requires = "remote_write_url",
+        verbatim_doc_comment
+    )]
+    pub remote_write_bearer_token: Option<String>,
+
+    /// Maximum amount of requests queued for sending to '--remote-write-url'.
+    ///
+    /// Failed requests are retried with exponential backoff: while that happens, new requests
+    /// are queued. Once the queue is full, new requests are dropped.
+    #[arg(
+        long = "remote-write-queue-capacity",
+        value_name = "REQUESTS",
+        default_value = DEFAULT_REMOTE_WRITE_QUEUE_CAPACITY,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub remote_write_queue_capacity: u64,
+
+    /// InfluxDB write endpoint to push the lag to, in line protocol.
+    ///
+    /// The full URL, including database or bucket: e.g. 'http://influxdb:8086/write?db=DB' for v1,
+    /// 'http://influxdb:8086/api/v2/write?org=ORG&bucket=BUCKET' for v2.
+    /// The same lines are always served at '/metrics/influx'; if not set, they are not pushed.
+    #[arg(long = "influx-url", value_name = "URL", verbatim_doc_comment)]
+    pub influx_url: Option<Url>,
+
+    /// Token authentication to '--influx-url' (sent as 'Authorization: Token TOKEN').
+    #[arg(
+        long = "influx-token",
+        value_name = "TOKEN",
+        requires = "influx_url",
+        verbatim_doc_comment
+    )]
+    pub influx_token: Option<String>,
+
+    /// How often to push the lag to '--influx-url', in seconds.
+    #[arg(
+        long = "influx-interval",
+        value_name = "SECONDS",
+        default_value = DEFAULT_INFLUX_INTERVAL,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub influx_interval: u64,
+
+    /// StatsD server to emit lag gauges to, over UDP (format: 'HOST:PORT').
+    ///
+    /// Per-partition and aggregated lag is emitted, labelled like the equivalent metrics
+    /// served by '/metrics'. If not set, no StatsD gauges are emitted.
+    #[arg(long = "statsd-address", value_name = "HOST:PORT", verbatim_doc_comment)]
+    pub statsd_address: Option<String>,
+
+    /// Line format of the gauges emitted to '--statsd-address'.
+    #[arg(
+        long = "statsd-format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = DEFAULT_STATSD_FORMAT,
+        verbatim_doc_comment
+    )]
+    pub statsd_format: StatsdFormat,
+
+    /// Prefix of the names of the gauges emitted to '--statsd-address'.
+    #[arg(
+        long = "statsd-prefix",
+        value_name = "PREFIX",
+        default_value = DEFAULT_STATSD_PREFIX,
+        verbatim_doc_comment
+    )]
+    pub statsd_prefix: String,
+
+    /// How often to flush gauges to '--statsd-address', in seconds.
+    #[arg(
+        long = "statsd-interval",
+        value_name = "SECONDS",
+        default_value = DEFAULT_STATSD_INTERVAL,
+        value_parser = clap::value_parser!(u64).range(1..),
+        verbatim_doc_comment
+    )]
+    pub statsd_interval: u64,
+
+    /// Maximum size of a packet sent to '--statsd-address', in bytes.
+    ///
+    /// Gauges are packed in as few packets as possible: raise this when
+    /// the network supports larger datagrams.
+    #[arg(
+        long = "statsd-max-packet-size",
+        value_name = "BYTES",
+        default_value = DEFAULT_STATSD_MAX_PACKET_SIZE,
+        value_parser = clap::value_parser!(u64).range(64..=65507),
+        verbatim_doc_comment
+    )]
+    pub statsd_max_packet_size: u64,
+
     /// Host address to listen on for HTTP requests.
     ///
     /// Supports both IPv4 and IPv6 addresses.
@@ -91,6 +640,41 @@ pub struct Cli {
     #[arg(long, default_value = DEFAULT_HTTP_PORT, verbatim_doc_comment)]
     pub port: u16,
 
+    /// Port to listen on for KEDA External Scaler (gRPC) requests.
+    ///
+    /// If not set, the KEDA External Scaler is disabled.
+    /// It listens on the same '--host' as the HTTP server.
+    #[arg(
+        long = "keda-scaler-port",
+        value_name = "PORT",
+        conflicts_with = "clusters",
+        verbatim_doc_comment
+    )]
+    pub keda_scaler_port: Option<u16>,
+
+    /// Port to listen on for Kubernetes External Metrics API (HTTPS) requests.
+    ///
+    /// If not set, the External Metrics API is disabled.
+    /// It listens on the same '--host' as the HTTP server, and requires
+    /// '--external-metrics-tls-cert' and '--external-metrics-tls-key'.
+    #[arg(
+        long = "external-metrics-port",
+        value_name = "PORT",
+        conflicts_with = "clusters",
+        requires = "external_metrics_tls_cert",
+        requires = "external_metrics_tls_key",
+        verbatim_doc_comment
+    )]
+    pub external_metrics_port: Option<u16>,
+
+    /// Certificate (chain) served by the External Metrics API, in PEM format.
+    #[arg(long = "external-metrics-tls-cert", value_name = "FILE", verbatim_doc_comment)]
+    pub external_metrics_tls_cert: Option<PathBuf>,
+
+    /// Private key of the certificate served by the External Metrics API, in PEM format.
+    #[arg(long = "external-metrics-tls-key", value_name = "FILE", verbatim_doc_comment)]
+    pub external_metrics_tls_key: Option<PathBuf>,
+
     /// Verbose logging.
     ///
     /// * none    = 'WARN'
@@ -118,22 +702,162 @@ impl Cli {
         self.verbose as i8 - self.quiet as i8
     }
 
+    /// The `metrics_profiles`, each only once (in the order first given).
+    pub fn unique_metrics_profiles(&self) -> Vec<MetricsProfile> {
+        let mut profiles = Vec::with_capacity(self.metrics_profiles.len());
+        for p in self.metrics_profiles.iter() {
+            if !profiles.contains(p) {
+                profiles.push(*p);
+            }
+        }
+        profiles
+    }
+
     pub fn listen_on(&self) -> SocketAddr {
         SocketAddr::from((self.host, self.port))
     }
 
+    pub fn keda_scaler_listen_on(&self) -> Option<SocketAddr> {
+        self.keda_scaler_port.map(|port| SocketAddr::from((self.host, port)))
+    }
+
+    pub fn external_metrics_listen_on(&self) -> Option<SocketAddr> {
+        self.external_metrics_port.map(|port| SocketAddr::from((self.host, port)))
+    }
+
+    pub fn snapshot_interval(&self) -> Duration {
+        Duration::from_secs(self.snapshot_interval)
+    }
+
+    pub fn snapshot_max_age(&self) -> Duration {
+        Duration::from_secs(self.snapshot_max_age)
+    }
+
+    pub fn lag_history_retention(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.lag_history_retention as i64)
+    }
+
+    pub fn lag_history_resolution(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.lag_history_resolution as i64)
+    }
+
+    pub fn status_min_distance(&self) -> chrono::Duration {
+        chrono::Duration::try_seconds(self.status_min_distance as i64)
+            .unwrap_or(chrono::Duration::MAX)
+    }
+
+    /// Configuration of the Kafka (Admin) Client connecting to the monitored Kafka Cluster.
     pub fn build_client_config(&self) -> ClientConfig {
+        self.build_client_config_with(self.bootstrap_brokers.clone().unwrap_or_default(), [])
+    }
+
+    /// Configuration of the Kafka (Admin) Client connecting to one of the clusters in '--clusters'.
+    ///
+    /// The cluster's own configuration is applied on top of '--kafka-conf'.
+    pub fn build_cluster_client_config(&self, cluster: &ClusterConfig) -> ClientConfig {
+        self.build_client_config_with(cluster.brokers.clone(), cluster.kafka_conf.clone())
+    }
+
+    fn build_client_config_with(
+        &self,
+        brokers: String,
+        extra_config: impl IntoIterator<Item = KVPair>,
+    ) -> ClientConfig {
         let mut config = ClientConfig::new();
-        config
-            .set("bootstrap.servers", self.bootstrap_brokers.clone())
-            .set("client.id", self.client_id.clone());
-        for cfg in &self.kafka_config {
-            config.set(cfg.0.clone(), cfg.1.clone());
+        config.set("bootstrap.servers", brokers).set("client.id", self.client_id.clone());
+        for cfg in self.kafka_config.iter().cloned().chain(extra_config) {
+            config.set(cfg.0, cfg.1);
         }
 
         trace!("Created:\n{:#?}", config);
         config
     }
+
+    /// Configuration of the file sink, if '--file-sink-dir' is set.
+    pub fn file_sink_config(&self) -> Option<FileSinkConfig> {
+        Some(FileSinkConfig {
+            dir: self.file_sink_dir.clone()?,
+            format: self.file_sink_format,
+            max_size: self.file_sink_max_size,
+            max_age: self.file_sink_max_age.map(Duration::from_secs),
+            retention: self.file_sink_retention as usize,
+        })
+    }
+
+    /// Configuration of the OTLP export, if '--otlp-endpoint' is set.
+    pub fn otlp_config(&self) -> Option<OtlpConfig> {
+        Some(OtlpConfig {
+            endpoint: self.otlp_endpoint.clone()?,
+            protocol: self.otlp_protocol,
+            interval: Duration::from_secs(self.otlp_interval),
+            headers: self.otlp_headers.clone(),
+        })
+    }
+
+    /// Configuration of the remote write, if '--remote-write-url' is set.
+    pub fn remote_write_config(&self) -> Option<RemoteWriteConfig> {
+        let auth = match (
+            self.remote_write_basic_auth.as_ref(),
+            self.remote_write_bearer_token.as_ref(),
+        ) {
+            (Some((username, password)), _) => Some(RemoteWriteAuth::Basic {
+                username: username.clone(),
+                password: password.clone(),
+            }),
+            (None, Some(token)) => Some(RemoteWriteAuth::Bearer(token.clone())),
+            (None, None) => None,
+        };
+
+        Some(RemoteWriteConfig {
+            url: self.remote_write_url.clone()?,
+            auth,
+            interval: Duration::from_secs(self.remote_write_interval),
+            queue_capacity: self.remote_write_queue_capacity as usize,
+        })
+    }
+
+    /// Configuration of the InfluxDB push, if '--influx-url' is set.
+    pub fn influx_config(&self) -> Option<InfluxConfig> {
+        Some(InfluxConfig {
+            url: self.influx_url.clone()?,
+            token: self.influx_token.clone(),
+            interval: Duration::from_secs(self.influx_interval),
+        })
+    }
+
+    /// Configuration of the StatsD emission, if '--statsd-address' is set.
+    pub fn statsd_config(&self) -> Option<StatsdConfig> {
+        Some(StatsdConfig {
+            address: self.statsd_address.clone()?,
+            format: self.statsd_format,
+            prefix: self.statsd_prefix.clone(),
+            interval: Duration::from_secs(self.statsd_interval),
+            max_packet_size: self.statsd_max_packet_size as usize,
+        })
+    }
+
+    /// Configuration of the Kafka sink, if '--kafka-sink-topic' is set.
+    ///
+    /// The Producer configuration is the `client_config` of the monitored Kafka Cluster, with
+    /// '--kafka-sink-brokers' and '--kafka-sink-conf' applied on top.
+    pub fn kafka_sink_config(&self, mut client_config: ClientConfig) -> Option<KafkaSinkConfig> {
+        let topic = self.kafka_sink_topic.clone()?;
+
+        if let Some(brokers) = self.kafka_sink_brokers.as_ref() {
+            client_config.set("bootstrap.servers", brokers.clone());
+        }
+        for cfg in &self.kafka_sink_config {
+            client_config.set(cfg.0.clone(), cfg.1.clone());
+        }
+
+        Some(KafkaSinkConfig {
+            client_config,
+            topic,
+            encoding: self.kafka_sink_encoding,
+            mode: self.kafka_sink_mode,
+            interval: Duration::from_secs(self.kafka_sink_interval),
+        })
+    }
 }
 
 /// A simple (key,value) pair of `String`s, useful to be parsed from arguments via [`kv_clap_value_parser`].
@@ -161,3 +885,42 @@ fn percent_clap_value_parser(percent_str: &str) -> Result<f64, String> {
 
     Ok(percent)
 }
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn dedupe_metrics_profiles() {
+        let cli = Cli::try_parse_from([
+            "kommitted",
+            "--brokers",
+            "kafka:9092",
+            "--metrics-profiles",
+            "kafka-exporter,kommitted,kafka-exporter",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.unique_metrics_profiles(),
+            [MetricsProfile::KafkaExporter, MetricsProfile::Kommitted]
+        );
+    }
+
+    #[test]
+    fn reject_zero_intervals() {
+        for flag in [
+            "--lag-history-resolution",
+            "--snapshot-interval",
+            "--kafka-sink-interval",
+            "--otlp-interval",
+            "--remote-write-interval",
+            "--statsd-interval",
+            "--influx-interval",
+        ] {
+            let res = Cli::try_parse_from(["kommitted", "--brokers", "kafka:9092", flag, "0"]);
+            assert_eq!(res.unwrap_err().kind(), clap::error::ErrorKind::ValueValidation, "{flag}");
+        }
+    }
+}
@@ -1,20 +1,27 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use prometheus::{
     register_histogram_with_registry, register_int_gauge_with_registry, Histogram, IntGauge,
     Registry,
 };
-use rdkafka::{admin::AdminClient, client::DefaultClientContext, metadata::Metadata, ClientConfig};
+use rdkafka::{
+    admin::{AdminClient, AdminOptions, OwnedResourceSpecifier, ResourceSpecifier},
+    client::DefaultClientContext,
+    error::KafkaResult,
+    metadata::Metadata,
+    ClientConfig,
+};
+use serde::{Deserialize, Serialize};
 use tokio::{
     sync::mpsc,
-    task::JoinHandle,
+    task::{block_in_place, JoinHandle},
     time::{interval, Duration},
 };
 use tokio_util::sync::CancellationToken;
 
 use crate::constants::{DEFAULT_CLUSTER_ID, KONSUMER_OFFSETS_DATA_TOPIC};
 use crate::internals::Emitter;
-use crate::kafka_types::{Broker, TopicPartitionsStatus};
+use crate::kafka_types::{Broker, TopicConfig, TopicPartitionsStatus};
 
 const CHANNEL_SIZE: usize = 5;
 
@@ -22,13 +29,14 @@ const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
 const FETCH_INTERVAL: Duration = Duration::from_secs(60);
 
 const MET_FETCH_NAME: &str = "cluster_status_emitter_fetch_time_milliseconds";
-const MET_FETCH_HELP: &str = "Time (ms) taken to fetch cluster status metadata";
+const MET_FETCH_HELP: &str =
+    "Time (ms) taken to fetch cluster status metadata and topics configuration";
 const MET_CH_CAP_NAME: &str = "cluster_status_emitter_channel_capacity";
 const MET_CH_CAP_HELP: &str =
     "Capacity of internal channel used to send cluster status metadata to rest of the service";
 
 /// This is a `Send`-able struct to carry Kafka Cluster status across thread boundaries.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Serialize, Deserialize)]
 pub struct ClusterStatus {
     /// Cluster identifier, defined as `cluster.id` in Brokers' configuration.
     /// It will be `__none__` if not set on Brokers.
@@ -132,10 +140,17 @@ impl Emitter for ClusterStatusEmitter {
             loop {
                 // Fetch metadata and update timer metric
                 let timer = metric_fetch.start_timer();
-                let res_status =
+                let mut res_status = block_in_place(|| {
                     admin_client.inner().fetch_metadata(None, FETCH_TIMEOUT).map(|m| {
                         Self::Emitted::from(admin_client.inner().fetch_cluster_id(FETCH_TIMEOUT), m)
-                    });
+                    })
+                });
+                if let Ok(status) = res_status.as_mut() {
+                    if let Err(e) = describe_topic_configs(&admin_client, &mut status.topics).await
+                    {
+                        warn!("Failed to describe topics configuration: {e}");
+                    }
+                }
                 timer.observe_duration();
 
                 match res_status {
@@ -165,3 +180,40 @@ impl Emitter for ClusterStatusEmitter {
         (rx, join_handle)
     }
 }
+
+/// Describe the configuration of the given Topics, to set their [`TopicConfig`].
+///
+/// Topics whose configuration can't be described are left with their current [`TopicConfig`].
+async fn describe_topic_configs(
+    admin_client: &AdminClient<DefaultClientContext>,
+    topics: &mut [TopicPartitionsStatus],
+) -> KafkaResult<()> {
+    if topics.is_empty() {
+        return Ok(());
+    }
+
+    let specifiers: Vec<ResourceSpecifier> =
+        topics.iter().map(|t| ResourceSpecifier::Topic(&t.name)).collect();
+    let opts = AdminOptions::new().request_timeout(Some(FETCH_TIMEOUT));
+    let results = admin_client.describe_configs(&specifiers, &opts).await?;
+
+    let mut configs: HashMap<String, TopicConfig> = HashMap::with_capacity(results.len());
+    for res in results {
+        match res {
+            Ok(cr) => {
+                if let OwnedResourceSpecifier::Topic(name) = &cr.specifier {
+                    configs.insert(name.clone(), TopicConfig::from(&cr));
+                }
+            },
+            Err(e) => warn!("Failed to describe topic configuration: {e}"),
+        }
+    }
+
+    for t in topics.iter_mut() {
+        if let Some(config) = configs.remove(&t.name) {
+            t.config = config;
+        }
+    }
+
+    Ok(())
+}
@@ -5,7 +5,7 @@ mod register;
 use std::sync::Arc;
 
 // Exports
-pub use emitter::ClusterStatusEmitter;
+pub use emitter::{ClusterStatus, ClusterStatusEmitter};
 pub use register::ClusterStatusRegister;
 
 // Imports
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use prometheus::{
     register_int_gauge_vec_with_registry, register_int_gauge_with_registry, IntGauge, IntGaugeVec,
@@ -10,7 +10,7 @@ use super::emitter::ClusterStatus;
 
 use crate::constants::DEFAULT_CLUSTER_ID;
 use crate::internals::Awaitable;
-use crate::kafka_types::{Broker, TopicPartition};
+use crate::kafka_types::{Broker, TopicConfig, TopicPartition};
 use crate::prometheus_metrics::LABEL_TOPIC;
 
 const MET_BROKERS_TOT_NAME: &str = "cluster_brokers_total";
@@ -29,6 +29,7 @@ const MET_TOPIC_PARTITIONS_TOT_HELP: &str = "Topic's Partitions currently in clu
 #[derive(Debug)]
 pub struct ClusterStatusRegister {
     latest_status: Arc<RwLock<Option<ClusterStatus>>>,
+    cluster_id_override: Option<String>,
 
     // Prometheus Metrics
     metric_brokers: IntGauge,
@@ -45,6 +46,7 @@ impl ClusterStatusRegister {
     ) -> Self {
         let csr = Self {
             latest_status: Arc::new(RwLock::new(None)),
+            cluster_id_override: cluster_id_override.clone(),
             metric_brokers: register_int_gauge_with_registry!(
                 MET_BROKERS_TOT_NAME,
                 MET_BROKERS_TOT_HELP,
@@ -132,6 +134,34 @@ impl ClusterStatusRegister {
         csr
     }
 
+    /// Latest [`ClusterStatus`], if any has been received yet.
+    pub async fn get_status(&self) -> Option<ClusterStatus> {
+        self.latest_status.read().await.clone()
+    }
+
+    /// Restore a previously known [`ClusterStatus`].
+    ///
+    /// This is ignored if a [`ClusterStatus`] has already been received from the Kafka cluster,
+    /// as that is assumed to be more recent. Returns `true` if the given status was restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `cs` - [`ClusterStatus`] to restore, usually read from a snapshot
+    pub async fn restore_status(&self, mut cs: ClusterStatus) -> bool {
+        let mut w_guard = self.latest_status.write().await;
+        if w_guard.is_some() {
+            return false;
+        }
+
+        // Override cluster identifier, if present
+        if let Some(c_id_over) = &self.cluster_id_override {
+            cs.id = c_id_over.to_string();
+        }
+
+        *w_guard = Some(cs);
+        true
+    }
+
     /// Current identifier of the Kafka cluster.
     pub async fn get_cluster_id(&self) -> String {
         match &*(self.latest_status.read().await) {
@@ -182,6 +212,14 @@ impl ClusterStatusRegister {
         }
     }
 
+    /// Current [`TopicConfig`] of each Topic in the Kafka cluster, indexed by Topic name.
+    pub async fn get_topic_configs(&self) -> HashMap<String, TopicConfig> {
+        match &*(self.latest_status.read().await) {
+            None => HashMap::new(),
+            Some(cs) => cs.topics.iter().map(|t| (t.name.clone(), t.config.clone())).collect(),
+        }
+    }
+
     /// Current Brokers constituting the Kafka cluster.
     #[allow(unused)]
     pub async fn get_brokers(&self) -> Vec<Broker> {
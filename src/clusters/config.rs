@@ -0,0 +1,108 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use super::errors::{ClustersError, ClustersResult};
+
+/// A monitored Kafka Cluster, as it appears in the clusters file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClusterConfig {
+    /// Identifier of the cluster: the `cluster_id` of all its series, and its name in the HTTP API.
+    pub id: String,
+
+    /// Initial Kafka Brokers to connect to (format: `HOST:PORT,...`).
+    pub brokers: String,
+
+    /// Additional configuration of the Kafka (Admin) Client, on top of `--kafka-conf`.
+    #[serde(default)]
+    pub kafka_conf: BTreeMap<String, String>,
+}
+
+/// Content of the clusters file.
+#[derive(Debug, Deserialize)]
+struct RawClusters {
+    #[serde(default)]
+    cluster: Vec<ClusterConfig>,
+}
+
+/// Parse the list of [`ClusterConfig`] from its TOML definition:
+///
+/// ```toml
+/// [[cluster]]
+/// id = "eu-1"
+/// brokers = "kafka-eu-1:9092"
+///
+/// [[cluster]]
+/// id = "us-1"
+/// brokers = "kafka-us-1:9093"
+/// kafka_conf = { "security.protocol" = "SASL_SSL", "sasl.mechanism" = "PLAIN" }
+/// ```
+pub fn parse(toml_str: &str) -> ClustersResult<Vec<ClusterConfig>> {
+    let raw: RawClusters = toml::from_str(toml_str)?;
+    if raw.cluster.is_empty() {
+        return Err(ClustersError::Empty);
+    }
+
+    let mut ids = HashSet::new();
+    for c in &raw.cluster {
+        let valid = !c.id.is_empty()
+            && c.id.chars().all(|ch| ch.is_ascii_alphanumeric() || "-_.".contains(ch));
+        if !valid {
+            return Err(ClustersError::InvalidId(c.id.clone()));
+        }
+        if !ids.insert(c.id.as_str()) {
+            return Err(ClustersError::DuplicateId(c.id.clone()));
+        }
+    }
+
+    Ok(raw.cluster)
+}
+
+/// Read and parse the list of [`ClusterConfig`] from the file at `path`.
+pub fn read(path: &Path) -> ClustersResult<Vec<ClusterConfig>> {
+    parse(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_clusters() {
+        let clusters = parse(
+            r#"
+            [[cluster]]
+            id = "eu-1"
+            brokers = "a:9092"
+
+            [[cluster]]
+            id = "us-1"
+            brokers = "b:9092,c:9092"
+            kafka_conf = { "security.protocol" = "SASL_SSL" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!((clusters[0].id.as_str(), clusters[0].brokers.as_str()), ("eu-1", "a:9092"));
+        assert!(clusters[0].kafka_conf.is_empty());
+        assert_eq!(clusters[1].kafka_conf["security.protocol"], "SASL_SSL");
+    }
+
+    #[test]
+    fn reject_invalid_clusters() {
+        assert!(matches!(parse(""), Err(ClustersError::Empty)));
+        assert!(matches!(
+            parse("[[cluster]]\nid = \"a\"\nbrokers = \"x\"\n[[cluster]]\nid = \"a\"\nbrokers = \"y\""),
+            Err(ClustersError::DuplicateId(id)) if id == "a"
+        ));
+        assert!(matches!(
+            parse("[[cluster]]\nid = \"a/b\"\nbrokers = \"x\""),
+            Err(ClustersError::InvalidId(_))
+        ));
+    }
+}
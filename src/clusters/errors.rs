@@ -0,0 +1,29 @@
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum ClustersError {
+    /// Reading the clusters file failed.
+    #[error("Clusters file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content of the clusters file could not be parsed.
+    #[error("Clusters file parsing failed: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    /// The clusters file lists no cluster.
+    #[error("Clusters file lists no cluster")]
+    Empty,
+
+    /// More than one cluster has the same identifier.
+    #[error("Cluster identifier '{0}' is not unique")]
+    DuplicateId(String),
+
+    /// A cluster has an identifier that can't be used in a URL path segment.
+    #[error(
+        "Cluster identifier '{0}' must only contain alphanumeric characters, '-', '_' and '.'"
+    )]
+    InvalidId(String),
+}
+
+pub type ClustersResult<T> = Result<T, ClustersError>;
@@ -0,0 +1,114 @@
+//! Monitor multiple Kafka Clusters from a single process.
+//!
+//! Each cluster listed in the clusters file gets its own pipeline of registers (see [`pipeline`]),
+//! started in isolation: a cluster that is slow to become ready, or that fails, doesn't affect
+//! the others. All of them are served by the same HTTP Service, via the [`ClusterSet`].
+
+// Inner modules
+mod config;
+mod errors;
+mod pipeline;
+mod set;
+
+// Exports
+pub use config::{read, ClusterConfig};
+pub use pipeline::{start, ClusterSpec};
+pub use set::{ClusterSet, ClusterStatus};
+
+// Imports
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::{sync::watch, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::alerts::AlertRules;
+use crate::cli::Cli;
+use crate::labels_mapping::LabelsMapper;
+
+/// Start the pipeline of each of the `clusters`, tracking their status in the returned [`ClusterSet`].
+///
+/// Per-cluster files are kept apart: the snapshot path gets the cluster identifier as suffix,
+/// and the file sink writes in a subdirectory named after it.
+pub fn init(
+    cli: Arc<Cli>,
+    clusters: Vec<ClusterConfig>,
+    labels_mapper: Arc<LabelsMapper>,
+    alert_rules: watch::Receiver<Arc<AlertRules>>,
+    shutdown_token: CancellationToken,
+) -> (Arc<ClusterSet>, Vec<JoinHandle<()>>) {
+    let set = Arc::new(ClusterSet::new(clusters.iter().map(|c| c.id.as_str())));
+
+    let joins = clusters
+        .into_iter()
+        .map(|cluster| {
+            let client_config = cli.build_cluster_client_config(&cluster);
+            let spec = ClusterSpec {
+                cluster_id: Some(cluster.id.clone()),
+                snapshot_path: cli
+                    .snapshot_path
+                    .as_ref()
+                    .map(|p| PathBuf::from(format!("{}.{}", p.display(), cluster.id))),
+                kafka_sink: cli.kafka_sink_config(client_config.clone()),
+                file_sink: cli.file_sink_config().map(|mut c| {
+                    c.dir.push(&cluster.id);
+                    c
+                }),
+                client_config,
+            };
+
+            spawn_supervised(
+                cluster.id,
+                cli.clone(),
+                spec,
+                labels_mapper.clone(),
+                alert_rules.clone(),
+                set.clone(),
+                shutdown_token.clone(),
+            )
+        })
+        .collect();
+
+    (set, joins)
+}
+
+/// Spawn the pipeline of a cluster, updating its status in the [`ClusterSet`] as it starts (or fails to),
+/// and if any of its tasks stops before shutdown.
+fn spawn_supervised(
+    id: String,
+    cli: Arc<Cli>,
+    spec: ClusterSpec,
+    labels_mapper: Arc<LabelsMapper>,
+    alert_rules: watch::Receiver<Arc<AlertRules>>,
+    set: Arc<ClusterSet>,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!("Starting cluster '{id}'");
+
+        // Start in a task of its own, so that a panic only fails this cluster
+        let start_token = shutdown_token.clone();
+        let start_join = tokio::spawn(async move {
+            start(&cli, spec, labels_mapper, alert_rules, start_token).await
+        });
+
+        match start_join.await {
+            Ok(Ok(mut pipeline)) => {
+                info!("Cluster '{id}' is ready");
+                set.set_ready(&id, pipeline.state.clone()).await;
+                if let Some(e) = pipeline.next_early_exit(&shutdown_token).await {
+                    error!("Cluster '{id}' stopped being tracked: {e}");
+                    set.set_failed(&id, e).await;
+                }
+                pipeline.join().await;
+            },
+            Ok(Err(e)) => {
+                warn!("Cluster '{id}' stopped before it was ready: {e}");
+                set.set_failed(&id, e.to_string()).await;
+            },
+            Err(e) => {
+                error!("Cluster '{id}' failed to start: {e}");
+                set.set_failed(&id, e.to_string()).await;
+            },
+        }
+    })
+}
@@ -0,0 +1,295 @@
+use std::future::{poll_fn, Future};
+use std::{path::PathBuf, pin::Pin, sync::Arc, task::Poll};
+
+use rdkafka::ClientConfig;
+use tokio::{sync::watch, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::alerts::AlertRules;
+use crate::cli::Cli;
+use crate::file_sink::FileSinkConfig;
+use crate::http::HttpServiceState;
+use crate::internals::{Awaitable, AwaitableResult};
+use crate::kafka_sink::KafkaSinkConfig;
+use crate::labels_mapping::LabelsMapper;
+use crate::{
+    alerts, cluster_status, consumer_groups, data_loss, file_sink, influx, kafka_sink,
+    konsumer_offsets_data, lag_history, lag_register, otlp, partition_offsets, prometheus_metrics,
+    remote_write, slo, snapshot, statsd,
+};
+
+/// What differs between the pipelines of different Kafka Clusters.
+pub struct ClusterSpec {
+    /// Configuration of the Kafka (Admin) Client connecting to the cluster.
+    pub client_config: ClientConfig,
+
+    /// Overrides the `cluster.id` from the Brokers' configuration.
+    pub cluster_id: Option<String>,
+    pub snapshot_path: Option<PathBuf>,
+    pub kafka_sink: Option<KafkaSinkConfig>,
+    pub file_sink: Option<FileSinkConfig>,
+}
+
+/// The registers tracking a Kafka Cluster, and the tasks keeping them up to date.
+pub struct Pipeline {
+    /// State to serve the cluster over HTTP: it holds all the registers.
+    pub state: HttpServiceState,
+    joins: Vec<JoinHandle<()>>,
+}
+
+impl Pipeline {
+    /// Wait for the tasks of the pipeline to terminate, until one does before `shutdown_token` is cancelled.
+    ///
+    /// The tasks are meant to run until shutdown: returns why the first one that didn't stopped (exited,
+    /// or panicked), or `None` once all of them terminated on shutdown.
+    pub async fn next_early_exit(&mut self, shutdown_token: &CancellationToken) -> Option<String> {
+        while !self.joins.is_empty() {
+            let (i, res) = poll_fn(|cx| {
+                for (i, j) in self.joins.iter_mut().enumerate() {
+                    if let Poll::Ready(res) = Pin::new(j).poll(cx) {
+                        return Poll::Ready((i, res));
+                    }
+                }
+                Poll::Pending
+            })
+            .await;
+            self.joins.swap_remove(i);
+
+            if !shutdown_token.is_cancelled() {
+                return Some(match res {
+                    Ok(()) => "A task of the pipeline exited before shutdown".to_string(),
+                    Err(e) => format!("A task of the pipeline failed before shutdown: {e}"),
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Wait for all the tasks of the pipeline to terminate.
+    pub async fn join(self) {
+        for j in self.joins {
+            let _ = j.await;
+        }
+    }
+}
+
+/// Start the pipeline of a Kafka Cluster, from the `cluster_status` module to the exporters.
+///
+/// Registers are awaited to be ready in order, as each depends on the previous: if `shutdown_token`
+/// is cancelled in the meantime, an error is returned.
+pub async fn start(
+    cli: &Cli,
+    spec: ClusterSpec,
+    labels_mapper: Arc<LabelsMapper>,
+    alert_rules: watch::Receiver<Arc<AlertRules>>,
+    shutdown_token: CancellationToken,
+) -> AwaitableResult<Pipeline> {
+    let admin_client_config = spec.client_config;
+    let mut joins = Vec::new();
+
+    // Load snapshot of the internal state, to restore registers from it (if any)
+    let mut snapshot =
+        spec.snapshot_path.as_ref().and_then(|path| snapshot::load(path, cli.snapshot_max_age()));
+
+    // Init `prometheus_metrics` module (may block fetching the Cluster ID)
+    let prom_reg = tokio::task::block_in_place(|| {
+        prometheus_metrics::init(
+            admin_client_config.clone(),
+            spec.cluster_id.clone(),
+            cli.static_labels.clone(),
+        )
+    });
+    let prom_reg_arc = Arc::new(prom_reg);
+
+    // Init `cluster_status` module, and await registry to be ready
+    let (cs_reg, cs_join) = cluster_status::init(
+        admin_client_config.clone(),
+        spec.cluster_id.clone(),
+        shutdown_token.clone(),
+        prom_reg_arc.clone(),
+    );
+    joins.push(cs_join);
+    if let Some(s) = snapshot.as_mut() {
+        s.restore_cluster_status(&cs_reg).await;
+    }
+    cs_reg.await_ready(shutdown_token.clone()).await?;
+    let cs_reg_arc = Arc::new(cs_reg);
+
+    // Init `partition_offsets` module, and await registry to be ready
+    let (po_reg, po_join) = partition_offsets::init(
+        admin_client_config.clone(),
+        cli.offsets_history,
+        cli.offsets_history_ready_at,
+        cs_reg_arc.clone(),
+        shutdown_token.clone(),
+        prom_reg_arc.clone(),
+    );
+    joins.push(po_join);
+    if let Some(s) = snapshot.as_mut() {
+        s.restore_partition_offsets(&po_reg).await;
+    }
+    po_reg.await_ready(shutdown_token.clone()).await?;
+    let po_reg_arc = Arc::new(po_reg);
+
+    // Init `konsumer_offsets_data` module
+    let (kod_rx, replay_caught_up, kod_join) =
+        konsumer_offsets_data::init(admin_client_config.clone(), shutdown_token.clone());
+    joins.push(kod_join);
+
+    // Init `consumer_groups` module
+    let (cg_rx, cg_join) = consumer_groups::init(
+        admin_client_config.clone(),
+        shutdown_token.clone(),
+        prom_reg_arc.clone(),
+    );
+    joins.push(cg_join);
+
+    // Init `lag_register` module, and await registry to be ready
+    let lag_reg = lag_register::init(
+        cg_rx,
+        kod_rx,
+        po_reg_arc.clone(),
+        cli.status_window as usize,
+        cli.status_min_distance(),
+    );
+    if let Some(s) = snapshot.as_mut() {
+        s.restore_lag(&lag_reg).await;
+    }
+    lag_reg.await_ready(shutdown_token.clone()).await?;
+    let lag_reg_arc = Arc::new(lag_reg);
+
+    // Init `slo` module
+    let (slo_reg, slo_join) = slo::init(
+        cli.slo_objectives.clone(),
+        lag_reg_arc.clone(),
+        po_reg_arc.clone(),
+        replay_caught_up.clone(),
+        shutdown_token.clone(),
+    );
+    joins.extend(slo_join);
+    if let Some(mut s) = snapshot.take() {
+        s.restore_slos(&slo_reg).await;
+    }
+    let slo_reg_arc = Arc::new(slo_reg);
+
+    // Init `data_loss` module
+    let (dl_reg, dl_join) = data_loss::init(
+        lag_reg_arc.clone(),
+        po_reg_arc.clone(),
+        replay_caught_up.clone(),
+        shutdown_token.clone(),
+    );
+    joins.push(dl_join);
+    let dl_reg_arc = Arc::new(dl_reg);
+
+    // Init `alerts` module
+    let (alerts_reg, ar_join, wh_join) = alerts::init(
+        alert_rules,
+        cli.alert_webhooks.clone(),
+        lag_reg_arc.clone(),
+        dl_reg_arc.clone(),
+        cs_reg_arc.clone(),
+        replay_caught_up.clone(),
+        shutdown_token.clone(),
+    );
+    joins.extend([ar_join, wh_join]);
+    let alerts_reg_arc = Arc::new(alerts_reg);
+
+    // Init `lag_history` module
+    let (lh_reg, lh_join) = lag_history::init(
+        lag_reg_arc.clone(),
+        cli.lag_history_retention(),
+        cli.lag_history_resolution(),
+        cli.lag_history_max_samples,
+        shutdown_token.clone(),
+        prom_reg_arc.clone(),
+    );
+    joins.push(lh_join);
+    let lh_reg_arc = Arc::new(lh_reg);
+
+    // Init `snapshot` module, if a snapshot path was given
+    joins.extend(spec.snapshot_path.map(|path| {
+        snapshot::init(
+            path,
+            cli.snapshot_interval(),
+            cs_reg_arc.clone(),
+            po_reg_arc.clone(),
+            lag_reg_arc.clone(),
+            slo_reg_arc.clone(),
+            shutdown_token.clone(),
+            prom_reg_arc.clone(),
+        )
+    }));
+
+    // Init `kafka_sink` module, if a topic was given
+    joins.extend(spec.kafka_sink.map(|config| {
+        kafka_sink::init(
+            config,
+            cs_reg_arc.clone(),
+            po_reg_arc.clone(),
+            lag_reg_arc.clone(),
+            shutdown_token.clone(),
+            prom_reg_arc.clone(),
+        )
+    }));
+
+    // Init `file_sink` module, if a directory was given
+    joins.extend(spec.file_sink.map(|config| {
+        file_sink::init(
+            config,
+            cs_reg_arc.clone(),
+            po_reg_arc.clone(),
+            lag_reg_arc.clone(),
+            shutdown_token.clone(),
+            prom_reg_arc.clone(),
+        )
+    }));
+
+    let state = HttpServiceState {
+        metrics_profiles: Arc::new(cli.unique_metrics_profiles()),
+        consumer_metrics: Arc::new(cli.consumer_metrics.clone()),
+        owner_labels: cli.owner_labels,
+        partitions_limit: cli.consumer_partitions_limit,
+        series_drop_policy: cli.series_drop_policy,
+        cs_reg: cs_reg_arc,
+        po_reg: po_reg_arc,
+        lag_reg: lag_reg_arc,
+        lag_history: lh_reg_arc,
+        data_loss: dl_reg_arc,
+        alerts: alerts_reg_arc,
+        slos: slo_reg_arc,
+        labels_mapper,
+        metrics: prom_reg_arc,
+        shutdown_token: shutdown_token.clone(),
+    };
+
+    // Init `otlp` module, if an endpoint was given
+    joins.extend(
+        cli.otlp_config().map(|config| otlp::init(config, state.clone(), shutdown_token.clone())),
+    );
+
+    // Init `remote_write` module, if a URL was given
+    if let Some(config) = cli.remote_write_config() {
+        let (rwc_join, rws_join) =
+            remote_write::init(config, state.clone(), shutdown_token.clone());
+        joins.extend([rwc_join, rws_join]);
+    }
+
+    // Init `influx` module, if a URL was given
+    joins.extend(
+        cli.influx_config()
+            .map(|config| influx::init(config, state.clone(), shutdown_token.clone())),
+    );
+
+    // Init `statsd` module, if an address was given
+    joins.extend(
+        cli.statsd_config()
+            .map(|config| statsd::init(config, state.clone(), shutdown_token.clone())),
+    );
+
+    Ok(Pipeline {
+        state,
+        joins,
+    })
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use axum::Router;
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::http::{cluster_router, HttpServiceState};
+
+/// Status of the pipeline of a Kafka Cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterStatus {
+    /// Waiting for its registers to be ready.
+    Starting,
+
+    /// Tracked and served.
+    Ready,
+
+    /// Failed to start, or one of its tasks stopped before shutdown: the other clusters are not affected.
+    Failed,
+}
+
+/// Status of a Kafka Cluster, as served by the HTTP API.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterInfo {
+    pub id: String,
+    pub status: ClusterStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct Entry {
+    status: ClusterStatus,
+    error: Option<String>,
+
+    /// Once [`ClusterStatus::Ready`], the state of the cluster and the router serving it.
+    served: Option<(HttpServiceState, Router)>,
+}
+
+/// The Kafka Clusters monitored by the process, each with the status of its pipeline.
+pub struct ClusterSet {
+    entries: RwLock<BTreeMap<String, Entry>>,
+}
+
+impl ClusterSet {
+    /// A [`ClusterSet`] where all the clusters are [`ClusterStatus::Starting`].
+    pub fn new<'a>(ids: impl IntoIterator<Item = &'a str>) -> Self {
+        let entries = ids
+            .into_iter()
+            .map(|id| {
+                let entry = Entry {
+                    status: ClusterStatus::Starting,
+                    error: None,
+                    served: None,
+                };
+                (id.to_string(), entry)
+            })
+            .collect();
+
+        ClusterSet {
+            entries: RwLock::new(entries),
+        }
+    }
+
+    pub async fn set_ready(&self, id: &str, state: HttpServiceState) {
+        if let Some(e) = self.entries.write().await.get_mut(id) {
+            e.status = ClusterStatus::Ready;
+            e.served = Some((state.clone(), cluster_router(state)));
+        }
+    }
+
+    pub async fn set_failed(&self, id: &str, error: String) {
+        if let Some(e) = self.entries.write().await.get_mut(id) {
+            e.status = ClusterStatus::Failed;
+            e.error = Some(error);
+            e.served = None;
+        }
+    }
+
+    /// Status of all the clusters, ordered by identifier.
+    pub async fn infos(&self) -> Vec<ClusterInfo> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .map(|(id, e)| ClusterInfo {
+                id: id.clone(),
+                status: e.status,
+                error: e.error.clone(),
+            })
+            .collect()
+    }
+
+    /// State of the [`ClusterStatus::Ready`] clusters, ordered by identifier.
+    pub async fn ready_states(&self) -> Vec<HttpServiceState> {
+        self.entries
+            .read()
+            .await
+            .values()
+            .filter_map(|e| e.served.as_ref().map(|(s, _)| s.clone()))
+            .collect()
+    }
+
+    /// Status of the cluster, and the router serving it (if it's [`ClusterStatus::Ready`]).
+    ///
+    /// Returns `None` if the cluster is unknown.
+    pub async fn router(&self, id: &str) -> Option<(ClusterStatus, Option<Router>)> {
+        self.entries
+            .read()
+            .await
+            .get(id)
+            .map(|e| (e.status, e.served.as_ref().map(|(_, r)| r.clone())))
+    }
+}
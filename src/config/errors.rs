@@ -0,0 +1,39 @@
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// Reading the configuration file failed.
+    #[error("Configuration file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content of the TOML configuration file could not be parsed.
+    #[error("Configuration file parsing failed: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    /// The content of the YAML configuration file could not be parsed.
+    #[error("Configuration file parsing failed: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    /// The configuration file extension is neither TOML nor YAML.
+    #[error("Configuration file '{0}' should have extension '.toml', '.yaml' or '.yml'")]
+    UnknownFormat(String),
+
+    /// The configuration file sets an option that doesn't exist.
+    #[error("Configuration option '{0}' does not exist")]
+    UnknownOption(String),
+
+    /// The configuration file sets the same option more than once (e.g. as `a-b` and `a_b`).
+    #[error("Configuration option '{0}' is set more than once")]
+    DuplicateOption(String),
+
+    /// The configuration file sets an option to a value of the wrong type.
+    #[error("Configuration option '{0}' is invalid: {1}")]
+    InvalidValue(String, &'static str),
+
+    /// The options, from the configuration file and the command line, failed validation.
+    #[error("{0}")]
+    Cli(#[from] clap::Error),
+}
+
+pub type ConfigResult<T> = Result<T, ConfigError>;
@@ -0,0 +1,323 @@
+use std::{collections::BTreeMap, ffi::OsString, fs, path::Path};
+
+use clap::{Arg, ArgAction, Command};
+use serde_json::Value;
+
+use super::errors::{ConfigError, ConfigResult};
+
+/// Format of a [`ConfigFile`], from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> ConfigResult<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            _ => Err(ConfigError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+/// Identifiers of the arguments pointing to a file, whose content can instead be set inline as a section.
+const SECTION_ARGS: [&str; 2] = ["labels_mapping", "alert_rules"];
+
+/// An option of the [`ConfigFile`], as the command line arguments equivalent to it.
+#[derive(Debug, Clone, PartialEq)]
+struct ConfigOption {
+    /// Identifier of the command line argument.
+    id: String,
+    args: Vec<OsString>,
+
+    /// Content (TOML) set inline, in place of the file the argument points to.
+    section: Option<String>,
+}
+
+/// Options read from a configuration file.
+///
+/// Each key is the long name of a command line argument, with `_` accepted in place of `-`.
+/// Flags that can be repeated (e.g. `verbose`) take a count, while arguments that can be repeated
+/// take a list of values: tables, for those in the `K:V` format. Arguments pointing to a TOML file
+/// (see [`SECTION_ARGS`]) can instead have its content inline, as a section.
+///
+/// ```yaml
+/// brokers: kafka-1:9092,kafka-2:9092
+/// kafka-conf:
+///   security.protocol: SASL_SSL
+///   sasl.mechanism: PLAIN
+/// metrics-profiles: [kommitted, kafka-exporter]
+/// labels-mapping:
+///   group:
+///     - regex: "^payments-"
+///       labels: { team: payments }
+/// alert-rules: /etc/kommitted/alerts.toml
+/// verbose: 1
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConfigFile {
+    /// Options by long name.
+    options: BTreeMap<String, ConfigOption>,
+}
+
+impl ConfigFile {
+    /// Parse [`ConfigFile`] from its definition, validating options against the arguments of `command`.
+    ///
+    /// Values are validated later, when parsed as the command line arguments returned by [`Self::args`].
+    pub fn parse(content: &str, format: ConfigFormat, command: &Command) -> ConfigResult<Self> {
+        let values: BTreeMap<String, Value> = match format {
+            ConfigFormat::Toml => toml::from_str(content)?,
+            ConfigFormat::Yaml => serde_yaml::from_str::<Option<_>>(content)?.unwrap_or_default(),
+        };
+
+        let mut options = BTreeMap::new();
+        for (key, value) in values {
+            let long = key.replace('_', "-");
+            let arg = command
+                .get_arguments()
+                .find(|a| a.get_long() == Some(long.as_str()))
+                .ok_or_else(|| ConfigError::UnknownOption(key.clone()))?;
+            if arg.get_id() == "config" {
+                return Err(ConfigError::InvalidValue(key, "only valid on the command line"));
+            }
+
+            let id = arg.get_id().to_string();
+            let option =
+                match value {
+                    Value::Object(_) if SECTION_ARGS.contains(&id.as_str()) => ConfigOption {
+                        id,
+                        args: Vec::new(),
+                        section: Some(toml::to_string(&value).map_err(|_| {
+                            ConfigError::InvalidValue(key.clone(), "invalid section")
+                        })?),
+                    },
+                    _ => ConfigOption {
+                        id,
+                        args: to_args(arg, &long, &value)
+                            .map_err(|reason| ConfigError::InvalidValue(key.clone(), reason))?,
+                        section: None,
+                    },
+                };
+            if options.insert(long, option).is_some() {
+                return Err(ConfigError::DuplicateOption(key));
+            }
+        }
+
+        Ok(ConfigFile {
+            options,
+        })
+    }
+
+    /// Read and parse [`ConfigFile`] from the file at `path`, in the format of its extension.
+    pub fn read(path: &Path, command: &Command) -> ConfigResult<Self> {
+        Self::parse(&fs::read_to_string(path)?, ConfigFormat::from_path(path)?, command)
+    }
+
+    /// Command line arguments equivalent to the options, except those of the arguments
+    /// for which `skip` returns `true`, given their identifier.
+    pub fn args(&self, skip: impl Fn(&str) -> bool) -> Vec<OsString> {
+        self.options.values().filter(|o| !skip(&o.id)).flat_map(|o| o.args.clone()).collect()
+    }
+
+    /// Content of the section set inline for the argument `id`, if any (see [`SECTION_ARGS`]).
+    pub fn section(&self, id: &str) -> Option<&str> {
+        self.options.values().find(|o| o.id == id).and_then(|o| o.section.as_deref())
+    }
+
+    /// Identifiers of the arguments whose options differ between `self` and `other`.
+    pub fn changed<'a>(&'a self, other: &'a Self) -> Vec<&'a str> {
+        let mut changed: Vec<&str> = self
+            .options
+            .iter()
+            .filter(|(long, o)| other.options.get(*long) != Some(o))
+            .chain(other.options.iter().filter(|(long, _)| !self.options.contains_key(*long)))
+            .map(|(_, o)| o.id.as_str())
+            .collect();
+        changed.sort_unstable();
+        changed.dedup();
+        changed
+    }
+}
+
+/// Convert the `value` of an option into the command line arguments for `arg`.
+fn to_args(arg: &Arg, long: &str, value: &Value) -> Result<Vec<OsString>, &'static str> {
+    let flag = || OsString::from(format!("--{long}"));
+    let with_value = |v: String| OsString::from(format!("--{long}={v}"));
+
+    match (arg.get_action(), value) {
+        (ArgAction::SetTrue, Value::Bool(b)) => Ok(if *b {
+            vec![flag()]
+        } else {
+            vec![]
+        }),
+        (ArgAction::SetTrue, _) => Err("expected a boolean"),
+        (ArgAction::Count, Value::Number(n)) => {
+            let count = n.as_u64().filter(|c| *c <= u8::MAX as u64).ok_or("expected a count")?;
+            Ok((0..count).map(|_| flag()).collect())
+        },
+        (ArgAction::Count, _) => Err("expected a count"),
+        (ArgAction::Set, v) => Ok(vec![with_value(to_scalar(v).ok_or("expected a single value")?)]),
+        (ArgAction::Append, Value::Array(values)) => values
+            .iter()
+            .map(|v| to_scalar(v).map(with_value).ok_or("expected a list of values"))
+            .collect(),
+        (ArgAction::Append, Value::Object(entries)) => entries
+            .iter()
+            .map(|(k, v)| {
+                to_scalar(v)
+                    .map(|v| with_value(format!("{k}:{v}")))
+                    .ok_or("expected a table of values")
+            })
+            .collect(),
+        (ArgAction::Append, v) => {
+            Ok(vec![with_value(to_scalar(v).ok_or("expected a list of values")?)])
+        },
+        _ => Err("can't be set in the configuration file"),
+    }
+}
+
+fn to_scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Null | Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    fn command() -> Command {
+        let mut command = Cli::command();
+        command.build();
+        command
+    }
+
+    fn args(config: &ConfigFile) -> Vec<String> {
+        config.args(|_| false).into_iter().map(|a| a.into_string().unwrap()).collect()
+    }
+
+    #[test]
+    fn parse_toml_and_yaml() {
+        let toml = ConfigFile::parse(
+            r#"
+            brokers = "kafka:9092"
+            kafka_conf = { "security.protocol" = "SASL_SSL" }
+            metrics-profiles = ["kommitted", "kafka-exporter"]
+            history-ready-at = 75.5
+            verbose = 2
+            "#,
+            ConfigFormat::Toml,
+            &command(),
+        )
+        .unwrap();
+        let yaml = ConfigFile::parse(
+            r#"
+            brokers: kafka:9092
+            kafka-conf:
+              security.protocol: SASL_SSL
+            metrics_profiles: [kommitted, kafka-exporter]
+            history-ready-at: 75.5
+            verbose: 2
+            "#,
+            ConfigFormat::Yaml,
+            &command(),
+        )
+        .unwrap();
+
+        assert_eq!(toml, yaml);
+        assert_eq!(
+            args(&toml),
+            [
+                "--brokers=kafka:9092",
+                "--history-ready-at=75.5",
+                "--kafka-conf=security.protocol:SASL_SSL",
+                "--metrics-profiles=kommitted",
+                "--metrics-profiles=kafka-exporter",
+                "--verbose",
+                "--verbose",
+            ]
+        );
+        assert_eq!(toml.args(|id| id == "verbose").len(), 5);
+        assert!(ConfigFile::parse("", ConfigFormat::Yaml, &command()).unwrap().options.is_empty());
+    }
+
+    #[test]
+    fn parse_sections() {
+        let toml = ConfigFile::parse(
+            r#"
+            alert-rules = "/etc/kommitted/alerts.toml"
+
+            [labels-mapping]
+            group = [{ regex = "^payments-", labels = { team = "payments" } }]
+            "#,
+            ConfigFormat::Toml,
+            &command(),
+        )
+        .unwrap();
+        let yaml = ConfigFile::parse(
+            r#"
+            alert-rules: /etc/kommitted/alerts.toml
+            labels_mapping:
+              group:
+                - regex: "^payments-"
+                  labels: { team: payments }
+            "#,
+            ConfigFormat::Yaml,
+            &command(),
+        )
+        .unwrap();
+
+        assert_eq!(toml, yaml);
+        assert_eq!(args(&toml), ["--alert-rules=/etc/kommitted/alerts.toml"]);
+        assert_eq!(toml.section("alert_rules"), None);
+
+        let section: toml::Value = toml::from_str(toml.section("labels_mapping").unwrap()).unwrap();
+        assert_eq!(section["group"][0]["regex"].as_str(), Some("^payments-"));
+        assert_eq!(section["group"][0]["labels"]["team"].as_str(), Some("payments"));
+
+        // Only arguments pointing to a file can be set as a section
+        assert!(matches!(
+            ConfigFile::parse(
+                "[brokers]
+a = 1",
+                ConfigFormat::Toml,
+                &command()
+            ),
+            Err(ConfigError::InvalidValue(..))
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_options() {
+        let parse = |toml: &str| ConfigFile::parse(toml, ConfigFormat::Toml, &command());
+
+        assert!(matches!(parse("nope = 1"), Err(ConfigError::UnknownOption(o)) if o == "nope"));
+        assert!(matches!(parse("help = true"), Err(ConfigError::InvalidValue(..))));
+        assert!(matches!(parse("config = \"a.toml\""), Err(ConfigError::InvalidValue(..))));
+        assert!(matches!(parse("verbose = \"yes\""), Err(ConfigError::InvalidValue(..))));
+        assert!(matches!(parse("brokers = [\"a\", \"b\"]"), Err(ConfigError::InvalidValue(..))));
+        assert!(matches!(parse("label = { team = [] }"), Err(ConfigError::InvalidValue(..))));
+        assert!(matches!(
+            parse("alert-rules = \"a\"\nalert_rules = \"b\""),
+            Err(ConfigError::DuplicateOption(_))
+        ));
+    }
+
+    #[test]
+    fn changed_options() {
+        let parse = |toml: &str| ConfigFile::parse(toml, ConfigFormat::Toml, &command()).unwrap();
+
+        let before = parse("port = 6564\nverbose = 1\nlabel = { env = \"prod\" }");
+        let after = parse("port = 6564\nquiet = 1\nlabel = { env = \"dev\" }");
+        assert_eq!(before.changed(&after), ["quiet", "static_labels", "verbose"]);
+        assert!(before.changed(&before).is_empty());
+    }
+}
@@ -0,0 +1,297 @@
+//! Configuration file, combined with the command line arguments.
+//!
+//! Every command line argument can also be set in the YAML or TOML file given via `--config`,
+//! while arguments on the command line take precedence. At runtime, on `SIGHUP` or when the file
+//! changes, the configuration is read again and the settings that can safely change are applied:
+//! log level, labels mapping and alert rules. The latter two can be set inline, as sections of the file.
+
+// Inner modules
+mod errors;
+mod file;
+
+// Exports
+pub use file::ConfigFile;
+
+// Imports
+use std::{ffi::OsString, path::PathBuf, sync::Arc, time::SystemTime};
+
+use clap::{error::ErrorKind, parser::ValueSource, Arg, Command, CommandFactory, Parser};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::watch,
+    task::JoinHandle,
+    time::{interval, Duration, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::alerts::AlertRules;
+use crate::cli::Cli;
+use crate::labels_mapping::LabelsMapper;
+use crate::logging;
+
+use errors::{ConfigError, ConfigResult};
+
+/// How often the configuration file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Identifiers of the arguments applied again when the configuration is reloaded.
+const RELOADABLE_ARGS: [&str; 4] = ["verbose", "quiet", "labels_mapping", "alert_rules"];
+
+/// Identifiers of the arguments that, set in the configuration file, are not replaced by conflicting
+/// command line arguments: nothing takes their place, so they would be silently ignored.
+const IRREPLACEABLE_ARGS: [&str; 2] = ["keda_scaler_port", "external_metrics_port"];
+
+/// Parse the [`Cli`] from the command line arguments, and the configuration file (if any).
+///
+/// # Exits
+///
+/// Like [`Parser::parse`], if the arguments or the configuration file are invalid.
+pub fn parse_cli() -> (Cli, ConfigFile) {
+    try_parse_cli(&std::env::args_os().collect::<Vec<_>>()).unwrap_or_else(|e| match e {
+        ConfigError::Cli(e) => e.exit(),
+        e => Cli::command().error(ErrorKind::InvalidValue, e).exit(),
+    })
+}
+
+/// Parse the [`Cli`] from the command line `args`, and the configuration file they point to (if any).
+///
+/// Options of the configuration file are placed before the command line arguments, unless the
+/// same arguments (or arguments conflicting with them, except [`IRREPLACEABLE_ARGS`]) are already
+/// given on the command line. The same goes for the sections set inline (e.g. `labels-mapping`).
+fn try_parse_cli(args: &[OsString]) -> ConfigResult<(Cli, ConfigFile)> {
+    let mut command = Cli::command();
+    command.build();
+
+    let cli_matches = command.clone().ignore_errors(true).try_get_matches_from(args)?;
+    let config = match cli_matches.get_one::<PathBuf>("config") {
+        Some(path) => ConfigFile::read(path, &command)?,
+        None => ConfigFile::default(),
+    };
+
+    let given: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|a| cli_matches.value_source(a.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .collect();
+    let overridden = |id: &str| {
+        let arg = command.get_arguments().find(|a| a.get_id() == id).expect("Known argument");
+        given.iter().any(|g| {
+            g.get_id() == id || (!IRREPLACEABLE_ARGS.contains(&id) && conflicting(&command, arg, g))
+        })
+    };
+
+    let (bin, cli_args) = args.split_first().map_or((None, args), |(b, a)| (Some(b), a));
+    let merged_args =
+        bin.into_iter().cloned().chain(config.args(overridden)).chain(cli_args.to_vec());
+
+    let mut cli = Cli::try_parse_from(merged_args)?;
+    let section = |id: &str| config.section(id).filter(|_| !overridden(id)).map(String::from);
+    cli.labels_mapping_inline = section("labels_mapping");
+    cli.alert_rules_inline = section("alert_rules");
+
+    Ok((cli, config))
+}
+
+/// Whether the arguments `a` and `b` can't be given together.
+fn conflicting(command: &Command, a: &Arg, b: &Arg) -> bool {
+    let conflicts_with = |x: &Arg, y: &Arg| {
+        command.get_arg_conflicts_with(x).iter().any(|c| c.get_id() == y.get_id())
+    };
+    let in_exclusive_group = command.get_groups().any(|g| {
+        let ids: Vec<_> = g.get_args().collect();
+        ids.contains(&a.get_id()) && ids.contains(&b.get_id()) && !g.clone().is_multiple()
+    });
+
+    conflicts_with(a, b) || conflicts_with(b, a) || in_exclusive_group
+}
+
+/// Applies the configuration again, when reloaded.
+struct Reloader {
+    args: Vec<OsString>,
+    cli: Arc<Cli>,
+    config: ConfigFile,
+    labels_mapper: Arc<LabelsMapper>,
+    alert_rules_tx: watch::Sender<Arc<AlertRules>>,
+}
+
+impl Reloader {
+    /// Read the configuration again and, if it's all valid, apply the settings that can change at runtime.
+    ///
+    /// Changes to other settings are only reported, as they require a restart.
+    fn reload(&mut self) {
+        match self.try_reload() {
+            Ok(restart_required) if restart_required.is_empty() => info!("Reloaded configuration"),
+            Ok(restart_required) => warn!(
+                "Reloaded configuration, but changes to {restart_required:?} require a restart"
+            ),
+            Err(e) => error!("Failed to reload configuration (keeping current): {e}"),
+        }
+    }
+
+    fn try_reload(&mut self) -> Result<Vec<String>, String> {
+        let (cli, config) = try_parse_cli(&self.args).map_err(|e| e.to_string())?;
+        let labels_mapping = self
+            .labels_mapper
+            .load(cli.labels_mapping.as_deref(), cli.labels_mapping_inline.as_deref())
+            .map_err(|e| format!("Invalid labels mapping: {e}"))?;
+        let alert_rules =
+            AlertRules::load(cli.alert_rules.as_deref(), cli.alert_rules_inline.as_deref())
+                .map_err(|e| format!("Invalid alert rules: {e}"))?;
+
+        if cli.verbosity_level() != self.cli.verbosity_level() {
+            logging::set_verbosity_level(cli.verbosity_level());
+        }
+        self.labels_mapper.apply(cli.labels_mapping.clone(), labels_mapping);
+        info!("Loaded {} alert rules", alert_rules.rules.len());
+        self.alert_rules_tx.send_replace(Arc::new(alert_rules));
+
+        let restart_required = self
+            .config
+            .changed(&config)
+            .into_iter()
+            .filter(|id| !RELOADABLE_ARGS.contains(id))
+            .map(String::from)
+            .collect();
+        self.cli = Arc::new(cli);
+        self.config = config;
+
+        Ok(restart_required)
+    }
+
+    fn modified_at(&self) -> Option<SystemTime> {
+        self.cli.config.as_ref().and_then(|p| p.metadata().and_then(|m| m.modified()).ok())
+    }
+}
+
+/// Spawn the task reloading the configuration on `SIGHUP` and, if `--config` is set,
+/// every time the file is modified.
+///
+/// `cli` and `config` are the configuration currently applied, as returned by [`parse_cli`].
+pub fn init(
+    cli: Arc<Cli>,
+    config: ConfigFile,
+    labels_mapper: Arc<LabelsMapper>,
+    alert_rules_tx: watch::Sender<Arc<AlertRules>>,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    let mut reloader = Reloader {
+        args: std::env::args_os().collect(),
+        cli,
+        config,
+        labels_mapper,
+        alert_rules_tx,
+    };
+
+    let join_handle = tokio::spawn(async move {
+        let mut sighup = signal(SignalKind::hangup()).expect("Failed to register SIGHUP handler");
+        let mut last_modified = reloader.modified_at();
+        let mut interval = interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = sighup.recv() => {
+                    info!("Received SIGHUP: reloading configuration");
+                    last_modified = reloader.modified_at();
+                    reloader.reload();
+                },
+                _ = interval.tick() => {
+                    let modified = reloader.modified_at();
+                    if modified != last_modified {
+                        last_modified = modified;
+                        reloader.reload();
+                    }
+                },
+                _ = shutdown_token.cancelled() => {
+                    info!("Shutting down");
+                    break;
+                },
+            }
+        }
+    });
+
+    debug!("Initialized");
+    join_handle
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    fn parse(name: &str, config: &str, cli_args: &[&str]) -> ConfigResult<Cli> {
+        let path =
+            std::env::temp_dir().join(format!("kommitted-{}-{name}.toml", std::process::id()));
+        fs::write(&path, config).unwrap();
+
+        let mut args: Vec<OsString> =
+            vec!["kommitted".into(), "--config".into(), path.clone().into()];
+        args.extend(cli_args.iter().map(OsString::from));
+        let res = try_parse_cli(&args).map(|(cli, _)| cli);
+
+        fs::remove_file(path).unwrap();
+        res
+    }
+
+    #[test]
+    fn command_line_overrides_config() {
+        let config = r#"
+            brokers = "kafka:9092"
+            port = 7000
+            label = { env = "prod" }
+            verbose = 2
+        "#;
+
+        let cli = parse("override", config, &[]).unwrap();
+        assert_eq!(cli.bootstrap_brokers.as_deref(), Some("kafka:9092"));
+        assert_eq!((cli.port, cli.verbosity_level()), (7000, 2));
+        assert_eq!(cli.static_labels, [("env".to_string(), "prod".to_string())]);
+
+        let cli =
+            parse("override", config, &["--port", "8000", "--label", "env:dev", "-q"]).unwrap();
+        assert_eq!(cli.bootstrap_brokers.as_deref(), Some("kafka:9092"));
+        assert_eq!((cli.port, cli.verbosity_level()), (8000, -1));
+        assert_eq!(cli.static_labels, [("env".to_string(), "dev".to_string())]);
+
+        let cli = parse("override", config, &["--clusters", "clusters.toml"]).unwrap();
+        assert_eq!(cli.bootstrap_brokers, None);
+    }
+
+    #[test]
+    fn command_line_overrides_sections() {
+        let config = r#"
+            brokers = "kafka:9092"
+
+            [alert-rules]
+            rule = [{ name = "lagging", kind = "offset_lag", threshold = 1000 }]
+        "#;
+
+        let cli = parse("sections", config, &[]).unwrap();
+        assert_eq!(cli.alert_rules, None);
+        let rules = AlertRules::load(cli.alert_rules.as_deref(), cli.alert_rules_inline.as_deref())
+            .unwrap();
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(cli.labels_mapping_inline, None);
+
+        let cli = parse("sections", config, &["--alert-rules", "alerts.toml"]).unwrap();
+        assert_eq!(cli.alert_rules.as_deref(), Some(std::path::Path::new("alerts.toml")));
+        assert_eq!(cli.alert_rules_inline, None);
+    }
+
+    #[test]
+    fn reject_invalid_config() {
+        assert!(matches!(parse("invalid-1", "port = \"http\"", &[]), Err(ConfigError::Cli(_))));
+        assert!(matches!(
+            parse("invalid-2", "bootstrap_brokers = \"a\"", &[]),
+            Err(ConfigError::UnknownOption(_))
+        ));
+        assert!(matches!(
+            parse("invalid-3", "brokers = \"a\"\nclusters = \"b\"", &[]),
+            Err(ConfigError::Cli(_))
+        ));
+        assert!(matches!(
+            parse("invalid-4", "keda-scaler-port = 9000", &["--clusters", "clusters.toml"]),
+            Err(ConfigError::Cli(_))
+        ));
+    }
+}
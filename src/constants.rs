@@ -25,5 +25,130 @@ pub(crate) const DEFAULT_OFFSETS_HISTORY: &str = "3600"; //< `usize` after parsi
 /// See [`crate::Cli`]'s `offsets_history_ready_at`.
 pub(crate) const DEFAULT_OFFSETS_HISTORY_READY_AT: &str = "0.3"; //< `f64` after parsing
 
+/// The default interval (in seconds) between snapshots of the internal state.
+///
+/// See [`crate::Cli`]'s `snapshot_interval`.
+pub(crate) const DEFAULT_SNAPSHOT_INTERVAL: &str = "60"; //< `u64` after parsing
+
+/// The default maximum age (in seconds) of a snapshot, for it to be restored at launch.
+///
+/// See [`crate::Cli`]'s `snapshot_max_age`.
+pub(crate) const DEFAULT_SNAPSHOT_MAX_AGE: &str = "900"; //< `u64` after parsing
+
+/// The default encoding of the records produced to the Kafka sink topic.
+///
+/// See [`crate::Cli`]'s `kafka_sink_encoding`.
+pub(crate) const DEFAULT_KAFKA_SINK_ENCODING: &str = "json"; //< `SinkEncoding` after parsing
+
+/// The default mode of producing records to the Kafka sink topic.
+///
+/// See [`crate::Cli`]'s `kafka_sink_mode`.
+pub(crate) const DEFAULT_KAFKA_SINK_MODE: &str = "periodic"; //< `SinkMode` after parsing
+
+/// The default interval (in seconds) between records produced to the Kafka sink topic.
+///
+/// See [`crate::Cli`]'s `kafka_sink_interval`.
+pub(crate) const DEFAULT_KAFKA_SINK_INTERVAL: &str = "60"; //< `u64` after parsing
+
+/// The default format of the files written by the file sink.
+///
+/// See [`crate::Cli`]'s `file_sink_format`.
+pub(crate) const DEFAULT_FILE_SINK_FORMAT: &str = "ndjson"; //< `FileSinkFormat` after parsing
+
+/// The default amount of complete files kept by the file sink.
+///
+/// See [`crate::Cli`]'s `file_sink_retention`.
+pub(crate) const DEFAULT_FILE_SINK_RETENTION: &str = "24"; //< `usize` after parsing
+
+/// The default transport protocol of the OTLP export.
+///
+/// See [`crate::Cli`]'s `otlp_protocol`.
+pub(crate) const DEFAULT_OTLP_PROTOCOL: &str = "http"; //< `OtlpProtocol` after parsing
+
+/// The default interval (in seconds) between OTLP exports.
+///
+/// See [`crate::Cli`]'s `otlp_interval`.
+pub(crate) const DEFAULT_OTLP_INTERVAL: &str = "60"; //< `u64` after parsing
+
+/// The default interval (in seconds) between remote write requests.
+///
+/// See [`crate::Cli`]'s `remote_write_interval`.
+pub(crate) const DEFAULT_REMOTE_WRITE_INTERVAL: &str = "15"; //< `u64` after parsing
+
+/// The default amount of remote write requests queued for sending.
+///
+/// See [`crate::Cli`]'s `remote_write_queue_capacity`.
+pub(crate) const DEFAULT_REMOTE_WRITE_QUEUE_CAPACITY: &str = "10"; //< `usize` after parsing
+
+/// The default interval (in seconds) between InfluxDB writes.
+///
+/// See [`crate::Cli`]'s `influx_interval`.
+pub(crate) const DEFAULT_INFLUX_INTERVAL: &str = "60"; //< `u64` after parsing
+
+/// The default line format of the StatsD gauges.
+///
+/// See [`crate::Cli`]'s `statsd_format`.
+pub(crate) const DEFAULT_STATSD_FORMAT: &str = "dogstatsd"; //< `StatsdFormat` after parsing
+
+/// The default prefix of the StatsD gauges names.
+///
+/// See [`crate::Cli`]'s `statsd_prefix`.
+pub(crate) const DEFAULT_STATSD_PREFIX: &str = "kommitted";
+
+/// The default interval (in seconds) between StatsD flushes.
+///
+/// See [`crate::Cli`]'s `statsd_interval`.
+pub(crate) const DEFAULT_STATSD_INTERVAL: &str = "10"; //< `u64` after parsing
+
+/// The default maximum size (in bytes) of a StatsD packet: fits the MTU of most networks.
+///
+/// See [`crate::Cli`]'s `statsd_max_packet_size`.
+pub(crate) const DEFAULT_STATSD_MAX_PACKET_SIZE: &str = "1432"; //< `usize` after parsing
+
+/// The default metrics profiles.
+///
+/// See [`crate::Cli`]'s `metrics_profiles`.
+pub(crate) const DEFAULT_METRICS_PROFILES: &str = "kommitted"; //< `Vec<MetricsProfile>` after parsing
+
+/// The default levels of detail at which consumer metrics are produced.
+///
+/// See [`crate::Cli`]'s `consumer_metrics`.
+pub(crate) const DEFAULT_CONSUMER_METRICS: &str = "partition,group-topic,group"; //< `Vec<ConsumerMetricsLevel>` after parsing
+
+/// The default granularity of the owner labels of consumer metrics.
+///
+/// See [`crate::Cli`]'s `owner_labels`.
+pub(crate) const DEFAULT_OWNER_LABELS: &str = "full"; //< `OwnerLabels` after parsing
+
+/// The default policy to drop series, when the consumer partitions limit is exceeded.
+///
+/// See [`crate::Cli`]'s `series_drop_policy`.
+pub(crate) const DEFAULT_SERIES_DROP_POLICY: &str = "lowest-lag-first"; //< `SeriesDropPolicy` after parsing
+
+/// The default amount of offset commits in the window used to evaluate consumers status.
+///
+/// See [`crate::Cli`]'s `status_window`.
+pub(crate) const DEFAULT_STATUS_WINDOW: &str = "10"; //< `usize` after parsing
+
+/// The default minimum time (in seconds) between the offset commits in the window used to evaluate consumers status.
+///
+/// See [`crate::Cli`]'s `status_min_distance`.
+pub(crate) const DEFAULT_STATUS_MIN_DISTANCE: &str = "1"; //< `u64` after parsing
+
+/// The default retention (in seconds) of the lag history.
+///
+/// See [`crate::Cli`]'s `lag_history_retention`.
+pub(crate) const DEFAULT_LAG_HISTORY_RETENTION: &str = "21600"; //< `u64` after parsing
+
+/// The default resolution (in seconds) of the lag history.
+///
+/// See [`crate::Cli`]'s `lag_history_resolution`.
+pub(crate) const DEFAULT_LAG_HISTORY_RESOLUTION: &str = "60"; //< `u64` after parsing
+
+/// The default maximum amount of samples held by the lag history.
+///
+/// See [`crate::Cli`]'s `lag_history_max_samples`.
+pub(crate) const DEFAULT_LAG_HISTORY_MAX_SAMPLES: &str = "1000000"; //< `usize` after parsing
+
 /// The default `cluster_id` value, if none is provided (either via CLI override, nor Cluster configuration).
 pub(crate) const DEFAULT_CLUSTER_ID: &str = "__not-set__";
@@ -10,7 +10,7 @@ use std::{
 };
 use tokio::{
     sync::mpsc,
-    task::JoinHandle,
+    task::{block_in_place, JoinHandle},
     time::{interval, Duration},
 };
 use tokio_util::sync::CancellationToken;
@@ -188,10 +188,9 @@ impl Emitter for ConsumerGroupsEmitter {
             loop {
                 // Fetch Consumer Groups and update timer metrics
                 let timer = metric_cg_fetch.start_timer();
-                let res_cg = admin_client
-                    .inner()
-                    .fetch_group_list(None, FETCH_TIMEOUT)
-                    .map(Self::Emitted::from);
+                let res_cg =
+                    block_in_place(|| admin_client.inner().fetch_group_list(None, FETCH_TIMEOUT))
+                        .map(Self::Emitted::from);
                 timer.observe_duration();
 
                 match res_cg {
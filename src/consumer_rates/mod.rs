@@ -0,0 +1,76 @@
+//! Estimation of the rates at which Topic Partitions are produced to and consumed,
+//! and of how long consumers will take to catch up with producers.
+//!
+//! Produce rates are derived from the tracked offsets of each Topic Partition,
+//! consume rates from the sliding window of [`crate::lag_register::Lag`] of each consumer.
+
+// Inner modules
+mod rates;
+
+// Exports
+pub use rates::PartitionRates;
+
+// Imports
+use std::collections::HashMap;
+
+use crate::kafka_types::TopicPartition;
+use crate::lag_register::{GroupWithLag, LagWithOwner};
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+/// Estimate the [`PartitionRates`] of a consumed Topic Partition.
+///
+/// # Arguments
+///
+/// * `lwo` - [`LagWithOwner`] of the consumer of the Topic Partition
+/// * `produce_rate` - Offsets produced per second to the Topic Partition, if known
+pub fn evaluate_partition(lwo: &LagWithOwner, produce_rate: Option<f64>) -> PartitionRates {
+    let consume_rate = rates::consume_rate(&lwo.window);
+
+    PartitionRates {
+        produce_rate,
+        consume_rate,
+        catch_up_eta: lwo
+            .lag
+            .as_ref()
+            .and_then(|l| rates::catch_up_eta(l.offset_lag, produce_rate, consume_rate)),
+    }
+}
+
+/// Estimate the [`PartitionRates`] of every Topic Partition consumed by the given [`GroupWithLag`].
+pub async fn evaluate_group(
+    gwl: &GroupWithLag,
+    po_reg: &PartitionOffsetsRegister,
+) -> HashMap<TopicPartition, PartitionRates> {
+    let mut res = HashMap::with_capacity(gwl.lag_by_topic_partition.len());
+    for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+        let produce_rate = po_reg.estimate_produce_rate(tp).await.ok();
+        res.insert(tp.clone(), evaluate_partition(lwo, produce_rate));
+    }
+    res
+}
+
+/// Estimate the [`PartitionRates`] of every Topic Partition consumed by each Consumer Group,
+/// indexed by group name and Topic Partition.
+pub async fn evaluate_groups<'a>(
+    lag_by_group: &'a HashMap<String, GroupWithLag>,
+    po_reg: &PartitionOffsetsRegister,
+) -> HashMap<(&'a str, &'a TopicPartition), PartitionRates> {
+    // Produce rates are shared by all the Consumer Groups consuming the same Topic Partition
+    let mut produce_rates: HashMap<&TopicPartition, Option<f64>> = HashMap::new();
+
+    let mut res = HashMap::new();
+    for (g, gwl) in lag_by_group.iter() {
+        for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+            let produce_rate = match produce_rates.get(tp) {
+                Some(pr) => *pr,
+                None => {
+                    let pr = po_reg.estimate_produce_rate(tp).await.ok();
+                    produce_rates.insert(tp, pr);
+                    pr
+                },
+            };
+            res.insert((g.as_str(), tp), evaluate_partition(lwo, produce_rate));
+        }
+    }
+    res
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+
+use chrono::Duration;
+use serde::{Serialize, Serializer};
+
+use crate::lag_register::Lag;
+
+/// Estimated time for the lag of a consumer to reach zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatchUpEta {
+    /// The consumer is slower than the producers: the lag will not reach zero.
+    Never,
+
+    /// The lag will reach zero after the given [`Duration`].
+    In(Duration),
+}
+
+impl CatchUpEta {
+    /// The ETA expressed in milliseconds, or `-1` if [`CatchUpEta::Never`].
+    pub fn as_millis(&self) -> i64 {
+        match self {
+            CatchUpEta::Never => -1,
+            CatchUpEta::In(d) => d.num_milliseconds(),
+        }
+    }
+}
+
+impl Serialize for CatchUpEta {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.as_millis())
+    }
+}
+
+/// Rates at which a Topic Partition is produced to, and consumed by a Consumer Group.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct PartitionRates {
+    /// Offsets produced per second, if known.
+    pub produce_rate: Option<f64>,
+
+    /// Offsets consumed per second by the Consumer Group, if known.
+    pub consume_rate: Option<f64>,
+
+    /// Estimated time for the lag to reach zero, if known: `-1` (ms) means never.
+    #[serde(rename = "catch_up_eta_ms")]
+    pub catch_up_eta: Option<CatchUpEta>,
+}
+
+/// Estimate the rate at which offsets are consumed (per second), from the sliding window of [`Lag`].
+///
+/// Needs at least 2 [`Lag`]s in the window, committed at different times.
+pub fn consume_rate(window: &VecDeque<Lag>) -> Option<f64> {
+    let (first, latest) = (window.front()?, window.back()?);
+
+    let elapsed_ms = (latest.offset_timestamp - first.offset_timestamp).num_milliseconds();
+    if elapsed_ms <= 0 {
+        return None;
+    }
+
+    Some(latest.offset.saturating_sub(first.offset) as f64 * 1000.0 / elapsed_ms as f64)
+}
+
+/// Estimate the [`CatchUpEta`] of a consumer, from its current offset lag and the rates.
+///
+/// If the lag is already zero, the ETA is zero as well.
+/// Otherwise, it needs both rates to be known.
+pub fn catch_up_eta(
+    offset_lag: u64,
+    produce_rate: Option<f64>,
+    consume_rate: Option<f64>,
+) -> Option<CatchUpEta> {
+    if offset_lag == 0 {
+        return Some(CatchUpEta::In(Duration::zero()));
+    }
+
+    let net_rate = consume_rate? - produce_rate?;
+    if net_rate <= 0.0 {
+        return Some(CatchUpEta::Never);
+    }
+
+    Some(CatchUpEta::In(Duration::milliseconds((offset_lag as f64 / net_rate * 1000.0) as i64)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use chrono::{Duration, Utc};
+
+    use super::{catch_up_eta, consume_rate, CatchUpEta};
+    use crate::lag_register::Lag;
+
+    fn window(commits: &[(u64, i64)]) -> VecDeque<Lag> {
+        let base = Utc::now();
+        commits
+            .iter()
+            .map(|(offset, at_s)| Lag {
+                offset: *offset,
+                offset_timestamp: base + Duration::seconds(*at_s),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn estimate_consume_rate() {
+        assert_eq!(consume_rate(&window(&[])), None);
+        assert_eq!(consume_rate(&window(&[(100, 0)])), None);
+        assert_eq!(consume_rate(&window(&[(100, 0), (200, 0)])), None);
+        assert_eq!(consume_rate(&window(&[(100, 0), (150, 5), (300, 10)])), Some(20.0));
+        assert_eq!(consume_rate(&window(&[(100, 0), (100, 10)])), Some(0.0));
+    }
+
+    #[test]
+    fn estimate_catch_up_eta() {
+        let zero = Some(CatchUpEta::In(Duration::zero()));
+        assert_eq!(catch_up_eta(0, None, None), zero);
+        assert_eq!(catch_up_eta(100, Some(10.0), None), None);
+        assert_eq!(catch_up_eta(100, None, Some(10.0)), None);
+        assert_eq!(catch_up_eta(100, Some(10.0), Some(10.0)), Some(CatchUpEta::Never));
+        assert_eq!(catch_up_eta(100, Some(10.0), Some(5.0)), Some(CatchUpEta::Never));
+        assert_eq!(
+            catch_up_eta(100, Some(10.0), Some(30.0)),
+            Some(CatchUpEta::In(Duration::seconds(5)))
+        );
+        assert_eq!(CatchUpEta::Never.as_millis(), -1);
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::lag_register::Lag;
+
+use super::ConsumerStatus;
+
+/// Evaluate the [`ConsumerStatus`] of a consumed Topic Partition, from its sliding window of [`Lag`].
+///
+/// Rules are evaluated in order, and the first that applies determines the status:
+///
+/// 1. No [`Lag`] at all: [`ConsumerStatus::NotFound`]
+/// 2. Latest committed offset behind the earliest available offset: [`ConsumerStatus::Err`]
+/// 3. Latest lag is zero: [`ConsumerStatus::Ok`]
+/// 4. The window spans no time at all: [`ConsumerStatus::NotFound`]
+/// 5. Time since the latest commit is longer than the window spans: [`ConsumerStatus::Stop`]
+/// 6. Committed offset never moved across the window: [`ConsumerStatus::Stall`]
+/// 7. Lag was zero at some point in the window: [`ConsumerStatus::Ok`]
+/// 8. Lag strictly increased across the whole window: [`ConsumerStatus::Warn`]
+///
+/// Otherwise, [`ConsumerStatus::Ok`].
+/// Rules 4 to 8 need at least 2 [`Lag`]s in the window.
+///
+/// # Arguments
+///
+/// * `window` - Most recent [`Lag`]s of the consumer, oldest first
+/// * `earliest_available_offset` - Earliest offset available in the Topic Partition (if known)
+/// * `now` - Instant of the evaluation
+pub fn evaluate(
+    window: &VecDeque<Lag>,
+    earliest_available_offset: Option<u64>,
+    now: DateTime<Utc>,
+) -> ConsumerStatus {
+    let (Some(first), Some(latest)) = (window.front(), window.back()) else {
+        return ConsumerStatus::NotFound;
+    };
+
+    if earliest_available_offset.is_some_and(|eao| latest.offset < eao) {
+        return ConsumerStatus::Err;
+    }
+
+    if latest.offset_lag == 0 || window.len() < 2 {
+        return ConsumerStatus::Ok;
+    }
+
+    let span = latest.offset_timestamp - first.offset_timestamp;
+    if span <= Duration::zero() {
+        return ConsumerStatus::NotFound;
+    }
+
+    if now - latest.offset_timestamp > span {
+        return ConsumerStatus::Stop;
+    }
+
+    if window.iter().all(|l| l.offset == first.offset) {
+        return ConsumerStatus::Stall;
+    }
+
+    if window.iter().any(|l| l.offset_lag == 0) {
+        return ConsumerStatus::Ok;
+    }
+
+    let lag_increasing = window
+        .iter()
+        .zip(window.iter().skip(1))
+        .all(|(prev, next)| next.offset_lag > prev.offset_lag);
+    if lag_increasing {
+        return ConsumerStatus::Warn;
+    }
+
+    ConsumerStatus::Ok
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Build a window of commits, one every 10 seconds, ending 5 seconds before `now`.
+    fn window(now: DateTime<Utc>, offsets_and_lags: &[(u64, u64)]) -> VecDeque<Lag> {
+        let count = offsets_and_lags.len() as i64;
+        offsets_and_lags
+            .iter()
+            .enumerate()
+            .map(|(i, (offset, offset_lag))| Lag {
+                offset: *offset,
+                offset_timestamp: now - Duration::seconds(5 + (count - 1 - i as i64) * 10),
+                offset_lag: *offset_lag,
+                time_lag: Duration::zero(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_evaluate_ok() {
+        let now = Utc::now();
+
+        assert_eq!(evaluate(&window(now, &[(10, 5), (20, 0)]), Some(0), now), ConsumerStatus::Ok);
+        assert_eq!(
+            evaluate(&window(now, &[(10, 5), (20, 8), (30, 3)]), Some(0), now),
+            ConsumerStatus::Ok
+        );
+        assert_eq!(evaluate(&window(now, &[(10, 5)]), None, now), ConsumerStatus::Ok);
+    }
+
+    #[test]
+    fn should_evaluate_not_ok() {
+        let now = Utc::now();
+
+        assert_eq!(evaluate(&VecDeque::new(), None, now), ConsumerStatus::NotFound);
+        assert_eq!(evaluate(&window(now, &[(10, 5), (20, 0)]), Some(50), now), ConsumerStatus::Err);
+        assert_eq!(
+            evaluate(&window(now, &[(10, 5), (20, 8), (30, 9)]), Some(0), now),
+            ConsumerStatus::Warn
+        );
+        assert_eq!(
+            evaluate(&window(now, &[(10, 5), (10, 8), (10, 9)]), Some(0), now),
+            ConsumerStatus::Stall
+        );
+
+        // Last commit happened longer ago than the window spans
+        let later = now + Duration::minutes(5);
+        assert_eq!(
+            evaluate(&window(now, &[(10, 5), (20, 8), (30, 9)]), Some(0), later),
+            ConsumerStatus::Stop
+        );
+    }
+
+    #[test]
+    fn should_not_stop_on_zero_span() {
+        let now = Utc::now();
+        let mut same_time = window(now, &[(10, 5), (20, 8), (30, 9)]);
+        for l in same_time.iter_mut() {
+            l.offset_timestamp = now - Duration::seconds(5);
+        }
+
+        assert_eq!(evaluate(&same_time, Some(0), now), ConsumerStatus::NotFound);
+    }
+
+    #[test]
+    fn should_find_worst_status() {
+        use ConsumerStatus::*;
+
+        assert_eq!(ConsumerStatus::worst([]), NotFound);
+        assert_eq!(ConsumerStatus::worst([Ok, Warn, Ok]), Warn);
+        assert_eq!(ConsumerStatus::worst([Stall, Err, Stop]), Err);
+        assert_eq!(ConsumerStatus::worst([NotFound, Ok]), Ok);
+    }
+}
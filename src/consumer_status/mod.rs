@@ -0,0 +1,63 @@
+//! Evaluation of the status of consumers, from the recent history of their lag.
+//!
+//! Raw lag numbers need interpretation: this turns them into a simple status,
+//! for each consumed Topic Partition and rolled up for each Consumer Group.
+
+// Inner modules
+mod evaluator;
+mod status;
+
+// Exports
+pub use status::ConsumerStatus;
+
+// Imports
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::kafka_types::TopicPartition;
+use crate::lag_register::GroupWithLag;
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+/// Status of a Consumer Group, and of each Topic Partition it consumes.
+#[derive(Debug, Clone, Default)]
+pub struct GroupStatus {
+    /// Worst status of all the Topic Partitions consumed by the Group.
+    pub status: ConsumerStatus,
+
+    /// Status of each Topic Partition consumed by the Group.
+    pub partitions: HashMap<TopicPartition, ConsumerStatus>,
+}
+
+/// Evaluate the [`GroupStatus`] of the given [`GroupWithLag`].
+///
+/// See [`evaluator::evaluate`] for the rules applied to each consumed Topic Partition.
+pub async fn evaluate_group(
+    gwl: &GroupWithLag,
+    po_reg: &PartitionOffsetsRegister,
+    now: DateTime<Utc>,
+) -> GroupStatus {
+    let mut partitions = HashMap::with_capacity(gwl.lag_by_topic_partition.len());
+    for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+        let eao = po_reg.get_earliest_available_offset(tp).await.ok();
+        partitions.insert(tp.clone(), evaluator::evaluate(&lwo.window, eao, now));
+    }
+
+    GroupStatus {
+        status: ConsumerStatus::worst(partitions.values().copied()),
+        partitions,
+    }
+}
+
+/// Evaluate the [`GroupStatus`] of all the given Consumer Groups, indexed by group name.
+pub async fn evaluate_groups(
+    lag_by_group: &HashMap<String, GroupWithLag>,
+    po_reg: &PartitionOffsetsRegister,
+    now: DateTime<Utc>,
+) -> HashMap<String, GroupStatus> {
+    let mut statuses = HashMap::with_capacity(lag_by_group.len());
+    for (g, gwl) in lag_by_group.iter() {
+        statuses.insert(g.clone(), evaluate_group(gwl, po_reg, now).await);
+    }
+    statuses
+}
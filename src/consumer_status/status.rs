@@ -0,0 +1,58 @@
+use serde::Serialize;
+
+/// Status of a consumer, following the rules made popular by [Burrow](https://github.com/linkedin/Burrow/wiki/Consumer-Lag-Evaluation-Rules).
+///
+/// The numeric codes match the ones used by Burrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ConsumerStatus {
+    /// Not enough information to evaluate (e.g. no offset committed yet).
+    #[default]
+    NotFound,
+
+    /// Consuming as expected.
+    Ok,
+
+    /// Lag is strictly increasing across the window.
+    Warn,
+
+    /// Committed offset is behind the earliest available offset: messages were lost.
+    Err,
+
+    /// Commits stopped entirely, while lag is non-zero.
+    Stop,
+
+    /// Committed offsets are not moving, while lag is non-zero.
+    Stall,
+}
+
+impl ConsumerStatus {
+    /// Numeric code of the status, as used by Burrow.
+    pub fn code(&self) -> u8 {
+        match self {
+            ConsumerStatus::NotFound => 0,
+            ConsumerStatus::Ok => 1,
+            ConsumerStatus::Warn => 2,
+            ConsumerStatus::Err => 3,
+            ConsumerStatus::Stop => 4,
+            ConsumerStatus::Stall => 5,
+        }
+    }
+
+    /// How severe the status is, to find the worst of many.
+    fn severity(&self) -> u8 {
+        match self {
+            ConsumerStatus::NotFound => 0,
+            ConsumerStatus::Ok => 1,
+            ConsumerStatus::Warn => 2,
+            ConsumerStatus::Stall => 3,
+            ConsumerStatus::Stop => 4,
+            ConsumerStatus::Err => 5,
+        }
+    }
+
+    /// The worst of the given statuses ([`ConsumerStatus::NotFound`] if there are none).
+    pub fn worst<I: IntoIterator<Item = ConsumerStatus>>(statuses: I) -> ConsumerStatus {
+        statuses.into_iter().max_by_key(ConsumerStatus::severity).unwrap_or_default()
+    }
+}
@@ -0,0 +1,33 @@
+//! Detection of data loss: messages deleted by retention before a Consumer Group consumed them.
+//!
+//! Data loss is both detected when it happens, and predicted from the Topics retention configuration.
+
+// Inner modules
+mod prediction;
+mod register;
+
+// Exports
+pub use prediction::predict_time_to_data_loss;
+pub use register::{DataLoss, DataLossRegister};
+
+// Imports
+use std::sync::Arc;
+
+use tokio::{sync::watch, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::lag_register::LagRegister;
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+pub fn init(
+    lag_reg: Arc<LagRegister>,
+    po_reg: Arc<PartitionOffsetsRegister>,
+    replay_caught_up: watch::Receiver<bool>,
+    shutdown_token: CancellationToken,
+) -> (DataLossRegister, JoinHandle<()>) {
+    let (dl_reg, dl_join) =
+        DataLossRegister::new(lag_reg, po_reg, replay_caught_up, shutdown_token);
+
+    debug!("Initialized");
+    (dl_reg, dl_join)
+}
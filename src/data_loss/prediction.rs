@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::kafka_types::{TopicConfig, TopicPartition};
+use crate::lag_register::{GroupWithLag, Lag};
+use crate::partition_offsets::{PartitionOffsetsRegister, TrackedOffset};
+
+/// Estimate how long until retention deletes messages a consumer has not consumed yet.
+///
+/// Messages are deleted once older than `retention.ms`, so the oldest unconsumed message
+/// is deleted when its age reaches it. Its age is the time lag but, if the committed offset
+/// is behind the earliest [`TrackedOffset`], it was produced before that: the time lag,
+/// extrapolated outside of the tracked offsets, is then bound by the age of the earliest one.
+///
+/// Size-based retention (`retention.bytes`) is not considered, as partition sizes are not tracked.
+///
+/// Returns `None` for compact-only Topics, if there is nothing left to consume,
+/// or if retention is unlimited. Returns zero if data is already being lost.
+///
+/// # Arguments
+///
+/// * `config` - [`TopicConfig`] of the consumed Topic
+/// * `lag` - Current [`Lag`] of the consumer
+/// * `earliest_tracked` - Earliest [`TrackedOffset`] of the Topic Partition, if any
+/// * `now` - Current time, to measure the age of `earliest_tracked`
+pub fn time_to_data_loss(
+    config: &TopicConfig,
+    lag: &Lag,
+    earliest_tracked: Option<&TrackedOffset>,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    if config.is_compact_only() || lag.offset_lag == 0 {
+        return None;
+    }
+
+    let age = match earliest_tracked {
+        Some(et) if lag.offset < et.offset => lag.time_lag.max(now - et.at),
+        _ => lag.time_lag,
+    };
+    let ttdl = config.retention_time()? - age;
+
+    Some(ttdl.max(Duration::zero()))
+}
+
+/// Predict the [`time_to_data_loss`] of every Topic Partition consumed by each Consumer Group,
+/// indexed by group name and Topic Partition.
+///
+/// Topic Partitions without a prediction (see [`time_to_data_loss`]) are omitted.
+pub async fn predict_time_to_data_loss<'a>(
+    lag_by_group: &'a HashMap<String, GroupWithLag>,
+    topic_configs: &HashMap<String, TopicConfig>,
+    po_reg: &PartitionOffsetsRegister,
+) -> HashMap<(&'a str, &'a TopicPartition), Duration> {
+    let mut predictions = HashMap::new();
+    let now = Utc::now();
+
+    for (g, gwl) in lag_by_group.iter() {
+        for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+            let (Some(lag), Some(config)) = (lwo.lag.as_ref(), topic_configs.get(&tp.topic)) else {
+                continue;
+            };
+            let earliest_tracked = po_reg.get_earliest_tracked_offset(tp).await.ok();
+
+            if let Some(ttdl) = time_to_data_loss(config, lag, earliest_tracked.as_ref(), now) {
+                predictions.insert((g.as_str(), tp), ttdl);
+            }
+        }
+    }
+
+    predictions
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+
+    use super::time_to_data_loss;
+    use crate::kafka_types::TopicConfig;
+    use crate::lag_register::Lag;
+    use crate::partition_offsets::TrackedOffset;
+
+    fn config(retention_ms: i64, retention_bytes: i64, cleanup_policy: &str) -> TopicConfig {
+        TopicConfig {
+            retention_ms: Some(retention_ms),
+            retention_bytes: Some(retention_bytes),
+            cleanup_policy: Some(cleanup_policy.to_string()),
+        }
+    }
+
+    fn lag(offset: u64, offset_lag: u64, time_lag_s: i64) -> Lag {
+        Lag {
+            offset,
+            offset_timestamp: Utc::now(),
+            offset_lag,
+            time_lag: Duration::seconds(time_lag_s),
+        }
+    }
+
+    #[test]
+    fn predict_by_time() {
+        let c = config(3_600_000, -1, "delete");
+        let now = Utc::now();
+
+        assert_eq!(
+            time_to_data_loss(&c, &lag(100, 10, 600), None, now),
+            Some(Duration::minutes(50))
+        );
+        assert_eq!(time_to_data_loss(&c, &lag(100, 10, 7200), None, now), Some(Duration::zero()));
+        assert_eq!(time_to_data_loss(&c, &lag(100, 0, 0), None, now), None);
+    }
+
+    #[test]
+    fn predict_behind_earliest_tracked_offset() {
+        let c = config(3_600_000, -1, "delete");
+        let now = Utc::now();
+        let earliest = TrackedOffset {
+            offset: 1000,
+            at: now - Duration::minutes(40),
+        };
+
+        // Behind the earliest tracked offset: produced at least 40 minutes ago
+        assert_eq!(
+            time_to_data_loss(&c, &lag(900, 10, 600), Some(&earliest), now),
+            Some(Duration::minutes(20))
+        );
+
+        // Within the tracked offsets, the time lag applies
+        assert_eq!(
+            time_to_data_loss(&c, &lag(1500, 10, 600), Some(&earliest), now),
+            Some(Duration::minutes(50))
+        );
+    }
+
+    #[test]
+    fn ignore_size_retention() {
+        let c = config(-1, 1_000, "delete");
+        assert_eq!(time_to_data_loss(&c, &lag(1500, 10, 600), None, Utc::now()), None);
+    }
+
+    #[test]
+    fn skip_compacted_and_unlimited() {
+        let lag = lag(100, 10, 600);
+        let now = Utc::now();
+
+        assert_eq!(time_to_data_loss(&config(3_600_000, -1, "compact"), &lag, None, now), None);
+        assert_eq!(
+            time_to_data_loss(&config(3_600_000, -1, "delete,compact"), &lag, None, now),
+            Some(Duration::minutes(50))
+        );
+        assert_eq!(time_to_data_loss(&config(-1, -1, "delete"), &lag, None, now), None);
+        assert_eq!(time_to_data_loss(&TopicConfig::default(), &lag, None, now), None);
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::{
+    sync::{watch, RwLock},
+    task::JoinHandle,
+    time::{interval, Duration, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::kafka_types::TopicPartition;
+use crate::lag_register::LagRegister;
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+/// How often committed offsets are compared with the earliest available offsets.
+const DETECTION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Data lost by a Consumer Group on a Topic Partition: its committed offset
+/// is behind the earliest offset still available in the partition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DataLoss {
+    pub group: String,
+
+    #[serde(flatten)]
+    pub topic_partition: TopicPartition,
+
+    /// Offset committed by the Consumer Group.
+    pub committed_offset: u64,
+
+    /// Earliest offset still available in the Topic Partition.
+    pub earliest_available_offset: u64,
+
+    /// Amount of offsets deleted before the Consumer Group consumed them.
+    pub offsets_lost: u64,
+
+    /// When the data loss was first detected.
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Committed offset of a Consumer Group on a Topic Partition, observed alongside
+/// the earliest offset available in the partition.
+struct Observation {
+    group: String,
+    tp: TopicPartition,
+    committed_offset: u64,
+    earliest_available_offset: u64,
+}
+
+#[derive(Debug, Default)]
+struct DataLossState {
+    /// Data loss currently ongoing, by Consumer Group and Topic Partition.
+    ongoing: HashMap<String, HashMap<TopicPartition, DataLoss>>,
+
+    /// Amount of data loss incidents detected since start, by Consumer Group and Topic.
+    incidents: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl DataLossState {
+    /// Update the state with the latest [`Observation`]s, replacing the ongoing data loss.
+    ///
+    /// A new incident begins when a Consumer Group falls behind the earliest available offset,
+    /// and lasts until it catches up (or stops consuming the Topic Partition).
+    ///
+    /// Until the replay of `__consumer_offsets` has caught up (`replay_caught_up`), committed offsets
+    /// can be stale, superseded by commits yet to be replayed: nothing is detected.
+    ///
+    /// Returns the incidents that began with this update.
+    fn update(
+        &mut self,
+        observations: Vec<Observation>,
+        replay_caught_up: bool,
+        now: DateTime<Utc>,
+    ) -> Vec<DataLoss> {
+        if !replay_caught_up {
+            return Vec::new();
+        }
+
+        let mut previous = std::mem::take(&mut self.ongoing);
+        let mut began = Vec::new();
+
+        for o in observations {
+            if o.committed_offset >= o.earliest_available_offset {
+                continue;
+            }
+
+            let ongoing = previous.get_mut(&o.group).and_then(|g| g.remove(&o.tp));
+            let detected_at = match ongoing.as_ref() {
+                Some(dl) => dl.detected_at,
+                None => {
+                    *self
+                        .incidents
+                        .entry(o.group.clone())
+                        .or_default()
+                        .entry(o.tp.topic.clone())
+                        .or_default() += 1;
+                    now
+                },
+            };
+
+            let dl = DataLoss {
+                group: o.group.clone(),
+                topic_partition: o.tp.clone(),
+                committed_offset: o.committed_offset,
+                earliest_available_offset: o.earliest_available_offset,
+                offsets_lost: o.earliest_available_offset - o.committed_offset,
+                detected_at,
+            };
+            if ongoing.is_none() {
+                began.push(dl.clone());
+            }
+            self.ongoing.entry(o.group).or_default().insert(o.tp, dl);
+        }
+
+        for (g, tps) in previous {
+            for tp in tps.keys() {
+                info!("Data loss ended: group '{g}' on {}:{}", tp.topic, tp.partition);
+            }
+        }
+
+        began
+    }
+}
+
+/// Detects Consumer Groups whose committed offset falls behind the earliest available offset,
+/// meaning that retention deleted messages before they were consumed.
+pub struct DataLossRegister {
+    state: Arc<RwLock<DataLossState>>,
+}
+
+impl DataLossRegister {
+    /// Create a new [`Self`], and spawn the task that periodically detects data loss.
+    ///
+    /// # Arguments
+    ///
+    /// * `lag_reg` - The [`LagRegister`] providing the committed offsets
+    /// * `po_reg` - The [`PartitionOffsetsRegister`] providing the earliest available offsets
+    /// * `replay_caught_up` - Whether the replay of `__consumer_offsets` has caught up
+    /// * `shutdown_token` - A [`CancellationToken`] that, when cancelled, will make the detection task terminate
+    pub fn new(
+        lag_reg: Arc<LagRegister>,
+        po_reg: Arc<PartitionOffsetsRegister>,
+        replay_caught_up: watch::Receiver<bool>,
+        shutdown_token: CancellationToken,
+    ) -> (Self, JoinHandle<()>) {
+        let dlr = Self {
+            state: Arc::new(RwLock::new(DataLossState::default())),
+        };
+
+        let state_clone = dlr.state.clone();
+        let join_handle = tokio::spawn(async move {
+            let mut interval = interval(DETECTION_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        let observations = observe(&lag_reg, &po_reg).await;
+                        let caught_up = *replay_caught_up.borrow();
+                        let began = state_clone.write().await.update(observations, caught_up, Utc::now());
+                        for dl in began {
+                            warn!(
+                                "Data loss: group '{}' committed offset {} on {}:{}, but earliest available is {} ({} offsets lost)",
+                                dl.group,
+                                dl.committed_offset,
+                                dl.topic_partition.topic,
+                                dl.topic_partition.partition,
+                                dl.earliest_available_offset,
+                                dl.offsets_lost,
+                            );
+                        }
+                    },
+                    _ = shutdown_token.cancelled() => {
+                        info!("Shutting down");
+                        break;
+                    },
+                }
+            }
+        });
+
+        (dlr, join_handle)
+    }
+
+    /// All the [`DataLoss`] currently ongoing, sorted by Consumer Group and Topic Partition.
+    pub async fn ongoing(&self) -> Vec<DataLoss> {
+        let mut res = self
+            .state
+            .read()
+            .await
+            .ongoing
+            .values()
+            .flat_map(|tps| tps.values().cloned())
+            .collect::<Vec<DataLoss>>();
+        res.sort_by(|a, b| (&a.group, &a.topic_partition).cmp(&(&b.group, &b.topic_partition)));
+        res
+    }
+
+    /// Amount of data loss incidents detected since start, as `(group, topic, count)`.
+    pub async fn incidents(&self) -> Vec<(String, String, u64)> {
+        self.state
+            .read()
+            .await
+            .incidents
+            .iter()
+            .flat_map(|(g, topics)| topics.iter().map(|(t, c)| (g.clone(), t.clone(), *c)))
+            .collect()
+    }
+}
+
+/// Observe the committed offsets of all the Consumer Groups in the [`LagRegister`],
+/// alongside the earliest available offset of each Topic Partition.
+async fn observe(lag_reg: &LagRegister, po_reg: &PartitionOffsetsRegister) -> Vec<Observation> {
+    let lag_by_group = lag_reg.lag_by_group.read().await;
+
+    let mut observations = Vec::new();
+    for (g, gwl) in lag_by_group.iter() {
+        for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+            let Some(lag) = lwo.lag.as_ref() else {
+                continue;
+            };
+
+            match po_reg.get_earliest_available_offset(tp).await {
+                Ok(eao) => observations.push(Observation {
+                    group: g.clone(),
+                    tp: tp.clone(),
+                    committed_offset: lag.offset,
+                    earliest_available_offset: eao,
+                }),
+                Err(e) => {
+                    debug!("Unable to check data loss of group '{g}': {e}");
+                },
+            }
+        }
+    }
+    observations
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+
+    use super::{DataLossState, Observation};
+    use crate::kafka_types::TopicPartition;
+
+    fn obs(group: &str, partition: u32, committed: u64, earliest: u64) -> Observation {
+        Observation {
+            group: group.to_string(),
+            tp: TopicPartition::new("t".to_string(), partition),
+            committed_offset: committed,
+            earliest_available_offset: earliest,
+        }
+    }
+
+    #[test]
+    fn stale_replayed_commit_is_not_data_loss() {
+        let mut state = DataLossState::default();
+        let t0 = Utc::now();
+
+        // While replaying, an old commit is behind the earliest available offset
+        assert!(state.update(vec![obs("g", 0, 10, 150)], false, t0).is_empty());
+        assert!(state.ongoing.is_empty());
+        assert!(state.incidents.is_empty());
+
+        // Once caught up, the latest commit is observed instead
+        let t1 = t0 + Duration::seconds(5);
+        assert!(state.update(vec![obs("g", 0, 180, 150)], true, t1).is_empty());
+        assert!(state.incidents.is_empty());
+    }
+
+    #[test]
+    fn incident_lasts_until_caught_up() {
+        let mut state = DataLossState::default();
+        let t0 = Utc::now();
+
+        // No data loss
+        assert!(state.update(vec![obs("g", 0, 100, 50)], true, t0).is_empty());
+        assert!(state.ongoing.is_empty());
+
+        // Retention deleted offsets the group did not consume
+        let began = state.update(vec![obs("g", 0, 100, 150), obs("g", 1, 10, 10)], true, t0);
+        assert_eq!(began.len(), 1);
+        assert_eq!(began[0].offsets_lost, 50);
+        assert_eq!(state.incidents["g"]["t"], 1);
+
+        // Still ongoing: same incident, updated amount of offsets lost
+        let t1 = t0 + Duration::seconds(5);
+        assert!(state.update(vec![obs("g", 0, 120, 200)], true, t1).is_empty());
+        let dl = &state.ongoing["g"][&TopicPartition::new("t".to_string(), 0)];
+        assert_eq!(dl.offsets_lost, 80);
+        assert_eq!(dl.detected_at, t0);
+        assert_eq!(state.incidents["g"]["t"], 1);
+
+        // Caught up, then fell behind again: a new incident
+        let t2 = t1 + Duration::seconds(5);
+        state.update(vec![obs("g", 0, 200, 200)], true, t2);
+        assert!(state.ongoing.is_empty());
+        let t3 = t2 + Duration::seconds(5);
+        assert_eq!(state.update(vec![obs("g", 0, 200, 300)], true, t3).len(), 1);
+        assert_eq!(state.incidents["g"]["t"], 2);
+    }
+}
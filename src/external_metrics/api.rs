@@ -0,0 +1,265 @@
+//! Resources of the `external.metrics.k8s.io/v1beta1` API, as served to the Kubernetes API Aggregation Layer.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use const_format::formatcp;
+use serde::{Deserialize, Serialize};
+
+use crate::lag_register::{LagAggregate, LagRegister};
+use crate::prometheus_metrics::NAMESPACE;
+
+use super::selector::Selector;
+
+pub(super) const GROUP_NAME: &str = "external.metrics.k8s.io";
+pub(super) const VERSION: &str = "v1beta1";
+pub(super) const GROUP_VERSION: &str = formatcp!("{GROUP_NAME}/{VERSION}");
+
+/// Sum of the offset lag, across the Topic Partitions consumed by a Consumer Group.
+const METRIC_OFFSET_LAG: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_lag_offset_sum");
+
+/// Maximum time lag (milliseconds), across the Topic Partitions consumed by a Consumer Group.
+const METRIC_TIME_LAG: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_lag_milliseconds_max");
+
+const METRICS: [&str; 2] = [METRIC_OFFSET_LAG, METRIC_TIME_LAG];
+
+/// Kubernetes `ObjectMeta`/`ListMeta`: always empty, as nothing served here is stored.
+#[derive(Debug, Default, Serialize)]
+struct Meta {}
+
+/// Kubernetes `Status`, returned when a request fails.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sStatus {
+    kind: &'static str,
+    api_version: &'static str,
+    metadata: Meta,
+    status: &'static str,
+    message: String,
+    reason: &'static str,
+    code: u16,
+}
+
+fn k8s_error(status: StatusCode, reason: &'static str, message: impl Into<String>) -> Response {
+    (
+        status,
+        Json(K8sStatus {
+            kind: "Status",
+            api_version: "v1",
+            metadata: Meta::default(),
+            status: "Failure",
+            message: message.into(),
+            reason,
+            code: status.as_u16(),
+        }),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupVersionForDiscovery {
+    group_version: &'static str,
+    version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiGroup {
+    kind: &'static str,
+    api_version: &'static str,
+    name: &'static str,
+    versions: Vec<GroupVersionForDiscovery>,
+    preferred_version: GroupVersionForDiscovery,
+}
+
+impl ApiGroup {
+    fn new() -> Self {
+        ApiGroup {
+            kind: "APIGroup",
+            api_version: "v1",
+            name: GROUP_NAME,
+            versions: vec![GroupVersionForDiscovery {
+                group_version: GROUP_VERSION,
+                version: VERSION,
+            }],
+            preferred_version: GroupVersionForDiscovery {
+                group_version: GROUP_VERSION,
+                version: VERSION,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiGroupList {
+    kind: &'static str,
+    api_version: &'static str,
+    groups: Vec<ApiGroup>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiResource {
+    name: &'static str,
+    singular_name: &'static str,
+    namespaced: bool,
+    kind: &'static str,
+    verbs: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiResourceList {
+    kind: &'static str,
+    api_version: &'static str,
+    group_version: &'static str,
+    resources: Vec<ApiResource>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExternalMetricValue {
+    metric_name: String,
+    metric_labels: BTreeMap<&'static str, String>,
+    timestamp: DateTime<Utc>,
+    /// Kubernetes `Quantity`: an integer is always a valid one.
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExternalMetricValueList {
+    kind: &'static str,
+    api_version: &'static str,
+    metadata: Meta,
+    items: Vec<ExternalMetricValue>,
+}
+
+/// `GET /apis`
+pub(super) async fn api_groups() -> Response {
+    Json(ApiGroupList {
+        kind: "APIGroupList",
+        api_version: "v1",
+        groups: vec![ApiGroup::new()],
+    })
+    .into_response()
+}
+
+/// `GET /apis/external.metrics.k8s.io`
+pub(super) async fn api_group() -> Response {
+    Json(ApiGroup::new()).into_response()
+}
+
+/// `GET /apis/external.metrics.k8s.io/v1beta1`
+///
+/// Lists the external metrics that can be requested.
+pub(super) async fn api_resources() -> Response {
+    Json(ApiResourceList {
+        kind: "APIResourceList",
+        api_version: "v1",
+        group_version: GROUP_VERSION,
+        resources: METRICS
+            .iter()
+            .map(|m| ApiResource {
+                name: m,
+                singular_name: "",
+                namespaced: true,
+                kind: "ExternalMetricValueList",
+                verbs: vec!["get"],
+            })
+            .collect(),
+    })
+    .into_response()
+}
+
+/// Query parameters of [`metric_values`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MetricValuesParams {
+    /// Consumer Groups (and Topics) to measure (e.g. `group=my-group,topic=my-topic`):
+    /// defaults to all the Consumer Groups.
+    label_selector: Option<String>,
+}
+
+/// `GET /apis/external.metrics.k8s.io/v1beta1/namespaces/:namespace/:metric?labelSelector=`
+///
+/// Returns the value of the metric for each selected Consumer Group.
+/// The namespace is ignored: Consumer Groups don't belong to any.
+pub(super) async fn metric_values(
+    State(lag_reg): State<Arc<LagRegister>>,
+    Path((_namespace, metric)): Path<(String, String)>,
+    Query(params): Query<MetricValuesParams>,
+) -> Response {
+    let value: fn(&LagAggregate) -> i64 = match metric.as_str() {
+        // Saturate, rather than wrap around to a negative lag
+        METRIC_OFFSET_LAG => |agg| i64::try_from(agg.offset_lag_sum).unwrap_or(i64::MAX),
+        METRIC_TIME_LAG => |agg| agg.time_lag_max.num_milliseconds(),
+        _ => {
+            return k8s_error(
+                StatusCode::NOT_FOUND,
+                "NotFound",
+                format!("Unknown external metric '{metric}'"),
+            )
+        },
+    };
+
+    let selector = match Selector::parse(params.label_selector.as_deref().unwrap_or_default()) {
+        Ok(s) => s,
+        Err(e) => return k8s_error(StatusCode::BAD_REQUEST, "BadRequest", e.to_string()),
+    };
+
+    let lag_by_group = lag_reg.lag_by_group.read().await;
+    if let Some(g) = selector.group.as_ref() {
+        if !lag_by_group.contains_key(g) {
+            return k8s_error(StatusCode::NOT_FOUND, "NotFound", format!("Unknown group '{g}'"));
+        }
+    }
+
+    let now = Utc::now();
+    let mut items = Vec::new();
+    for (g, gwl) in
+        lag_by_group.iter().filter(|(g, _)| selector.group.as_ref().is_none_or(|sg| sg == *g))
+    {
+        let agg = match selector.topic.as_ref() {
+            None => gwl.aggregate(),
+            Some(t) => match gwl.aggregate_by_topic().remove(t.as_str()) {
+                Some(agg) => agg,
+                None => continue,
+            },
+        };
+
+        // Same as the Prometheus metrics, omitted if no partition lag is known
+        if agg.partitions_with_lag == 0 {
+            continue;
+        }
+
+        let mut metric_labels = BTreeMap::from([("group", g.clone())]);
+        if let Some(t) = selector.topic.as_ref() {
+            metric_labels.insert("topic", t.clone());
+        }
+
+        items.push(ExternalMetricValue {
+            metric_name: metric.clone(),
+            metric_labels,
+            timestamp: now,
+            value: value(&agg).to_string(),
+        });
+    }
+    items.sort_by(|a, b| a.metric_labels.cmp(&b.metric_labels));
+
+    Json(ExternalMetricValueList {
+        kind: "ExternalMetricValueList",
+        api_version: GROUP_VERSION,
+        metadata: Meta::default(),
+        items,
+    })
+    .into_response()
+}
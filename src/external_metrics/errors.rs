@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum ExternalMetricsError {
+    /// The TLS certificate and key to serve could not be loaded.
+    #[error("Failed to load TLS certificate '{0}' and key '{1}': {2}")]
+    Tls(String, String, #[source] std::io::Error),
+}
+
+pub type ExternalMetricsResult<T> = Result<T, ExternalMetricsError>;
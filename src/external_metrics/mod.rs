@@ -0,0 +1,91 @@
+//! Kubernetes [External Metrics API](https://github.com/kubernetes/design-proposals-archive/blob/main/instrumentation/external-metrics-api.md),
+//! to scale consumers via the `HorizontalPodAutoscaler` based on their lag.
+//!
+//! Served over HTTPS, so that Kommitted can be registered as the `APIService`
+//! of `v1beta1.external.metrics.k8s.io`, answering from the [`LagRegister`].
+
+// Inner modules
+mod api;
+mod errors;
+mod selector;
+
+// Exports
+pub use errors::{ExternalMetricsError, ExternalMetricsResult};
+
+// Imports
+use std::{net::SocketAddr, path::Path, sync::Arc, time::Duration};
+
+use axum::{routing::get, Router};
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use const_format::formatcp;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tower_http::timeout::TimeoutLayer;
+
+use crate::lag_register::LagRegister;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const GROUP_PATH: &str = formatcp!("/apis/{}", api::GROUP_NAME);
+const GROUP_VERSION_PATH: &str = formatcp!("/apis/{}", api::GROUP_VERSION);
+const METRIC_PATH: &str = formatcp!("/apis/{}/namespaces/:namespace/:metric", api::GROUP_VERSION);
+
+/// Load the TLS certificate (chain) and private key to serve, from PEM files.
+///
+/// Done before anything else is started, so that a bad certificate or key fails the startup.
+pub async fn load_tls_config(
+    tls_cert: &Path,
+    tls_key: &Path,
+) -> ExternalMetricsResult<RustlsConfig> {
+    // Only the `ring` crypto provider is compiled in: make it the process default
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    RustlsConfig::from_pem_file(tls_cert, tls_key).await.map_err(|e| {
+        ExternalMetricsError::Tls(tls_cert.display().to_string(), tls_key.display().to_string(), e)
+    })
+}
+
+/// Spawn the HTTPS server of the External Metrics API, listening on `listen_on`.
+///
+/// # Arguments
+///
+/// * `listen_on` - Address to listen on
+/// * `tls_config` - TLS certificate and key to serve (see [`load_tls_config`])
+/// * `lag_reg` - The [`LagRegister`] the metrics are computed from
+/// * `shutdown_token` - A [`CancellationToken`] that, when cancelled, will make the server terminate
+pub fn init(
+    listen_on: SocketAddr,
+    tls_config: RustlsConfig,
+    lag_reg: Arc<LagRegister>,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    let app = Router::new()
+        .route("/apis", get(api::api_groups))
+        .route(GROUP_PATH, get(api::api_group))
+        .route(GROUP_VERSION_PATH, get(api::api_resources))
+        .route(METRIC_PATH, get(api::metric_values))
+        .layer(TimeoutLayer::new(REQUEST_TIMEOUT))
+        .with_state(lag_reg);
+
+    let join_handle = tokio::spawn(async move {
+        // Shutdown gracefully, once the token is cancelled
+        let handle = Handle::new();
+        let handle_clone = handle.clone();
+        tokio::spawn(async move {
+            shutdown_token.cancelled().await;
+            handle_clone.graceful_shutdown(Some(REQUEST_TIMEOUT));
+        });
+
+        info!("External Metrics API listening on '{listen_on}' (HTTPS)");
+        if let Err(e) = axum_server::bind_rustls(listen_on, tls_config)
+            .handle(handle)
+            .serve(app.into_make_service())
+            .await
+        {
+            error!("External Metrics API failed: {e}");
+        }
+    });
+
+    debug!("Initialized");
+    join_handle
+}
@@ -0,0 +1,90 @@
+use thiserror::Error;
+
+const LABEL_GROUP: &str = "group";
+const LABEL_TOPIC: &str = "topic";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SelectorError {
+    #[error("Unsupported requirement '{0}' in label selector: only 'key=value' is supported")]
+    UnsupportedRequirement(String),
+
+    #[error("Unsupported label '{0}' in label selector: only 'group' and 'topic' are supported")]
+    UnsupportedLabel(String),
+}
+
+/// Selection of the Consumer Groups (and Topics) to measure, as parsed from the `labelSelector`
+/// of an external metrics request.
+#[derive(Debug, Default, PartialEq)]
+pub struct Selector {
+    /// Consumer Group to measure: if not set, all the Consumer Groups.
+    pub group: Option<String>,
+
+    /// Topic to measure: if not set, all the Topics consumed by the Consumer Group.
+    pub topic: Option<String>,
+}
+
+impl Selector {
+    /// Parse a Kubernetes [label selector](https://kubernetes.io/docs/concepts/overview/working-with-objects/labels/#label-selectors).
+    ///
+    /// Only equality-based requirements on the `group` and `topic` labels are supported
+    /// (e.g. `group=my-group,topic=my-topic`).
+    pub fn parse(label_selector: &str) -> Result<Self, SelectorError> {
+        let mut selector = Selector::default();
+
+        for req in label_selector.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let (key, value) = req
+                .split_once("==")
+                .or_else(|| req.split_once('='))
+                .filter(|(k, v)| !k.ends_with('!') && !v.is_empty())
+                .ok_or_else(|| SelectorError::UnsupportedRequirement(req.to_string()))?;
+
+            match key.trim() {
+                LABEL_GROUP => selector.group = Some(value.trim().to_string()),
+                LABEL_TOPIC => selector.topic = Some(value.trim().to_string()),
+                k => return Err(SelectorError::UnsupportedLabel(k.to_string())),
+            }
+        }
+
+        Ok(selector)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Selector, SelectorError};
+
+    #[test]
+    fn parse_equality() {
+        assert_eq!(Selector::parse("").unwrap(), Selector::default());
+        assert_eq!(
+            Selector::parse("group=g").unwrap(),
+            Selector {
+                group: Some("g".to_string()),
+                topic: None,
+            }
+        );
+        assert_eq!(
+            Selector::parse("group==g, topic=t").unwrap(),
+            Selector {
+                group: Some("g".to_string()),
+                topic: Some("t".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            Selector::parse("group!=g"),
+            Err(SelectorError::UnsupportedRequirement(_))
+        ));
+        assert!(matches!(
+            Selector::parse("group in (a,b)"),
+            Err(SelectorError::UnsupportedRequirement(_))
+        ));
+        assert_eq!(
+            Selector::parse("group=g,app=a"),
+            Err(SelectorError::UnsupportedLabel("app".to_string()))
+        );
+    }
+}
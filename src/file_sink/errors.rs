@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum FileSinkError {
+    /// Creating, writing, renaming or deleting a file failed.
+    #[error("File sink I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Encoding a record as JSON failed.
+    #[error("File sink JSON encoding failed: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Encoding a record as CSV failed.
+    #[error("File sink CSV encoding failed: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// Encoding records as Parquet failed.
+    #[error("File sink Parquet encoding failed: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+pub type FileSinkResult<T> = Result<T, FileSinkError>;
@@ -0,0 +1,174 @@
+//! Append every lag update to local files, for offline analysis (e.g. loading them into notebooks).
+//!
+//! Fed by the changes of the [`LagRegister`]: each offset commit becomes a [`crate::kafka_sink::LagRecord`],
+//! written in batches as NDJSON, CSV or Parquet. Files are rotated by size and/or age,
+//! and only the most recent ones are kept. Commits replayed from `__consumer_offsets` at startup
+//! are not written.
+
+// Inner modules
+mod errors;
+mod rotation;
+mod writer;
+
+// Imports
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use clap::ValueEnum;
+use prometheus::{register_int_counter_with_registry, IntCounter, Registry};
+use tokio::{
+    sync::broadcast::error::RecvError,
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::cluster_status::ClusterStatusRegister;
+use crate::kafka_sink::{build_record, is_replayed, owner_of};
+use crate::lag_register::{LagChange, LagRegister};
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+use rotation::{RotatingFile, RotationPolicy};
+
+/// How often buffered records are written: with Parquet, each write is a row group.
+const WRITE_INTERVAL: Duration = Duration::from_secs(5);
+
+const MET_WRITTEN_NAME: &str = "file_sink_written_records_total";
+const MET_WRITTEN_HELP: &str = "Lag records written to the file sink";
+const MET_FAILED_NAME: &str = "file_sink_failed_records_total";
+const MET_FAILED_HELP: &str = "Lag records that failed to be written to the file sink";
+
+/// Format of the files written by the sink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum FileSinkFormat {
+    /// Newline-delimited JSON: one object per line.
+    Ndjson,
+
+    /// CSV, with a header line.
+    Csv,
+
+    /// Apache Parquet, snappy-compressed.
+    Parquet,
+}
+
+impl FileSinkFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            FileSinkFormat::Ndjson => "ndjson",
+            FileSinkFormat::Csv => "csv",
+            FileSinkFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Configuration of the file sink.
+#[derive(Debug, Clone)]
+pub struct FileSinkConfig {
+    pub dir: PathBuf,
+    pub format: FileSinkFormat,
+
+    /// Rotate the file once it reaches this size, in bytes.
+    pub max_size: Option<u64>,
+
+    /// Rotate the file once it's this old.
+    pub max_age: Option<Duration>,
+
+    /// Amount of complete files to keep.
+    pub retention: usize,
+}
+
+/// Spawn the task writing every lag update of the [`LagRegister`] to the files in `config.dir`.
+pub fn init(
+    config: FileSinkConfig,
+    cs_reg: Arc<ClusterStatusRegister>,
+    po_reg: Arc<PartitionOffsetsRegister>,
+    lag_reg: Arc<LagRegister>,
+    shutdown_token: CancellationToken,
+    metrics: Arc<Registry>,
+) -> JoinHandle<()> {
+    let written: IntCounter =
+        register_int_counter_with_registry!(MET_WRITTEN_NAME, MET_WRITTEN_HELP, metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {MET_WRITTEN_NAME}"));
+    let failed: IntCounter =
+        register_int_counter_with_registry!(MET_FAILED_NAME, MET_FAILED_HELP, metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {MET_FAILED_NAME}"));
+
+    let policy = RotationPolicy {
+        max_size: config.max_size,
+        max_age: config.max_age.map(|a| chrono::Duration::seconds(a.as_secs() as i64)),
+        retention: config.retention,
+    };
+    let mut file = RotatingFile::new(config.dir.clone(), config.format, policy);
+    if let Err(e) = file.recover() {
+        error!("Failed to recover partial files in '{}': {e}", config.dir.display());
+    }
+    let mut changes_rx = lag_reg.subscribe();
+    debug!("Initialized, writing {:?} to '{}'", config.format, config.dir.display());
+
+    tokio::spawn(async move {
+        let mut interval = interval(WRITE_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut batch = Vec::new();
+
+        loop {
+            tokio::select! {
+                res = changes_rx.recv() => match res {
+                    Ok(LagChange::Lag { group, topic_partition, lag }) => {
+                        if is_replayed(&lag, Utc::now()) {
+                            continue;
+                        }
+
+                        let owner = owner_of(&lag_reg, &group, &topic_partition).await;
+                        batch.push(
+                            build_record(&cs_reg, &po_reg, &group, &topic_partition, &lag, owner)
+                                .await,
+                        );
+                        continue;
+                    },
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Fell behind lag updates: {skipped} not written");
+                        failed.inc_by(skipped);
+                        continue;
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {},
+                _ = shutdown_token.cancelled() => break,
+            }
+
+            // Write on the blocking pool, handing the file over and back
+            let records = std::mem::take(&mut batch);
+            let (f, res) = tokio::task::spawn_blocking(move || {
+                let res = file.write(&records, Utc::now()).map(|_| records.len());
+                (file, res.map_err(|e| (e, records.len())))
+            })
+            .await
+            .expect("File sink write task panicked");
+            file = f;
+
+            match res {
+                Ok(n) => written.inc_by(n as u64),
+                Err((e, n)) => {
+                    error!("Failed to write {n} lag records: {e}");
+                    failed.inc_by(n as u64);
+                },
+            }
+        }
+
+        // Write what's left, then complete the current file
+        let remaining = batch.len();
+        let res = tokio::task::spawn_blocking(move || {
+            file.write(&batch, Utc::now())?;
+            file.close()
+        })
+        .await
+        .expect("File sink close task panicked");
+        match res {
+            Ok(()) => written.inc_by(remaining as u64),
+            Err(e) => error!("Failed to complete file: {e}"),
+        }
+
+        info!("Shutting down");
+    })
+}
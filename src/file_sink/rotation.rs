@@ -0,0 +1,336 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+
+use crate::kafka_sink::LagRecord;
+
+use super::errors::FileSinkResult;
+use super::writer::FileWriter;
+use super::FileSinkFormat;
+
+/// Prefix of the name of every file written by the sink.
+const FILE_PREFIX: &str = "kommitted-lag-";
+
+/// Suffix of the file being written: it's removed once the file is complete.
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// When to rotate the file being written, and how many to keep.
+#[derive(Debug, Clone)]
+pub struct RotationPolicy {
+    pub max_size: Option<u64>,
+    pub max_age: Option<Duration>,
+
+    /// Amount of complete files to keep: the oldest are deleted.
+    pub retention: usize,
+}
+
+/// File currently being written.
+struct Current {
+    path: PathBuf,
+    writer: FileWriter,
+    opened_at: DateTime<Utc>,
+}
+
+/// Writes [`LagRecord`]s to a sequence of files in a directory, rotating them according to a [`RotationPolicy`].
+///
+/// Files are named after the time they were opened, so they sort chronologically.
+pub struct RotatingFile {
+    dir: PathBuf,
+    format: FileSinkFormat,
+    policy: RotationPolicy,
+    current: Option<Current>,
+}
+
+impl RotatingFile {
+    pub fn new(dir: PathBuf, format: FileSinkFormat, policy: RotationPolicy) -> Self {
+        RotatingFile {
+            dir,
+            format,
+            policy,
+            current: None,
+        }
+    }
+
+    /// Recover the files left partial in the directory (e.g. by a crash), then apply the retention.
+    ///
+    /// NDJSON and CSV files are cut after their last complete line, and completed. Parquet files
+    /// can't be read without the footer written when closing them: they are deleted, like files
+    /// without any complete record.
+    pub fn recover(&self) -> FileSinkResult<()> {
+        if !self.dir.exists() {
+            return Ok(());
+        }
+
+        for (path, format) in partial_files(&self.dir)? {
+            if recover_lines(&path, format)? {
+                let complete = path.with_extension("");
+                fs::rename(&path, &complete)?;
+                info!("Recovered partial file '{}'", complete.display());
+            } else {
+                fs::remove_file(&path)?;
+                warn!("Deleted partial file '{}': no recoverable records", path.display());
+            }
+        }
+
+        self.apply_retention()
+    }
+
+    /// Write a batch of `records`, rotating the current file first if it's due.
+    ///
+    /// A new file is only opened when there are records to write.
+    pub fn write(&mut self, records: &[LagRecord], now: DateTime<Utc>) -> FileSinkResult<()> {
+        if self.rotation_due(now) {
+            self.close()?;
+        }
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        if self.current.is_none() {
+            fs::create_dir_all(&self.dir)?;
+            let name = format!(
+                "{FILE_PREFIX}{}.{}{PARTIAL_SUFFIX}",
+                now.format("%Y%m%dT%H%M%S%.3fZ"),
+                self.format.extension()
+            );
+            let path = self.dir.join(name);
+            debug!("Opening file '{}'", path.display());
+            self.current = Some(Current {
+                writer: FileWriter::create(&path, self.format)?,
+                path,
+                opened_at: now,
+            });
+        }
+
+        self.current.as_mut().expect("Just ensured there is a current file").writer.write(records)
+    }
+
+    /// Complete the current file (if any), and delete the oldest files beyond retention.
+    pub fn close(&mut self) -> FileSinkResult<()> {
+        let Some(current) = self.current.take() else {
+            return Ok(());
+        };
+
+        current.writer.close()?;
+        let complete = current.path.with_extension("");
+        fs::rename(&current.path, &complete)?;
+        debug!("Completed file '{}'", complete.display());
+
+        self.apply_retention()
+    }
+
+    fn rotation_due(&self, now: DateTime<Utc>) -> bool {
+        self.current.as_ref().is_some_and(|c| {
+            self.policy.max_size.is_some_and(|max| c.writer.size() >= max)
+                || self.policy.max_age.is_some_and(|max| now - c.opened_at >= max)
+        })
+    }
+
+    fn apply_retention(&self) -> FileSinkResult<()> {
+        let mut complete = complete_files(&self.dir, self.format)?;
+        if complete.len() > self.policy.retention {
+            complete.sort();
+            for path in &complete[..complete.len() - self.policy.retention] {
+                debug!("Deleting file '{}', beyond retention", path.display());
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Partial files in `dir`, with their format.
+fn partial_files(dir: &Path) -> FileSinkResult<Vec<(PathBuf, FileSinkFormat)>> {
+    Ok(fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter_map(|p| {
+            let name = p.file_name()?.to_str()?;
+            let ext =
+                name.strip_prefix(FILE_PREFIX)?.strip_suffix(PARTIAL_SUFFIX)?.rsplit('.').next()?;
+            let format = FileSinkFormat::value_variants().iter().find(|f| f.extension() == ext)?;
+            Some((p, *format))
+        })
+        .collect())
+}
+
+/// Cut a partial file after its last complete line, if it's in a line-based format.
+///
+/// Returns whether any complete record is left.
+fn recover_lines(path: &Path, format: FileSinkFormat) -> FileSinkResult<bool> {
+    let header_lines = match format {
+        FileSinkFormat::Ndjson => 0,
+        FileSinkFormat::Csv => 1,
+        FileSinkFormat::Parquet => return Ok(false),
+    };
+
+    let content = fs::read(path)?;
+    let end = content.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    if content[..end].iter().filter(|b| **b == b'\n').count() <= header_lines {
+        return Ok(false);
+    }
+
+    fs::OpenOptions::new().write(true).open(path)?.set_len(end as u64)?;
+    Ok(true)
+}
+
+/// Complete files of the given format, in `dir`.
+fn complete_files(dir: &Path, format: FileSinkFormat) -> FileSinkResult<Vec<PathBuf>> {
+    let suffix = format!(".{}", format.extension());
+
+    Ok(fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(FILE_PREFIX) && n.ends_with(&suffix))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kommitted-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn record(offset: u64) -> LagRecord {
+        LagRecord {
+            cluster_id: "c".to_string(),
+            group: "g".to_string(),
+            topic: "t".to_string(),
+            partition: 0,
+            offset,
+            offset_timestamp: Utc.timestamp_millis_opt(1).unwrap(),
+            offset_lag: 2,
+            time_lag_ms: 3,
+            owner: None,
+            earliest_offset: None,
+            latest_offset: Some(offset + 2),
+            timestamp: Utc.timestamp_millis_opt(4).unwrap(),
+        }
+    }
+
+    #[test]
+    fn rotate_by_age_with_retention() {
+        let dir = test_dir("rotate");
+        let mut rf = RotatingFile::new(
+            dir.clone(),
+            FileSinkFormat::Csv,
+            RotationPolicy {
+                max_size: None,
+                max_age: Some(Duration::seconds(10)),
+                retention: 2,
+            },
+        );
+
+        let t0 = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        for i in 0..4 {
+            let now = t0 + Duration::seconds(10 * i);
+            rf.write(&[record(i as u64), record(i as u64 + 1)], now).unwrap();
+        }
+        rf.close().unwrap();
+
+        let mut files = complete_files(&dir, FileSinkFormat::Csv).unwrap();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("kommitted-lag-20231114T221340.000Z.csv"));
+
+        let csv = fs::read_to_string(&files[1]).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("cluster_id,group,topic,partition"));
+        assert_eq!(
+            lines[1],
+            "c,g,t,0,3,1970-01-01T00:00:00.001+00:00,2,3,,,,,5,1970-01-01T00:00:00.004+00:00"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotate_by_size_parquet() {
+        let dir = test_dir("parquet");
+        let mut rf = RotatingFile::new(
+            dir.clone(),
+            FileSinkFormat::Parquet,
+            RotationPolicy {
+                max_size: Some(1),
+                max_age: None,
+                retention: 10,
+            },
+        );
+
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        rf.write(&[record(1), record(2), record(3)], now).unwrap();
+        rf.write(&[record(4)], now + Duration::milliseconds(1)).unwrap();
+        rf.close().unwrap();
+
+        let mut files = complete_files(&dir, FileSinkFormat::Parquet).unwrap();
+        files.sort();
+        let rows: Vec<i64> = files
+            .iter()
+            .map(|f| {
+                let reader = SerializedFileReader::new(fs::File::open(f).unwrap()).unwrap();
+                reader.metadata().file_metadata().num_rows()
+            })
+            .collect();
+        assert_eq!(rows, [3, 1]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recover_partial_files() {
+        let dir = test_dir("recover");
+        fs::create_dir_all(&dir).unwrap();
+        let partial = |name: &str, content: &str| {
+            fs::write(dir.join(format!("{FILE_PREFIX}{name}{PARTIAL_SUFFIX}")), content).unwrap();
+        };
+        partial("20240101T000000.000Z.ndjson", "{\"offset\":1}\n{\"offset\":2}\n{\"off");
+        partial("20240101T000001.000Z.ndjson", "{\"off");
+        partial("20240101T000002.000Z.csv", "cluster_id,group\nc,g\nc,");
+        partial("20240101T000003.000Z.csv", "cluster_id,group\n");
+        partial("20240101T000004.000Z.parquet", "PAR1");
+
+        let rf = RotatingFile::new(
+            dir.clone(),
+            FileSinkFormat::Ndjson,
+            RotationPolicy {
+                max_size: None,
+                max_age: None,
+                retention: 10,
+            },
+        );
+        rf.recover().unwrap();
+
+        let mut files: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            ["kommitted-lag-20240101T000000.000Z.ndjson", "kommitted-lag-20240101T000002.000Z.csv"]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join(&files[0])).unwrap(),
+            "{\"offset\":1}\n{\"offset\":2}\n"
+        );
+        assert_eq!(fs::read_to_string(dir.join(&files[1])).unwrap(), "cluster_id,group\nc,g\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
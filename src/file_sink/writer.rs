@@ -0,0 +1,247 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use parquet::{
+    basic::Compression,
+    data_type::{ByteArray, ByteArrayType, DataType, Int32Type, Int64Type},
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedColumnWriter, SerializedFileWriter},
+    },
+    schema::parser::parse_message_type,
+};
+
+use crate::kafka_sink::LagRecord;
+
+use super::errors::FileSinkResult;
+use super::FileSinkFormat;
+
+/// Columns of the CSV and Parquet files: the owner [`crate::kafka_types::Member`] is flattened.
+pub const COLUMNS: [&str; 14] = [
+    "cluster_id",
+    "group",
+    "topic",
+    "partition",
+    "offset",
+    "offset_timestamp",
+    "offset_lag",
+    "time_lag_ms",
+    "owner_id",
+    "owner_client_id",
+    "owner_client_host",
+    "earliest_offset",
+    "latest_offset",
+    "timestamp",
+];
+
+/// Parquet schema, with the [`COLUMNS`] in the same order.
+const PARQUET_SCHEMA: &str = "
+message lag_record {
+    REQUIRED BYTE_ARRAY cluster_id (STRING);
+    REQUIRED BYTE_ARRAY group (STRING);
+    REQUIRED BYTE_ARRAY topic (STRING);
+    REQUIRED INT32 partition;
+    REQUIRED INT64 offset;
+    REQUIRED INT64 offset_timestamp (TIMESTAMP(MILLIS, true));
+    REQUIRED INT64 offset_lag;
+    REQUIRED INT64 time_lag_ms;
+    OPTIONAL BYTE_ARRAY owner_id (STRING);
+    OPTIONAL BYTE_ARRAY owner_client_id (STRING);
+    OPTIONAL BYTE_ARRAY owner_client_host (STRING);
+    OPTIONAL INT64 earliest_offset;
+    OPTIONAL INT64 latest_offset;
+    REQUIRED INT64 timestamp (TIMESTAMP(MILLIS, true));
+}
+";
+
+/// Writer of [`LagRecord`]s to a single file, in one of the [`FileSinkFormat`]s.
+pub enum FileWriter {
+    Ndjson(BufWriter<File>),
+    Csv(csv::Writer<File>),
+
+    /// Every batch of records is written as a row group: the file is only readable once closed.
+    Parquet(SerializedFileWriter<File>),
+}
+
+impl FileWriter {
+    pub fn create(path: &Path, format: FileSinkFormat) -> FileSinkResult<Self> {
+        let file = File::create(path)?;
+
+        Ok(match format {
+            FileSinkFormat::Ndjson => FileWriter::Ndjson(BufWriter::new(file)),
+            FileSinkFormat::Csv => {
+                let mut w = csv::Writer::from_writer(file);
+                w.write_record(COLUMNS)?;
+                FileWriter::Csv(w)
+            },
+            FileSinkFormat::Parquet => {
+                let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+                let props =
+                    WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+                FileWriter::Parquet(SerializedFileWriter::new(file, schema, Arc::new(props))?)
+            },
+        })
+    }
+
+    /// Write a batch of `records`, and flush them.
+    pub fn write(&mut self, records: &[LagRecord]) -> FileSinkResult<()> {
+        match self {
+            FileWriter::Ndjson(w) => {
+                for r in records {
+                    serde_json::to_writer(&mut *w, r)?;
+                    w.write_all(b"\n")?;
+                }
+                w.flush()?;
+            },
+            FileWriter::Csv(w) => {
+                for r in records {
+                    w.write_record(csv_row(r))?;
+                }
+                w.flush()?;
+            },
+            FileWriter::Parquet(w) => write_row_group(w, records)?,
+        }
+        Ok(())
+    }
+
+    /// Bytes written so far.
+    pub fn size(&self) -> u64 {
+        match self {
+            FileWriter::Ndjson(w) => w.get_ref().metadata().map(|m| m.len()).unwrap_or_default(),
+            FileWriter::Csv(w) => w.get_ref().metadata().map(|m| m.len()).unwrap_or_default(),
+            FileWriter::Parquet(w) => w.bytes_written() as u64,
+        }
+    }
+
+    pub fn close(self) -> FileSinkResult<()> {
+        match self {
+            FileWriter::Ndjson(mut w) => w.flush()?,
+            FileWriter::Csv(mut w) => w.flush()?,
+            FileWriter::Parquet(w) => {
+                w.close()?;
+            },
+        }
+        Ok(())
+    }
+}
+
+fn csv_row(r: &LagRecord) -> [String; 14] {
+    let owner = |f: fn(&crate::kafka_types::Member) -> &String| {
+        r.owner.as_ref().map(|m| f(m).clone()).unwrap_or_default()
+    };
+    let optional = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+
+    [
+        r.cluster_id.clone(),
+        r.group.clone(),
+        r.topic.clone(),
+        r.partition.to_string(),
+        r.offset.to_string(),
+        r.offset_timestamp.to_rfc3339(),
+        r.offset_lag.to_string(),
+        r.time_lag_ms.to_string(),
+        owner(|m| &m.id),
+        owner(|m| &m.client_id),
+        owner(|m| &m.client_host),
+        optional(r.earliest_offset),
+        optional(r.latest_offset),
+        r.timestamp.to_rfc3339(),
+    ]
+}
+
+fn write_row_group(
+    w: &mut SerializedFileWriter<File>,
+    records: &[LagRecord],
+) -> FileSinkResult<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    let string = |v: &str| Some(ByteArray::from(v));
+    let owner = |f: fn(&crate::kafka_types::Member) -> &str| -> Vec<Option<ByteArray>> {
+        records.iter().map(|r| r.owner.as_ref().and_then(|m| string(f(m)))).collect()
+    };
+
+    let mut rg = w.next_row_group()?;
+    let mut idx = 0;
+    while let Some(mut col) = rg.next_column()? {
+        match COLUMNS[idx] {
+            "cluster_id" => write_column::<ByteArrayType>(
+                &mut col,
+                records.iter().map(|r| string(&r.cluster_id)).collect(),
+            )?,
+            "group" => write_column::<ByteArrayType>(
+                &mut col,
+                records.iter().map(|r| string(&r.group)).collect(),
+            )?,
+            "topic" => write_column::<ByteArrayType>(
+                &mut col,
+                records.iter().map(|r| string(&r.topic)).collect(),
+            )?,
+            "partition" => write_column::<Int32Type>(
+                &mut col,
+                records.iter().map(|r| Some(r.partition as i32)).collect(),
+            )?,
+            "offset" => write_column::<Int64Type>(
+                &mut col,
+                records.iter().map(|r| Some(r.offset as i64)).collect(),
+            )?,
+            "offset_timestamp" => write_column::<Int64Type>(
+                &mut col,
+                records.iter().map(|r| Some(r.offset_timestamp.timestamp_millis())).collect(),
+            )?,
+            "offset_lag" => write_column::<Int64Type>(
+                &mut col,
+                records.iter().map(|r| Some(r.offset_lag as i64)).collect(),
+            )?,
+            "time_lag_ms" => write_column::<Int64Type>(
+                &mut col,
+                records.iter().map(|r| Some(r.time_lag_ms)).collect(),
+            )?,
+            "owner_id" => write_column::<ByteArrayType>(&mut col, owner(|m| &m.id))?,
+            "owner_client_id" => write_column::<ByteArrayType>(&mut col, owner(|m| &m.client_id))?,
+            "owner_client_host" => {
+                write_column::<ByteArrayType>(&mut col, owner(|m| &m.client_host))?
+            },
+            "earliest_offset" => write_column::<Int64Type>(
+                &mut col,
+                records.iter().map(|r| r.earliest_offset.map(|o| o as i64)).collect(),
+            )?,
+            "latest_offset" => write_column::<Int64Type>(
+                &mut col,
+                records.iter().map(|r| r.latest_offset.map(|o| o as i64)).collect(),
+            )?,
+            "timestamp" => write_column::<Int64Type>(
+                &mut col,
+                records.iter().map(|r| Some(r.timestamp.timestamp_millis())).collect(),
+            )?,
+            c => unreachable!("Column '{c}' is not in the Parquet schema"),
+        }
+        col.close()?;
+        idx += 1;
+    }
+    rg.close()?;
+
+    Ok(())
+}
+
+/// Write the `values` of a column: `None`s are only allowed in `OPTIONAL` columns.
+fn write_column<T: DataType>(
+    col: &mut SerializedColumnWriter,
+    values: Vec<Option<T::T>>,
+) -> FileSinkResult<()> {
+    let w = col.typed::<T>();
+    if w.get_descriptor().max_def_level() > 0 {
+        let def_levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+        let values: Vec<T::T> = values.into_iter().flatten().collect();
+        w.write_batch(&values, Some(&def_levels), None)?;
+    } else {
+        let values: Vec<T::T> = values.into_iter().flatten().collect();
+        w.write_batch(&values, None, None)?;
+    }
+    Ok(())
+}
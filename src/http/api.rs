@@ -0,0 +1,279 @@
+//! REST API, to build further automation on top of the data collected by the service.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::consumer_rates::{self, PartitionRates};
+use crate::consumer_status::{evaluate_group, evaluate_groups, ConsumerStatus};
+use crate::data_loss::DataLoss;
+use crate::kafka_types::{Group, Member, TopicPartition};
+use crate::labels_mapping::Labels;
+use crate::lag_register::Lag;
+use crate::scaling::recommend_group;
+
+use super::HttpServiceState;
+
+/// Default time range of [`group_history`], when `from` is not provided.
+const DEFAULT_HISTORY_RANGE: Duration = Duration::hours(1);
+
+/// Default target time lag of [`group_scaling`], when `target_time_lag` is not provided.
+const DEFAULT_SCALING_TARGET_TIME_LAG: Duration = Duration::minutes(1);
+
+/// Maximum target time lag of [`group_scaling`].
+const MAX_SCALING_TARGET_TIME_LAG: Duration = Duration::days(365);
+
+/// Body of the response, when an API request fails.
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+}
+
+fn api_error(status: StatusCode, error: impl Into<String>) -> Response {
+    (
+        status,
+        Json(ApiError {
+            error: error.into(),
+        }),
+    )
+        .into_response()
+}
+
+/// Convert a query parameter in seconds into a [`Duration`], if it's representable.
+fn seconds(s: u64) -> Option<Duration> {
+    i64::try_from(s).ok().and_then(Duration::try_seconds)
+}
+
+/// Query parameters of [`group_history`].
+#[derive(Debug, Deserialize)]
+pub(super) struct HistoryParams {
+    /// Beginning of the time range (RFC 3339): defaults to 1 hour before `to`.
+    from: Option<DateTime<Utc>>,
+
+    /// End of the time range (RFC 3339): defaults to now.
+    to: Option<DateTime<Utc>>,
+
+    /// At most one sample per `step` seconds is returned: defaults to the history resolution,
+    /// and can't exceed the history retention.
+    step: Option<u64>,
+}
+
+/// `GET /api/v1/groups/:group/history?from=&to=&step=`
+///
+/// Returns the lag history of a Consumer Group, for each of its Topic Partitions
+/// and aggregated across all of them.
+pub(super) async fn group_history(
+    State(state): State<HttpServiceState>,
+    Path(group): Path<String>,
+    Query(params): Query<HistoryParams>,
+) -> Response {
+    let to = params.to.unwrap_or_else(Utc::now);
+    let from = params.from.unwrap_or(to - DEFAULT_HISTORY_RANGE);
+    if from >= to {
+        return api_error(StatusCode::BAD_REQUEST, "'from' must precede 'to'");
+    }
+
+    let retention = state.lag_history.retention();
+    let step = match params.step.map(seconds) {
+        None => state.lag_history.resolution(),
+        Some(Some(s)) if s > Duration::zero() && s <= retention => {
+            s.max(state.lag_history.resolution())
+        },
+        Some(_) => {
+            return api_error(
+                StatusCode::BAD_REQUEST,
+                format!("'step' must be between 1 and {} seconds", retention.num_seconds()),
+            )
+        },
+    };
+
+    match state.lag_history.query(&group, from, to, step).await {
+        Some(range) => Json(range).into_response(),
+        None => api_error(StatusCode::NOT_FOUND, format!("No history for group '{group}'")),
+    }
+}
+
+/// Query parameters of [`group_scaling`].
+#[derive(Debug, Deserialize)]
+pub(super) struct ScalingParams {
+    /// Time lag (seconds) to stay under: defaults to 1 minute, and can't exceed 1 year.
+    target_time_lag: Option<u64>,
+}
+
+/// `GET /api/v1/groups/:group/scaling?target_time_lag=`
+///
+/// Returns the recommended amount of consumers for a Consumer Group, to keep its time lag
+/// under the target, alongside the inputs the recommendation is based on.
+pub(super) async fn group_scaling(
+    State(state): State<HttpServiceState>,
+    Path(group): Path<String>,
+    Query(params): Query<ScalingParams>,
+) -> Response {
+    let target_time_lag = match params.target_time_lag.map(seconds) {
+        None => DEFAULT_SCALING_TARGET_TIME_LAG,
+        Some(Some(s)) if s > Duration::zero() && s <= MAX_SCALING_TARGET_TIME_LAG => s,
+        Some(_) => {
+            return api_error(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "'target_time_lag' must be between 1 and {} seconds",
+                    MAX_SCALING_TARGET_TIME_LAG.num_seconds()
+                ),
+            )
+        },
+    };
+
+    let lag_by_group = state.lag_reg.lag_by_group.read().await;
+    let Some(gwl) = lag_by_group.get(&group) else {
+        return api_error(StatusCode::NOT_FOUND, format!("Unknown group '{group}'"));
+    };
+
+    Json(recommend_group(gwl, &state.cs_reg, &state.po_reg, target_time_lag).await).into_response()
+}
+
+/// Summary of a Consumer Group, as returned by [`groups`].
+#[derive(Debug, Serialize)]
+struct GroupSummary<'a> {
+    #[serde(flatten)]
+    group: &'a Group,
+    partitions: usize,
+    status: ConsumerStatus,
+    labels: Labels,
+}
+
+/// `GET /api/v1/groups`
+///
+/// Returns all the known Consumer Groups, sorted by name, with their status
+/// and the labels mapped to them.
+pub(super) async fn groups(State(state): State<HttpServiceState>) -> Response {
+    let labels_mapping = state.labels_mapper.current();
+    let lag_by_group = state.lag_reg.lag_by_group.read().await;
+    let statuses = evaluate_groups(&lag_by_group, &state.po_reg, Utc::now()).await;
+
+    let mut groups: Vec<GroupSummary> = lag_by_group
+        .values()
+        .map(|gwl| GroupSummary {
+            group: &gwl.group,
+            partitions: gwl.lag_by_topic_partition.len(),
+            status: statuses.get(&gwl.group.name).map(|gs| gs.status).unwrap_or_default(),
+            labels: labels_mapping.group_labels(&gwl.group.name),
+        })
+        .collect();
+    groups.sort_by(|a, b| a.group.name.cmp(&b.group.name));
+
+    Json(groups).into_response()
+}
+
+/// Detail of a Consumer Group, as returned by [`group`].
+#[derive(Debug, Serialize)]
+struct GroupDetail<'a> {
+    #[serde(flatten)]
+    group: &'a Group,
+    status: ConsumerStatus,
+    labels: Labels,
+    partitions: Vec<PartitionDetail<'a>>,
+}
+
+/// Detail of a Topic Partition consumed by a Consumer Group.
+#[derive(Debug, Serialize)]
+struct PartitionDetail<'a> {
+    #[serde(flatten)]
+    topic_partition: &'a TopicPartition,
+    status: ConsumerStatus,
+    labels: Labels,
+    owner: Option<&'a Member>,
+    lag: Option<&'a Lag>,
+    #[serde(flatten)]
+    rates: PartitionRates,
+}
+
+/// `GET /api/v1/groups/:group`
+///
+/// Returns a Consumer Group, with the lag, owner and status of each of its Topic Partitions
+/// (sorted by topic and partition), and the labels mapped to them.
+pub(super) async fn group(
+    State(state): State<HttpServiceState>,
+    Path(group): Path<String>,
+) -> Response {
+    let labels_mapping = state.labels_mapper.current();
+    let lag_by_group = state.lag_reg.lag_by_group.read().await;
+
+    let Some(gwl) = lag_by_group.get(&group) else {
+        return api_error(StatusCode::NOT_FOUND, format!("Unknown group '{group}'"));
+    };
+    let group_status = evaluate_group(gwl, &state.po_reg, Utc::now()).await;
+    let group_rates = consumer_rates::evaluate_group(gwl, &state.po_reg).await;
+
+    let mut partitions: Vec<PartitionDetail> = gwl
+        .lag_by_topic_partition
+        .iter()
+        .map(|(tp, lwo)| PartitionDetail {
+            topic_partition: tp,
+            status: group_status.partitions.get(tp).copied().unwrap_or_default(),
+            labels: labels_mapping.group_topic_labels(&group, &tp.topic),
+            owner: lwo.owner.as_ref(),
+            lag: lwo.lag.as_ref(),
+            rates: group_rates.get(tp).copied().unwrap_or_default(),
+        })
+        .collect();
+    partitions.sort_by(|a, b| a.topic_partition.cmp(b.topic_partition));
+
+    Json(GroupDetail {
+        group: &gwl.group,
+        status: group_status.status,
+        labels: labels_mapping.group_labels(&group),
+        partitions,
+    })
+    .into_response()
+}
+
+/// Data loss incidents of a Consumer Group on a Topic, as returned by [`data_loss`].
+#[derive(Debug, Serialize)]
+struct DataLossIncidents {
+    group: String,
+    topic: String,
+    incidents: u64,
+}
+
+/// Data loss report, as returned by [`data_loss`].
+#[derive(Debug, Serialize)]
+struct DataLossReport {
+    ongoing: Vec<DataLoss>,
+    incidents: Vec<DataLossIncidents>,
+}
+
+/// `GET /api/v1/data-loss`
+///
+/// Returns the data loss currently ongoing (sorted by group, topic and partition),
+/// and the amount of incidents detected since start, for each Consumer Group and Topic.
+pub(super) async fn data_loss(State(state): State<HttpServiceState>) -> Response {
+    let incidents = state
+        .data_loss
+        .incidents()
+        .await
+        .into_iter()
+        .map(|(group, topic, incidents)| DataLossIncidents {
+            group,
+            topic,
+            incidents,
+        })
+        .collect();
+
+    Json(DataLossReport {
+        ongoing: state.data_loss.ongoing().await,
+        incidents,
+    })
+    .into_response()
+}
+
+/// `GET /api/v1/alerts`
+///
+/// Returns the alerts currently pending or firing, sorted by rule, group and topic.
+pub(super) async fn alerts(State(state): State<HttpServiceState>) -> Response {
+    Json(state.alerts.active().await).into_response()
+}
@@ -0,0 +1,553 @@
+//! Compatible subset of the [Burrow](https://github.com/linkedin/Burrow/wiki/HTTP-Endpoint) HTTP API (v3).
+//!
+//! Meant for a drop-in migration from Burrow: clusters are named after their `cluster_id`.
+//! When monitoring multiple clusters, each request is dispatched to the cluster it names
+//! (see [`super::multi`]).
+
+use std::collections::HashMap;
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path, State},
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::consumer_status::{evaluate_group, ConsumerStatus};
+use crate::kafka_types::TopicPartition;
+use crate::lag_register::{GroupWithLag, Lag};
+
+use super::HttpServiceState;
+
+/// Details of the request, that Burrow echoes back in every response.
+#[derive(Debug, Serialize)]
+pub(super) struct BurrowRequest {
+    url: String,
+    host: String,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for BurrowRequest {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(BurrowRequest {
+            url: parts.uri.path().to_string(),
+            host: parts
+                .headers
+                .get(header::HOST)
+                .and_then(|h| h.to_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+}
+
+/// Envelope of every Burrow response.
+#[derive(Debug, Serialize)]
+struct BurrowResponse<T: Serialize> {
+    error: bool,
+    message: String,
+    #[serde(flatten)]
+    body: T,
+    request: BurrowRequest,
+}
+
+#[derive(Debug, Serialize)]
+struct NoBody {}
+
+fn respond<T: Serialize>(message: &str, body: T, request: BurrowRequest) -> Response {
+    Json(BurrowResponse {
+        error: false,
+        message: message.to_string(),
+        body,
+        request,
+    })
+    .into_response()
+}
+
+fn respond_error(status: StatusCode, message: &str, request: BurrowRequest) -> Response {
+    let res = BurrowResponse {
+        error: true,
+        message: message.to_string(),
+        body: NoBody {},
+        request,
+    };
+    (status, Json(res)).into_response()
+}
+
+/// Burrow only knows of clusters by name: the one served here is named after its `cluster_id`.
+async fn is_known_cluster(state: &HttpServiceState, cluster: &str) -> bool {
+    state.cs_reg.get_cluster_id().await == cluster
+}
+
+#[derive(Debug, Serialize)]
+struct Clusters {
+    clusters: Vec<String>,
+}
+
+/// `GET /v3/kafka`
+pub(super) async fn clusters(
+    State(state): State<HttpServiceState>,
+    request: BurrowRequest,
+) -> Response {
+    cluster_list(vec![state.cs_reg.get_cluster_id().await], request)
+}
+
+/// Response of `GET /v3/kafka`, listing the given clusters.
+pub(super) fn cluster_list(clusters: Vec<String>, request: BurrowRequest) -> Response {
+    respond(
+        "cluster list returned",
+        Clusters {
+            clusters,
+        },
+        request,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct Topics {
+    topics: Vec<String>,
+}
+
+/// `GET /v3/kafka/:cluster/topic`
+pub(super) async fn topics(
+    State(state): State<HttpServiceState>,
+    Path(cluster): Path<String>,
+    request: BurrowRequest,
+) -> Response {
+    if !is_known_cluster(&state, &cluster).await {
+        return respond_error(StatusCode::NOT_FOUND, "cluster not found", request);
+    }
+
+    let mut topics = state.cs_reg.get_topics().await;
+    topics.sort();
+    respond(
+        "topic list returned",
+        Topics {
+            topics,
+        },
+        request,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct TopicOffsets {
+    offsets: Vec<i64>,
+}
+
+/// `GET /v3/kafka/:cluster/topic/:topic`
+///
+/// Returns the latest available offset of each partition of the topic (`-1` if unknown).
+pub(super) async fn topic(
+    State(state): State<HttpServiceState>,
+    Path((cluster, topic)): Path<(String, String)>,
+    request: BurrowRequest,
+) -> Response {
+    if !is_known_cluster(&state, &cluster).await {
+        return respond_error(StatusCode::NOT_FOUND, "cluster not found", request);
+    }
+
+    let Some(mut partitions) = state.cs_reg.get_partitions_for_topic(&topic).await else {
+        return respond_error(StatusCode::NOT_FOUND, "topic not found", request);
+    };
+    partitions.sort();
+
+    let mut offsets = Vec::with_capacity(partitions.len());
+    for p in partitions {
+        let tp = TopicPartition::new(topic.clone(), p);
+        offsets.push(state.po_reg.get_latest_available_offset(&tp).await.map_or(-1, |o| o as i64));
+    }
+
+    respond(
+        "topic offsets returned",
+        TopicOffsets {
+            offsets,
+        },
+        request,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct Consumers {
+    consumers: Vec<String>,
+}
+
+/// `GET /v3/kafka/:cluster/consumer`
+pub(super) async fn consumers(
+    State(state): State<HttpServiceState>,
+    Path(cluster): Path<String>,
+    request: BurrowRequest,
+) -> Response {
+    if !is_known_cluster(&state, &cluster).await {
+        return respond_error(StatusCode::NOT_FOUND, "cluster not found", request);
+    }
+
+    let mut consumers: Vec<String> =
+        state.lag_reg.lag_by_group.read().await.keys().cloned().collect();
+    consumers.sort();
+    respond(
+        "consumer list returned",
+        Consumers {
+            consumers,
+        },
+        request,
+    )
+}
+
+/// An offset commit, as reported by Burrow.
+#[derive(Debug, Clone, Serialize)]
+struct BurrowOffset {
+    offset: u64,
+    timestamp: i64,
+    #[serde(rename = "observedAt")]
+    observed_at: i64,
+    lag: u64,
+}
+
+impl From<&Lag> for BurrowOffset {
+    fn from(l: &Lag) -> Self {
+        BurrowOffset {
+            offset: l.offset,
+            timestamp: l.offset_timestamp.timestamp_millis(),
+            observed_at: l.offset_timestamp.timestamp_millis(),
+            lag: l.offset_lag,
+        }
+    }
+}
+
+/// Status of a consumed Topic Partition, as reported by Burrow.
+#[derive(Debug, Clone, Serialize)]
+struct BurrowPartitionStatus {
+    topic: String,
+    partition: u32,
+    owner: String,
+    client_id: String,
+    status: ConsumerStatus,
+    start: Option<BurrowOffset>,
+    end: Option<BurrowOffset>,
+    current_lag: u64,
+    complete: f64,
+}
+
+/// Status of a Consumer Group, as reported by Burrow.
+#[derive(Debug, Serialize)]
+struct BurrowConsumerStatus {
+    cluster: String,
+    group: String,
+    status: ConsumerStatus,
+    complete: f64,
+    partitions: Vec<BurrowPartitionStatus>,
+    partition_count: usize,
+    maxlag: Option<BurrowPartitionStatus>,
+    totallag: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ConsumerStatusBody {
+    status: BurrowConsumerStatus,
+}
+
+/// `GET /v3/kafka/:cluster/consumer/:group/lag`
+///
+/// Returns the status of the Consumer Group, and of each Topic Partition it consumes.
+pub(super) async fn consumer_lag(
+    State(state): State<HttpServiceState>,
+    Path((cluster, group)): Path<(String, String)>,
+    request: BurrowRequest,
+) -> Response {
+    consumer_status_response(&state, cluster, group, false, request).await
+}
+
+/// `GET /v3/kafka/:cluster/consumer/:group/status`
+///
+/// Like [`consumer_lag`], but only reports the Topic Partitions that are not `OK`.
+pub(super) async fn consumer_status(
+    State(state): State<HttpServiceState>,
+    Path((cluster, group)): Path<(String, String)>,
+    request: BurrowRequest,
+) -> Response {
+    consumer_status_response(&state, cluster, group, true, request).await
+}
+
+async fn consumer_status_response(
+    state: &HttpServiceState,
+    cluster: String,
+    group: String,
+    only_not_ok: bool,
+    request: BurrowRequest,
+) -> Response {
+    let cluster_id = state.cs_reg.get_cluster_id().await;
+    let lag_by_group = state.lag_reg.lag_by_group.read().await;
+    let gwl = match find_group(&cluster_id, &lag_by_group, &cluster, &group) {
+        Ok(gwl) => gwl,
+        Err(message) => return respond_error(StatusCode::NOT_FOUND, message, request),
+    };
+    let group_status = evaluate_group(gwl, &state.po_reg, Utc::now()).await;
+
+    let mut current_lags = HashMap::with_capacity(gwl.lag_by_topic_partition.len());
+    for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+        if let Some(l) = lwo.window.back() {
+            let lag = state.po_reg.estimate_offset_lag(tp, l.offset).await.unwrap_or(l.offset_lag);
+            current_lags.insert(tp.clone(), lag);
+        }
+    }
+
+    let status = burrow_consumer_status(
+        cluster,
+        group,
+        gwl,
+        &group_status.partitions,
+        &current_lags,
+        state.lag_reg.status_window,
+        only_not_ok,
+    );
+    respond(
+        "consumer status returned",
+        ConsumerStatusBody {
+            status,
+        },
+        request,
+    )
+}
+
+/// Find the Consumer Group a request is about, or the message of the `404 Not Found` to reply with.
+fn find_group<'a>(
+    cluster_id: &str,
+    lag_by_group: &'a HashMap<String, GroupWithLag>,
+    cluster: &str,
+    group: &str,
+) -> Result<&'a GroupWithLag, &'static str> {
+    if cluster_id != cluster {
+        return Err("cluster not found");
+    }
+    lag_by_group.get(group).ok_or("consumer group not found")
+}
+
+/// Build the [`BurrowConsumerStatus`] of a Consumer Group, from the status and current lag of
+/// each Topic Partition it consumes.
+///
+/// The status of the group is the worst of its partitions, rolled up by [`group_rollup`].
+fn burrow_consumer_status(
+    cluster: String,
+    group: String,
+    gwl: &GroupWithLag,
+    statuses: &HashMap<TopicPartition, ConsumerStatus>,
+    current_lags: &HashMap<TopicPartition, u64>,
+    window_size: usize,
+    only_not_ok: bool,
+) -> BurrowConsumerStatus {
+    let mut partitions: Vec<BurrowPartitionStatus> = gwl
+        .lag_by_topic_partition
+        .iter()
+        .map(|(tp, lwo)| BurrowPartitionStatus {
+            topic: tp.topic.clone(),
+            partition: tp.partition,
+            owner: lwo.owner.as_ref().map(|o| o.client_host.clone()).unwrap_or_default(),
+            client_id: lwo.owner.as_ref().map(|o| o.client_id.clone()).unwrap_or_default(),
+            status: statuses.get(tp).copied().unwrap_or_default(),
+            start: lwo.window.front().map(BurrowOffset::from),
+            end: lwo.window.back().map(BurrowOffset::from),
+            current_lag: current_lags.get(tp).copied().unwrap_or_default(),
+            complete: (lwo.window.len() as f64 / window_size as f64).min(1.0),
+        })
+        .collect();
+    partitions.sort_by(|a, b| (&a.topic, a.partition).cmp(&(&b.topic, b.partition)));
+
+    let partition_count = partitions.len();
+    let totallag = partitions.iter().map(|p| p.current_lag).sum();
+    let maxlag = partitions.iter().max_by_key(|p| p.current_lag).cloned();
+    let complete = if partition_count > 0 {
+        partitions.iter().map(|p| p.complete).sum::<f64>() / partition_count as f64
+    } else {
+        0.0
+    };
+    let status = group_rollup(ConsumerStatus::worst(partitions.iter().map(|p| p.status)));
+    if only_not_ok {
+        partitions.retain(|p| p.status != ConsumerStatus::Ok);
+    }
+
+    BurrowConsumerStatus {
+        cluster,
+        group,
+        status,
+        complete,
+        partitions,
+        partition_count,
+        maxlag,
+        totallag,
+    }
+}
+
+/// Burrow rolls up the status of a Consumer Group to one of `OK`, `WARN` or `ERR`.
+fn group_rollup(worst: ConsumerStatus) -> ConsumerStatus {
+    match worst {
+        ConsumerStatus::Stop | ConsumerStatus::Stall | ConsumerStatus::Err => ConsumerStatus::Err,
+        s => s,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use axum::body::to_bytes;
+    use chrono::{Duration, TimeZone};
+
+    use crate::kafka_types::Member;
+    use crate::lag_register::LagWithOwner;
+
+    use super::*;
+
+    fn request() -> BurrowRequest {
+        BurrowRequest {
+            url: "/v3/kafka/c/consumer".to_string(),
+            host: "localhost:6564".to_string(),
+        }
+    }
+
+    async fn json(res: Response) -> (StatusCode, serde_json::Value) {
+        let status = res.status();
+        let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    /// Window of `offsets_and_lags`, one commit per second.
+    fn window(offsets_and_lags: &[(u64, u64)]) -> VecDeque<Lag> {
+        offsets_and_lags
+            .iter()
+            .enumerate()
+            .map(|(i, (offset, offset_lag))| Lag {
+                offset: *offset,
+                offset_timestamp: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap()
+                    + Duration::seconds(i as i64),
+                offset_lag: *offset_lag,
+                time_lag: Duration::zero(),
+            })
+            .collect()
+    }
+
+    /// Consumer Group `g`, consuming partitions 0 and 1 of Topic `t`.
+    fn example_group() -> GroupWithLag {
+        let lwo = |w: VecDeque<Lag>| LagWithOwner {
+            lag: w.back().cloned(),
+            owner: Some(Member {
+                id: "m-1".to_string(),
+                client_id: "c-1".to_string(),
+                client_host: "/10.0.0.1".to_string(),
+            }),
+            window: w,
+        };
+        GroupWithLag {
+            lag_by_topic_partition: HashMap::from([
+                (TopicPartition::new("t".to_string(), 0), lwo(window(&[(10, 0), (20, 0)]))),
+                (TopicPartition::new("t".to_string(), 1), lwo(window(&[(10, 5), (10, 15)]))),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    fn statuses(p0: ConsumerStatus, p1: ConsumerStatus) -> HashMap<TopicPartition, ConsumerStatus> {
+        HashMap::from([
+            (TopicPartition::new("t".to_string(), 0), p0),
+            (TopicPartition::new("t".to_string(), 1), p1),
+        ])
+    }
+
+    #[tokio::test]
+    async fn envelope() {
+        let (status, body) = json(respond(
+            "cluster list returned",
+            Clusters {
+                clusters: vec!["c".to_string()],
+            },
+            request(),
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["error"], false);
+        assert_eq!(body["message"], "cluster list returned");
+        assert_eq!(body["clusters"], serde_json::json!(["c"]));
+        assert_eq!(body["request"]["url"], "/v3/kafka/c/consumer");
+        assert_eq!(body["request"]["host"], "localhost:6564");
+
+        let (status, body) =
+            json(respond_error(StatusCode::NOT_FOUND, "cluster not found", request())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], true);
+        assert_eq!(body["message"], "cluster not found");
+        assert_eq!(body["request"]["url"], "/v3/kafka/c/consumer");
+        assert_eq!(body.as_object().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn unknown_cluster_or_group() {
+        let lag_by_group = HashMap::from([("g".to_string(), example_group())]);
+
+        assert!(find_group("c", &lag_by_group, "c", "g").is_ok());
+        assert_eq!(find_group("c", &lag_by_group, "x", "g").unwrap_err(), "cluster not found");
+        assert_eq!(
+            find_group("c", &lag_by_group, "c", "x").unwrap_err(),
+            "consumer group not found"
+        );
+    }
+
+    #[test]
+    fn rollup_to_worst_partition() {
+        let gwl = example_group();
+        let current_lags = HashMap::from([
+            (TopicPartition::new("t".to_string(), 0), 0),
+            (TopicPartition::new("t".to_string(), 1), 20),
+        ]);
+        let rollup = |p0, p1| {
+            let s = burrow_consumer_status(
+                "c".to_string(),
+                "g".to_string(),
+                &gwl,
+                &statuses(p0, p1),
+                &current_lags,
+                4,
+                false,
+            );
+            s.status
+        };
+
+        assert_eq!(rollup(ConsumerStatus::Ok, ConsumerStatus::Ok), ConsumerStatus::Ok);
+        assert_eq!(rollup(ConsumerStatus::Ok, ConsumerStatus::Warn), ConsumerStatus::Warn);
+        assert_eq!(rollup(ConsumerStatus::NotFound, ConsumerStatus::Ok), ConsumerStatus::Ok);
+        assert_eq!(rollup(ConsumerStatus::Warn, ConsumerStatus::Stall), ConsumerStatus::Err);
+        assert_eq!(rollup(ConsumerStatus::Ok, ConsumerStatus::Stop), ConsumerStatus::Err);
+        assert_eq!(rollup(ConsumerStatus::Err, ConsumerStatus::Ok), ConsumerStatus::Err);
+    }
+
+    #[test]
+    fn consumer_status_of_partitions() {
+        let current_lags = HashMap::from([
+            (TopicPartition::new("t".to_string(), 0), 0),
+            (TopicPartition::new("t".to_string(), 1), 20),
+        ]);
+        let s = burrow_consumer_status(
+            "c".to_string(),
+            "g".to_string(),
+            &example_group(),
+            &statuses(ConsumerStatus::Ok, ConsumerStatus::Warn),
+            &current_lags,
+            4,
+            true,
+        );
+
+        assert_eq!(s.partition_count, 2);
+        assert_eq!(s.totallag, 20);
+        assert_eq!(s.complete, 0.5);
+        assert_eq!(s.maxlag.unwrap().partition, 1);
+        assert_eq!(s.partitions.len(), 1);
+        let p = &s.partitions[0];
+        assert_eq!((p.partition, p.status), (1, ConsumerStatus::Warn));
+        assert_eq!((p.owner.as_str(), p.client_id.as_str()), ("/10.0.0.1", "c-1"));
+        assert_eq!(p.start.as_ref().unwrap().lag, 5);
+        assert_eq!(p.end.as_ref().unwrap().lag, 15);
+    }
+}
@@ -0,0 +1,123 @@
+//! Merge the metrics of multiple Kafka Clusters, so they can be served as one exposition.
+//!
+//! The text format requires all the samples of a metric family to be contiguous,
+//! after a single set of `# HELP` and `# TYPE` headers: concatenating is not enough.
+
+use std::collections::HashMap;
+
+use prometheus::proto::MetricFamily;
+
+use crate::prometheus_metrics::bespoke::HEADER_HELP;
+
+/// Merge the bespoke metrics (as rendered by [`super::render_bespoke_metrics`]) of multiple clusters.
+///
+/// Families keep the order they first appear in, with their headers once, followed by the samples
+/// of all the clusters in order.
+pub fn merge_bespoke(rendered: Vec<Vec<String>>) -> Vec<String> {
+    // Family name -> (headers, samples), and the order families first appear in
+    let mut families: HashMap<String, (Vec<String>, Vec<String>)> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+
+    for lines in rendered {
+        let mut current = String::new();
+        let mut seen_headers = false;
+
+        for line in lines.iter().flat_map(|l| l.lines()) {
+            if let Some(rest) = line.strip_prefix(HEADER_HELP) {
+                current = rest.split_whitespace().next().unwrap_or_default().to_string();
+                seen_headers = families.contains_key(&current);
+            }
+
+            let (headers, samples) = families.entry(current.clone()).or_insert_with(|| {
+                order.push(current.clone());
+                Default::default()
+            });
+            if line.starts_with('#') {
+                if !seen_headers {
+                    headers.push(line.to_string());
+                }
+            } else {
+                samples.push(line.to_string());
+            }
+        }
+    }
+
+    order
+        .into_iter()
+        .flat_map(|name| {
+            let (headers, samples) = families.remove(&name).unwrap_or_default();
+            headers.into_iter().chain(samples)
+        })
+        .collect()
+}
+
+/// Merge the [`MetricFamily`]s gathered from the registries of multiple clusters.
+///
+/// Families with the same name become one, with the metrics of all the clusters.
+pub fn merge_families(gathered: Vec<Vec<MetricFamily>>) -> Vec<MetricFamily> {
+    let mut merged: Vec<MetricFamily> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for mut mf in gathered.into_iter().flatten() {
+        match index.get(mf.get_name()) {
+            Some(&i) => merged[i].mut_metric().extend(mf.take_metric()),
+            None => {
+                index.insert(mf.get_name().to_string(), merged.len());
+                merged.push(mf);
+            },
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use prometheus::{IntCounter, Opts, Registry};
+
+    use super::*;
+
+    fn rendered(cluster: &str) -> Vec<String> {
+        [
+            "# HELP kmtd_a A.".to_string(),
+            "# TYPE kmtd_a gauge".to_string(),
+            format!("kmtd_a{{cluster_id=\"{cluster}\"}} 1"),
+            "# HELP kmtd_b B.\n# TYPE kmtd_b gauge".to_string(),
+            format!("kmtd_b{{cluster_id=\"{cluster}\"}} 2"),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn merge_bespoke_families() {
+        assert_eq!(
+            merge_bespoke(vec![rendered("x"), rendered("y")]),
+            [
+                "# HELP kmtd_a A.",
+                "# TYPE kmtd_a gauge",
+                r#"kmtd_a{cluster_id="x"} 1"#,
+                r#"kmtd_a{cluster_id="y"} 1"#,
+                "# HELP kmtd_b B.",
+                "# TYPE kmtd_b gauge",
+                r#"kmtd_b{cluster_id="x"} 2"#,
+                r#"kmtd_b{cluster_id="y"} 2"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_registry_families() {
+        let gather = |cluster: &str| {
+            let registry = Registry::new();
+            let counter =
+                IntCounter::with_opts(Opts::new("c", "C.").const_label("cluster_id", cluster))
+                    .unwrap();
+            registry.register(Box::new(counter)).unwrap();
+            registry.gather()
+        };
+
+        let merged = merge_families(vec![gather("x"), gather("y")]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].get_metric().len(), 2);
+    }
+}
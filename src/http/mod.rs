@@ -1,4 +1,10 @@
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+mod api;
+mod burrow;
+mod merge;
+mod multi;
+mod stream;
+
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
 
 use axum::{
     extract::State,
@@ -7,15 +13,27 @@ use axum::{
     routing::get,
     Router,
 };
+use chrono::Utc;
 use prometheus::{Registry, TextEncoder};
 use tokio::net::TcpListener;
 use tokio_util::sync::CancellationToken;
 use tower_http::timeout::TimeoutLayer;
 
+use crate::alerts::AlertsRegister;
 use crate::cluster_status::ClusterStatusRegister;
+use crate::clusters::ClusterSet;
+use crate::consumer_rates;
+use crate::consumer_status::evaluate_groups;
+use crate::data_loss::{predict_time_to_data_loss, DataLossRegister};
+use crate::influx;
+use crate::labels_mapping::{render_labels, LabelsMapper};
+use crate::lag_history::LagHistoryRegister;
 use crate::lag_register::LagRegister;
 use crate::partition_offsets::PartitionOffsetsRegister;
-use crate::prometheus_metrics::bespoke::*;
+use crate::prometheus_metrics::{
+    bespoke::*, compat, ConsumerMetricsLevel, MetricsProfile, OwnerLabels, SeriesDropPolicy,
+};
+use crate::slo::SloRegister;
 
 // TODO https://github.com/kafkesc/kommitted/issues/47
 // TODO https://github.com/kafkesc/kommitted/issues/48
@@ -25,39 +43,73 @@ use crate::prometheus_metrics::bespoke::*;
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// State shared by all the routes of the HTTP Service.
 #[derive(Clone)]
-struct HttpServiceState {
-    cs_reg: Arc<ClusterStatusRegister>,
-    po_reg: Arc<PartitionOffsetsRegister>,
-    lag_reg: Arc<LagRegister>,
-    metrics: Arc<Registry>,
+pub struct HttpServiceState {
+    pub metrics_profiles: Arc<Vec<MetricsProfile>>,
+    pub consumer_metrics: Arc<Vec<ConsumerMetricsLevel>>,
+    pub owner_labels: OwnerLabels,
+    pub partitions_limit: Option<usize>,
+    pub series_drop_policy: SeriesDropPolicy,
+    pub cs_reg: Arc<ClusterStatusRegister>,
+    pub po_reg: Arc<PartitionOffsetsRegister>,
+    pub lag_reg: Arc<LagRegister>,
+    pub lag_history: Arc<LagHistoryRegister>,
+    pub data_loss: Arc<DataLossRegister>,
+    pub alerts: Arc<AlertsRegister>,
+    pub slos: Arc<SloRegister>,
+    pub labels_mapper: Arc<LabelsMapper>,
+    pub metrics: Arc<Registry>,
+    pub shutdown_token: CancellationToken,
+}
+
+/// Router serving a single Kafka Cluster, described by `state`.
+pub fn cluster_router(state: HttpServiceState) -> Router {
+    Router::new()
+        // `GET /` goes to `root`
+        .route("/", get(root))
+        .route("/metrics", get(prometheus_metrics))
+        .route("/metrics/influx", get(influx_metrics))
+        .route("/api/v1/groups", get(api::groups))
+        .route("/api/v1/groups/:group", get(api::group))
+        .route("/api/v1/groups/:group/history", get(api::group_history))
+        .route("/api/v1/groups/:group/scaling", get(api::group_scaling))
+        .route("/api/v1/data-loss", get(api::data_loss))
+        .route("/api/v1/alerts", get(api::alerts))
+        .route("/api/v1/stream", get(stream::sse))
+        .route("/api/v1/stream/ws", get(stream::ws))
+        // Burrow-compatible API
+        .route("/v3/kafka", get(burrow::clusters))
+        .route("/v3/kafka/:cluster/topic", get(burrow::topics))
+        .route("/v3/kafka/:cluster/topic/:topic", get(burrow::topic))
+        .route("/v3/kafka/:cluster/consumer", get(burrow::consumers))
+        .route("/v3/kafka/:cluster/consumer/:group/lag", get(burrow::consumer_lag))
+        .route("/v3/kafka/:cluster/consumer/:group/status", get(burrow::consumer_status))
+        .with_state(state)
 }
 
+/// Serve a single Kafka Cluster.
 pub async fn init(
     listen_on: SocketAddr,
-    cs_reg: Arc<ClusterStatusRegister>,
-    po_reg: Arc<PartitionOffsetsRegister>,
-    lag_reg: Arc<LagRegister>,
+    state: HttpServiceState,
     shutdown_token: CancellationToken,
-    metrics: Arc<Registry>,
 ) {
-    // Assemble the HTTP Service State object, that will be passed to the routes
-    let state = HttpServiceState {
-        cs_reg,
-        po_reg,
-        lag_reg,
-        metrics,
-    };
+    serve(listen_on, cluster_router(state), shutdown_token).await
+}
 
-    // Setup Router
-    let app = Router::new()
-        // `GET /` goes to `root`
-        .route("/", get(root))
-        .route("/metrics", get(prometheus_metrics))
-        // In addition to handling shutdown gracefully (see below),
-        // enforce a request timeout just to avoid requests hanging forever.
-        .layer(TimeoutLayer::new(REQUEST_TIMEOUT))
-        .with_state(state);
+/// Serve all the Kafka Clusters of a [`ClusterSet`]: see [`multi`].
+pub async fn init_multi(
+    listen_on: SocketAddr,
+    clusters: Arc<ClusterSet>,
+    shutdown_token: CancellationToken,
+) {
+    serve(listen_on, multi::router(clusters), shutdown_token).await
+}
+
+async fn serve(listen_on: SocketAddr, app: Router, shutdown_token: CancellationToken) {
+    // In addition to handling shutdown gracefully (see below),
+    // enforce a request timeout just to avoid requests hanging forever.
+    let app = app.layer(TimeoutLayer::new(REQUEST_TIMEOUT));
 
     // Setup Connections Listener
     info!("Begin listening on '{}'...", listen_on);
@@ -78,133 +130,499 @@ async fn prometheus_metrics(State(state): State<HttpServiceState>) -> impl IntoR
     let mut status = StatusCode::OK;
     let mut headers = HeaderMap::new();
 
+    // As defined by Prometheus: https://github.com/prometheus/docs/blob/main/content/docs/instrumenting/exposition_formats.md#basic-info
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; version=0.0.4"));
+
+    // Turn the bespoke metrics into a single String
+    let mut body = render_bespoke_metrics(&state).await.join("\n") + "\n";
+
+    // Append to the bespoke metrics, classic Prometheus Metrics
+    let metrics_family = state.metrics.gather();
+    if let Err(e) = TextEncoder.encode_utf8(&metrics_family, &mut body) {
+        status = StatusCode::INTERNAL_SERVER_ERROR;
+        body = format!("Failed to encode metrics: {e}");
+    }
+
+    (status, headers, body)
+}
+
+async fn influx_metrics(State(state): State<HttpServiceState>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+
+    let mut body = influx::render_lines(&state).await.join("\n");
+    body.push('\n');
+
+    (StatusCode::OK, headers, body)
+}
+
+/// Render the bespoke metrics (i.e. all but the classic ones in the [`Registry`]),
+/// in Prometheus text format: one line per element.
+pub async fn render_bespoke_metrics(state: &HttpServiceState) -> Vec<String> {
     // Procure the Cluster ID once and reuse it in all metrics that get generated
     let cluster_id = state.cs_reg.get_cluster_id().await;
 
     // Procure the TopicPartitions once and reuse it in all metrics that need it
     let tps = state.cs_reg.get_topic_partitions().await;
 
-    // As defined by Prometheus: https://github.com/prometheus/docs/blob/main/content/docs/instrumenting/exposition_formats.md#basic-info
-    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; version=0.0.4"));
+    // Use the same labels mapping throughout, even if it gets reloaded in the meantime
+    let labels_mapping = state.labels_mapper.current();
+
+    // Read the lag of all the groups once, so that all metrics are rendered from the same state
+    let lag_by_group = state.lag_reg.lag_by_group.read().await;
 
     // Allocate a Vector of Strings to build the body of the output.
     // The capacity is pre-calculated to try to do as little mem-alloc as possible.
     //
     // The capacity is necessarily a function of the number of metric types produced,
-    // and the number of topic partitions.
-    let tp_count: usize = state
-        .lag_reg
-        .lag_by_group
-        .read()
-        .await
-        .iter()
-        .map(|(_, gwl)| gwl.lag_by_topic_partition.len())
-        .sum();
-    let metric_types_count: usize = 3;
+    // and the number of topic partitions: it's estimated from the 7 types of per-partition metrics,
+    // the most numerous.
+    let tp_count: usize = lag_by_group.values().map(|gwl| gwl.lag_by_topic_partition.len()).sum();
+    let metric_types_count: usize = 7;
     let headers_footers_count: usize = metric_types_count * 2;
     let metrics_count: usize = tp_count * metric_types_count;
     let mut body: Vec<String> = Vec::with_capacity(metrics_count + headers_footers_count);
 
-    // ----------------------------------------------------------- METRIC: consumer_partition_offset
-    consumer_partition_offset::append_headers(&mut body);
-    iter_lag_reg(&state.lag_reg, &mut body, &cluster_id, consumer_partition_offset::append_metric)
-        .await;
-
-    // ------------------------------------------------------- METRIC: consumer_partition_lag_offset
-    consumer_partition_lag_offset::append_headers(&mut body);
-    iter_lag_reg(
-        &state.lag_reg,
-        &mut body,
-        &cluster_id,
-        consumer_partition_lag_offset::append_metric,
-    )
-    .await;
-
-    // ------------------------------------------------- METRIC: consumer_partition_lag_milliseconds
-    consumer_partition_lag_milliseconds::append_headers(&mut body);
-    iter_lag_reg(
-        &state.lag_reg,
-        &mut body,
-        &cluster_id,
-        consumer_partition_lag_milliseconds::append_metric,
-    )
-    .await;
-
-    // ------------------------------------------------- METRIC: partition_earliest_available_offset
-    partition_earliest_available_offset::append_headers(&mut body);
-    for tp in tps.iter() {
-        match state.po_reg.get_earliest_available_offset(tp).await {
-            Ok(eao) => {
-                partition_earliest_available_offset::append_metric(
-                    &cluster_id,
-                    &tp.topic,
-                    tp.partition,
-                    eao,
-                    &mut body,
-                );
-            },
-            Err(e) => {
-                warn!("Unable to generate 'partition_earliest_available_offset': {e}");
-            },
-        }
+    // Native metrics are optional, if other metrics profiles are enabled
+    let native = state.metrics_profiles.contains(&MetricsProfile::Kommitted);
+
+    // Evaluate the status of all consumers once, to reuse it across metrics
+    let group_statuses = if native {
+        evaluate_groups(&lag_by_group, &state.po_reg, Utc::now()).await
+    } else {
+        HashMap::new()
+    };
+
+    if native && state.consumer_metrics.contains(&ConsumerMetricsLevel::Partition) {
+        let (consumer_partitions, suppressed) = select_consumer_partitions(
+            &lag_by_group,
+            state.owner_labels,
+            &labels_mapping,
+            state.partitions_limit,
+            state.series_drop_policy,
+        );
+
+        // ----------------------------------------------------------- METRIC: consumer_partition_offset
+        consumer_partition_offset::append_headers(&mut body);
+        iter_consumer_partitions(
+            &consumer_partitions,
+            &mut body,
+            &cluster_id,
+            consumer_partition_offset::append_metric,
+        );
+
+        // ------------------------------------------------------- METRIC: consumer_partition_lag_offset
+        consumer_partition_lag_offset::append_headers(&mut body);
+        iter_consumer_partitions(
+            &consumer_partitions,
+            &mut body,
+            &cluster_id,
+            consumer_partition_lag_offset::append_metric,
+        );
+
+        // ------------------------------------------------- METRIC: consumer_partition_lag_milliseconds
+        consumer_partition_lag_milliseconds::append_headers(&mut body);
+        iter_consumer_partitions(
+            &consumer_partitions,
+            &mut body,
+            &cluster_id,
+            consumer_partition_lag_milliseconds::append_metric,
+        );
+
+        // ------------------------------------------------------------ METRIC: consumer_partition_status
+        consumer_partition_status::append_headers(&mut body);
+        iter_consumer_partitions_status(
+            &consumer_partitions,
+            &mut body,
+            &cluster_id,
+            &group_statuses,
+        );
+
+        // ---------------------------------------- METRIC: consumer_partition_consume_offsets_per_second
+        let rates = consumer_rates::evaluate_groups(&lag_by_group, &state.po_reg).await;
+        consumer_partition_consume_offsets_per_second::append_headers(&mut body);
+        iter_consumer_partitions_rates(
+            &consumer_partitions,
+            &mut body,
+            &cluster_id,
+            &rates,
+            consumer_partition_consume_offsets_per_second::append_metric,
+        );
+
+        // ----------------------------------------- METRIC: consumer_partition_catch_up_eta_milliseconds
+        consumer_partition_catch_up_eta_milliseconds::append_headers(&mut body);
+        iter_consumer_partitions_rates(
+            &consumer_partitions,
+            &mut body,
+            &cluster_id,
+            &rates,
+            consumer_partition_catch_up_eta_milliseconds::append_metric,
+        );
+
+        // ------------------------------------ METRIC: consumer_partition_time_to_data_loss_milliseconds
+        let topic_configs = state.cs_reg.get_topic_configs().await;
+        let predictions =
+            predict_time_to_data_loss(&lag_by_group, &topic_configs, &state.po_reg).await;
+        consumer_partition_time_to_data_loss_milliseconds::append_headers(&mut body);
+        iter_consumer_partitions_time_to_data_loss(
+            &consumer_partitions,
+            &mut body,
+            &cluster_id,
+            &predictions,
+        );
+
+        // ------------------------------------------------- METRIC: consumer_partition_series_suppressed
+        consumer_partition_series_suppressed::append_headers(&mut body);
+        consumer_partition_series_suppressed::append_metric(
+            &cluster_id,
+            suppressed,
+            &render_labels(labels_mapping.static_labels()),
+            &mut body,
+        );
     }
 
-    // ------------------------------------------------- METRIC: partition_latest_available_offset
-    partition_latest_available_offset::append_headers(&mut body);
-    for tp in tps.iter() {
-        match state.po_reg.get_latest_available_offset(tp).await {
-            Ok(lao) => {
-                partition_latest_available_offset::append_metric(
-                    &cluster_id,
-                    &tp.topic,
-                    tp.partition,
-                    lao,
-                    &mut body,
-                );
-            },
-            Err(e) => {
-                warn!("Unable to generate 'partition_latest_available_offset': {e}");
-            },
+    if native && state.consumer_metrics.contains(&ConsumerMetricsLevel::GroupTopic) {
+        let group_topic_aggregates = aggregate_group_topics(&lag_by_group, &labels_mapping);
+
+        // ------------------------------------------ METRIC: consumer_group_topic_lag_offset_sum
+        consumer_group_topic_lag_offset_sum::append_headers(&mut body);
+        iter_group_topic_aggregates(
+            &group_topic_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_topic_lag_offset_sum::append_metric,
+        );
+
+        // ------------------------------------------ METRIC: consumer_group_topic_lag_milliseconds_max
+        consumer_group_topic_lag_milliseconds_max::append_headers(&mut body);
+        iter_group_topic_aggregates(
+            &group_topic_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_topic_lag_milliseconds_max::append_metric,
+        );
+
+        // ------------------------------------------ METRIC: consumer_group_topic_partitions_lagging
+        consumer_group_topic_partitions_lagging::append_headers(&mut body);
+        iter_group_topic_aggregates(
+            &group_topic_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_topic_partitions_lagging::append_metric,
+        );
+
+        // ------------------------------------------ METRIC: consumer_group_topic_partitions_owned
+        consumer_group_topic_partitions_owned::append_headers(&mut body);
+        iter_group_topic_aggregates(
+            &group_topic_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_topic_partitions_owned::append_metric,
+        );
+
+        // ------------------------------------------ METRIC: consumer_group_topic_partitions_unowned
+        consumer_group_topic_partitions_unowned::append_headers(&mut body);
+        iter_group_topic_aggregates(
+            &group_topic_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_topic_partitions_unowned::append_metric,
+        );
+    }
+
+    // The status of each group rolls up the status of its partitions: emitted alongside it
+    if native
+        && (state.consumer_metrics.contains(&ConsumerMetricsLevel::Partition)
+            || state.consumer_metrics.contains(&ConsumerMetricsLevel::Group))
+    {
+        // -------------------------------------------------------------- METRIC: consumer_group_status
+        consumer_group_status::append_headers(&mut body);
+        for (g, gs) in group_statuses.iter() {
+            consumer_group_status::append_metric(
+                &cluster_id,
+                g,
+                gs.status,
+                &render_labels(&labels_mapping.group_labels(g)),
+                &mut body,
+            );
         }
     }
 
-    // ------------------------------------------------- METRIC: partition_earliest_tracked_offset
-    partition_earliest_tracked_offset::append_headers(&mut body);
-    for tp in tps.iter() {
-        match state.po_reg.get_earliest_tracked_offset(tp).await {
-            Ok(eto) => {
-                partition_earliest_tracked_offset::append_metric(
+    if native && state.consumer_metrics.contains(&ConsumerMetricsLevel::Group) {
+        let group_aggregates = aggregate_groups(&lag_by_group, &labels_mapping);
+
+        // ------------------------------------------------ METRIC: consumer_group_lag_offset_sum
+        consumer_group_lag_offset_sum::append_headers(&mut body);
+        iter_group_aggregates(
+            &group_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_lag_offset_sum::append_metric,
+        );
+
+        // ------------------------------------------------ METRIC: consumer_group_lag_milliseconds_max
+        consumer_group_lag_milliseconds_max::append_headers(&mut body);
+        iter_group_aggregates(
+            &group_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_lag_milliseconds_max::append_metric,
+        );
+
+        // ------------------------------------------------ METRIC: consumer_group_partitions_lagging
+        consumer_group_partitions_lagging::append_headers(&mut body);
+        iter_group_aggregates(
+            &group_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_partitions_lagging::append_metric,
+        );
+
+        // ------------------------------------------------ METRIC: consumer_group_partitions_owned
+        consumer_group_partitions_owned::append_headers(&mut body);
+        iter_group_aggregates(
+            &group_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_partitions_owned::append_metric,
+        );
+
+        // ------------------------------------------------ METRIC: consumer_group_partitions_unowned
+        consumer_group_partitions_unowned::append_headers(&mut body);
+        iter_group_aggregates(
+            &group_aggregates,
+            &mut body,
+            &cluster_id,
+            consumer_group_partitions_unowned::append_metric,
+        );
+    }
+
+    if native {
+        // ---------------------------------------------- METRIC: consumer_partition_data_loss_offsets
+        consumer_partition_data_loss_offsets::append_headers(&mut body);
+        for dl in state.data_loss.ongoing().await {
+            consumer_partition_data_loss_offsets::append_metric(
+                &cluster_id,
+                &dl,
+                &render_labels(
+                    &labels_mapping.group_topic_labels(&dl.group, &dl.topic_partition.topic),
+                ),
+                &mut body,
+            );
+        }
+
+        // ------------------------------------- METRIC: consumer_group_topic_data_loss_incidents_total
+        consumer_group_topic_data_loss_incidents_total::append_headers(&mut body);
+        for (g, t, incidents) in state.data_loss.incidents().await {
+            consumer_group_topic_data_loss_incidents_total::append_metric(
+                &cluster_id,
+                &g,
+                &t,
+                incidents,
+                &render_labels(&labels_mapping.group_topic_labels(&g, &t)),
+                &mut body,
+            );
+        }
+
+        // Status of the SLOs, and labels from the labels mapping for each of their groups
+        let slo_statuses = state.slos.status(Utc::now()).await;
+        let mut group_labels: HashMap<&str, String> = HashMap::new();
+        for s in slo_statuses.iter() {
+            group_labels
+                .entry(s.group.as_str())
+                .or_insert_with(|| render_labels(&labels_mapping.group_labels(&s.group)));
+        }
+
+        // ------------------------------------------------ METRIC: consumer_group_slo_objective_ratio
+        consumer_group_slo_objective_ratio::append_headers(&mut body);
+        for s in slo_statuses.iter() {
+            let extra_labels = &group_labels[s.group.as_str()];
+            consumer_group_slo_objective_ratio::append_metric(
+                &cluster_id,
+                s,
+                extra_labels,
+                &mut body,
+            );
+        }
+
+        // ---------------------------------------------- METRIC: consumer_group_slo_events_good_total
+        consumer_group_slo_events_good_total::append_headers(&mut body);
+        for s in slo_statuses.iter() {
+            let extra_labels = &group_labels[s.group.as_str()];
+            consumer_group_slo_events_good_total::append_metric(
+                &cluster_id,
+                s,
+                extra_labels,
+                &mut body,
+            );
+        }
+
+        // --------------------------------------------------- METRIC: consumer_group_slo_events_total
+        consumer_group_slo_events_total::append_headers(&mut body);
+        for s in slo_statuses.iter() {
+            let extra_labels = &group_labels[s.group.as_str()];
+            consumer_group_slo_events_total::append_metric(&cluster_id, s, extra_labels, &mut body);
+        }
+
+        // ---------------------------------- METRIC: consumer_group_slo_error_budget_remaining_ratio
+        consumer_group_slo_error_budget_remaining_ratio::append_headers(&mut body);
+        for s in slo_statuses.iter() {
+            let extra_labels = &group_labels[s.group.as_str()];
+            consumer_group_slo_error_budget_remaining_ratio::append_metric(
+                &cluster_id,
+                s,
+                extra_labels,
+                &mut body,
+            );
+        }
+
+        // ------------------------------------------------------ METRIC: consumer_group_slo_burn_rate
+        consumer_group_slo_burn_rate::append_headers(&mut body);
+        for s in slo_statuses.iter() {
+            let extra_labels = &group_labels[s.group.as_str()];
+            consumer_group_slo_burn_rate::append_metric(&cluster_id, s, extra_labels, &mut body);
+        }
+
+        // Labels from the labels mapping, for each topic
+        let mut topic_labels: HashMap<&str, String> = HashMap::new();
+        for tp in tps.iter() {
+            topic_labels
+                .entry(tp.topic.as_str())
+                .or_insert_with(|| render_labels(&labels_mapping.topic_labels(&tp.topic)));
+        }
+
+        // --------------------------------------------- METRIC: partition_earliest_available_offset
+        partition_earliest_available_offset::append_headers(&mut body);
+        for tp in tps.iter() {
+            match state.po_reg.get_earliest_available_offset(tp).await {
+                Ok(eao) => {
+                    partition_earliest_available_offset::append_metric(
+                        &cluster_id,
+                        &tp.topic,
+                        tp.partition,
+                        eao,
+                        &topic_labels[tp.topic.as_str()],
+                        &mut body,
+                    );
+                },
+                Err(e) => {
+                    warn!("Unable to generate 'partition_earliest_available_offset': {e}");
+                },
+            }
+        }
+
+        // --------------------------------------------- METRIC: partition_latest_available_offset
+        partition_latest_available_offset::append_headers(&mut body);
+        for tp in tps.iter() {
+            match state.po_reg.get_latest_available_offset(tp).await {
+                Ok(lao) => {
+                    partition_latest_available_offset::append_metric(
+                        &cluster_id,
+                        &tp.topic,
+                        tp.partition,
+                        lao,
+                        &topic_labels[tp.topic.as_str()],
+                        &mut body,
+                    );
+                },
+                Err(e) => {
+                    warn!("Unable to generate 'partition_latest_available_offset': {e}");
+                },
+            }
+        }
+
+        // --------------------------------------------- METRIC: partition_earliest_tracked_offset
+        partition_earliest_tracked_offset::append_headers(&mut body);
+        for tp in tps.iter() {
+            match state.po_reg.get_earliest_tracked_offset(tp).await {
+                Ok(eto) => {
+                    partition_earliest_tracked_offset::append_metric(
+                        &cluster_id,
+                        &tp.topic,
+                        tp.partition,
+                        eto.offset,
+                        eto.at.timestamp_millis(),
+                        &topic_labels[tp.topic.as_str()],
+                        &mut body,
+                    );
+                },
+                Err(e) => {
+                    warn!("Unable to generate 'partition_earliest_tracked_offset': {e}");
+                },
+            }
+        }
+
+        // --------------------------------------------- METRIC: partition_latest_tracked_offset
+        partition_latest_tracked_offset::append_headers(&mut body);
+        for tp in tps.iter() {
+            match state.po_reg.get_latest_tracked_offset(tp).await {
+                Ok(lto) => {
+                    partition_latest_tracked_offset::append_metric(
+                        &cluster_id,
+                        &tp.topic,
+                        tp.partition,
+                        lto.offset,
+                        lto.at.timestamp_millis(),
+                        &topic_labels[tp.topic.as_str()],
+                        &mut body,
+                    );
+                },
+                Err(e) => {
+                    warn!("Unable to generate 'partition_latest_tracked_offset': {e}");
+                },
+            }
+        }
+
+        // ---------------------------------------- METRIC: partition_produce_offsets_per_second
+        partition_produce_offsets_per_second::append_headers(&mut body);
+        for tp in tps.iter() {
+            // Skipped until enough offsets are tracked: not worth logging at every scrape
+            if let Ok(rate) = state.po_reg.estimate_produce_rate(tp).await {
+                partition_produce_offsets_per_second::append_metric(
                     &cluster_id,
                     &tp.topic,
                     tp.partition,
-                    eto.offset,
-                    eto.at.timestamp_millis(),
+                    rate,
+                    &topic_labels[tp.topic.as_str()],
                     &mut body,
                 );
-            },
-            Err(e) => {
-                warn!("Unable to generate 'partition_earliest_tracked_offset': {e}");
-            },
+            }
         }
     }
 
-    // ------------------------------------------------- METRIC: partition_latest_tracked_offset
-    partition_latest_tracked_offset::append_headers(&mut body);
-    for tp in tps.iter() {
-        match state.po_reg.get_latest_tracked_offset(tp).await {
-            Ok(lto) => {
-                partition_latest_tracked_offset::append_metric(
+    // --- COMPATIBILITY METRICS ---
+    if state.metrics_profiles.iter().any(|p| *p != MetricsProfile::Kommitted) {
+        let watermarks = compat::collect_watermarks(&state.po_reg, &tps).await;
+
+        // Owner labels follow the conventions of each profile, if any
+        let (consumer_partitions, _) = select_consumer_partitions(
+            &lag_by_group,
+            OwnerLabels::None,
+            &labels_mapping,
+            state.partitions_limit,
+            state.series_drop_policy,
+        );
+
+        for profile in state.metrics_profiles.iter() {
+            match profile {
+                MetricsProfile::Kommitted => {},
+                MetricsProfile::KafkaLagExporter => compat::kafka_lag_exporter::append_metrics(
                     &cluster_id,
-                    &tp.topic,
-                    tp.partition,
-                    lto.offset,
-                    lto.at.timestamp_millis(),
+                    &consumer_partitions,
+                    &lag_by_group,
+                    &watermarks,
+                    state.owner_labels,
+                    &labels_mapping,
                     &mut body,
-                );
-            },
-            Err(e) => {
-                warn!("Unable to generate 'partition_latest_tracked_offset': {e}");
-            },
+                ),
+                MetricsProfile::KafkaExporter => compat::kafka_exporter::append_metrics(
+                    &consumer_partitions,
+                    &lag_by_group,
+                    &watermarks,
+                    &labels_mapping,
+                    &mut body,
+                ),
+            }
         }
     }
 
@@ -217,15 +635,5 @@ async fn prometheus_metrics(State(state): State<HttpServiceState>) -> impl IntoR
     // TODO https://github.com/kafkesc/kommitted/issues/56
     // TODO https://github.com/kafkesc/kommitted/issues/57
 
-    // Turn the bespoke metrics created so far, into a single String
-    let mut body = body.join("\n") + "\n";
-
-    // Append to the bespoke metrics, classic Prometheus Metrics
-    let metrics_family = state.metrics.gather();
-    if let Err(e) = TextEncoder.encode_utf8(&metrics_family, &mut body) {
-        status = StatusCode::INTERNAL_SERVER_ERROR;
-        body = format!("Failed to encode metrics: {e}");
-    }
-
-    (status, headers, body)
+    body
 }
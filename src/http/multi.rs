@@ -0,0 +1,137 @@
+//! HTTP Service of multiple Kafka Clusters, as tracked by a [`ClusterSet`].
+//!
+//! - `/metrics` and `/metrics/influx` serve all the ready clusters at once
+//! - `/api/v1/clusters` lists the clusters, with the status of their pipeline
+//! - `/clusters/:cluster/...` serves everything a single cluster serves (e.g. `/clusters/eu-1/api/v1/groups`)
+//! - `/v3/kafka/:cluster/...` dispatches each Burrow-compatible request to the cluster it names
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use prometheus::TextEncoder;
+use tower::ServiceExt;
+
+use crate::clusters::{ClusterSet, ClusterStatus};
+use crate::influx;
+
+use super::burrow::{cluster_list, BurrowRequest};
+use super::merge::{merge_bespoke, merge_families};
+use super::{render_bespoke_metrics, root};
+
+pub(super) fn router(clusters: Arc<ClusterSet>) -> Router {
+    Router::new()
+        .route("/", get(root))
+        .route("/metrics", get(prometheus_metrics))
+        .route("/metrics/influx", get(influx_metrics))
+        .route("/api/v1/clusters", get(clusters_list))
+        .route("/clusters/:cluster/*rest", get(cluster))
+        .route("/v3/kafka", get(burrow_clusters))
+        .route("/v3/kafka/:cluster/*rest", get(burrow_cluster))
+        .with_state(clusters)
+}
+
+async fn prometheus_metrics(State(clusters): State<Arc<ClusterSet>>) -> impl IntoResponse {
+    let mut status = StatusCode::OK;
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; version=0.0.4"));
+
+    let states = clusters.ready_states().await;
+    let mut rendered = Vec::with_capacity(states.len());
+    for s in &states {
+        rendered.push(render_bespoke_metrics(s).await);
+    }
+    let mut body = merge_bespoke(rendered).join("\n") + "\n";
+
+    let metrics_families = merge_families(states.iter().map(|s| s.metrics.gather()).collect());
+    if let Err(e) = TextEncoder.encode_utf8(&metrics_families, &mut body) {
+        status = StatusCode::INTERNAL_SERVER_ERROR;
+        body = format!("Failed to encode metrics: {e}");
+    }
+
+    (status, headers, body)
+}
+
+async fn influx_metrics(State(clusters): State<Arc<ClusterSet>>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+
+    let mut body = String::new();
+    for s in clusters.ready_states().await {
+        for line in influx::render_lines(&s).await {
+            body.push_str(&line);
+            body.push('\n');
+        }
+    }
+
+    (StatusCode::OK, headers, body)
+}
+
+/// `GET /api/v1/clusters`
+async fn clusters_list(State(clusters): State<Arc<ClusterSet>>) -> impl IntoResponse {
+    Json(clusters.infos().await)
+}
+
+/// `GET /clusters/:cluster/*rest`: serve `/*rest` of the cluster.
+async fn cluster(
+    State(clusters): State<Arc<ClusterSet>>,
+    Path((cluster, rest)): Path<(String, String)>,
+    mut req: Request,
+) -> Response {
+    let path_and_query = match req.uri().query() {
+        Some(q) => format!("/{rest}?{q}"),
+        None => format!("/{rest}"),
+    };
+    match path_and_query.parse::<Uri>() {
+        Ok(uri) => *req.uri_mut() = uri,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    }
+
+    dispatch(&clusters, &cluster, req).await
+}
+
+/// `GET /v3/kafka`
+async fn burrow_clusters(
+    State(clusters): State<Arc<ClusterSet>>,
+    request: BurrowRequest,
+) -> Response {
+    let ready = clusters
+        .infos()
+        .await
+        .into_iter()
+        .filter(|c| c.status == ClusterStatus::Ready)
+        .map(|c| c.id)
+        .collect();
+    cluster_list(ready, request)
+}
+
+/// `GET /v3/kafka/:cluster/*rest`: the path is the same the cluster serves.
+async fn burrow_cluster(
+    State(clusters): State<Arc<ClusterSet>>,
+    Path((cluster, _)): Path<(String, String)>,
+    req: Request,
+) -> Response {
+    dispatch(&clusters, &cluster, req).await
+}
+
+/// Let the router of the `cluster` serve the request, if the cluster is ready.
+async fn dispatch(clusters: &ClusterSet, cluster: &str, req: Request<Body>) -> Response {
+    match clusters.router(cluster).await {
+        Some((_, Some(router))) => match router.oneshot(req).await {
+            Ok(res) => res,
+            Err(never) => match never {},
+        },
+        Some((status, None)) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Cluster '{cluster}' is not ready: {status:?}"),
+        )
+            .into_response(),
+        None => (StatusCode::NOT_FOUND, format!("Cluster '{cluster}' not found")).into_response(),
+    }
+}
@@ -0,0 +1,230 @@
+//! Stream of the changes of the [`LagRegister`], as Server-Sent Events or WebSocket messages.
+//!
+//! Each subscriber is fed by its own task, that forwards the matching [`LagChange`]s.
+//! A slow subscriber never slows down the register: once it falls too far behind,
+//! it skips the oldest changes and receives a [`Lagged`] message instead.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+
+use crate::lag_register::{LagChange, LagRegister};
+
+use super::HttpServiceState;
+
+/// Amount of messages buffered between the task feeding a subscriber, and the subscriber itself.
+const SUBSCRIBER_BUFFER: usize = 64;
+
+/// Query parameters of [`sse`] and [`ws`].
+#[derive(Debug, Default, Clone, Deserialize)]
+pub(super) struct StreamParams {
+    /// Only stream the changes of this Consumer Group.
+    group: Option<String>,
+
+    /// Only stream the changes of this Topic.
+    topic: Option<String>,
+}
+
+impl StreamParams {
+    fn matches(&self, change: &LagChange) -> bool {
+        self.group.as_ref().is_none_or(|g| g == change.group())
+            && self.topic.as_ref().is_none_or(|t| *t == change.topic_partition().topic)
+    }
+}
+
+/// Sent to a subscriber that fell behind, in place of the changes it skipped.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename = "lagged")]
+struct Lagged {
+    skipped: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum StreamMessage {
+    Change(LagChange),
+    Lagged(Lagged),
+}
+
+impl StreamMessage {
+    fn event_name(&self) -> &'static str {
+        match self {
+            StreamMessage::Change(LagChange::Lag {
+                ..
+            }) => "lag",
+            StreamMessage::Change(LagChange::Owner {
+                ..
+            }) => "owner",
+            StreamMessage::Change(LagChange::Removed {
+                ..
+            }) => "removed",
+            StreamMessage::Lagged(_) => "lagged",
+        }
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            error!("Failed to serialize stream message: {e}");
+            String::default()
+        })
+    }
+}
+
+/// Subscribe to the [`LagRegister`], spawning a task that forwards the changes matching `params`.
+///
+/// The task terminates when the returned receiver is dropped, or the `shutdown_token` is cancelled.
+fn subscribe(
+    lag_reg: &LagRegister,
+    params: StreamParams,
+    shutdown_token: CancellationToken,
+) -> mpsc::Receiver<StreamMessage> {
+    let mut changes_rx = lag_reg.subscribe();
+    let (tx, rx) = mpsc::channel(SUBSCRIBER_BUFFER);
+
+    tokio::spawn(async move {
+        loop {
+            let msg = tokio::select! {
+                res = changes_rx.recv() => match res {
+                    Ok(change) if params.matches(&change) => StreamMessage::Change(change),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("Stream subscriber fell behind: skipped {skipped} changes");
+                        StreamMessage::Lagged(Lagged { skipped })
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = tx.closed() => break,
+                _ = shutdown_token.cancelled() => break,
+            };
+
+            // While waiting for the subscriber to make room, changes keep
+            // being buffered by the register: eventually, they are skipped.
+            if tx.send(msg).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+/// `GET /api/v1/stream?group=&topic=`
+///
+/// Streams the changes of the lag of the Consumer Groups, as Server-Sent Events.
+pub(super) async fn sse(
+    State(state): State<HttpServiceState>,
+    Query(params): Query<StreamParams>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = subscribe(&state.lag_reg, params, state.shutdown_token.clone());
+
+    let stream = ReceiverStream::new(rx)
+        .map(|msg| Ok(Event::default().event(msg.event_name()).data(msg.to_json())));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// `GET /api/v1/stream/ws?group=&topic=`
+///
+/// Streams the changes of the lag of the Consumer Groups, as WebSocket text messages.
+pub(super) async fn ws(
+    State(state): State<HttpServiceState>,
+    Query(params): Query<StreamParams>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade.on_upgrade(move |socket| forward_to_socket(socket, state, params))
+}
+
+async fn forward_to_socket(mut socket: WebSocket, state: HttpServiceState, params: StreamParams) {
+    let mut rx = subscribe(&state.lag_reg, params, state.shutdown_token.clone());
+
+    loop {
+        tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(msg) => {
+                    if socket.send(Message::Text(msg.to_json())).await.is_err() {
+                        break;
+                    }
+                },
+                None => {
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                },
+            },
+            // Messages from the client are ignored, but it's how we know it went away
+            incoming = socket.recv() => match incoming {
+                None | Some(Err(_)) | Some(Ok(Message::Close(_))) => break,
+                Some(Ok(_)) => {},
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kafka_types::TopicPartition;
+    use crate::lag_register::{Lag, LagChange};
+
+    use super::{Lagged, StreamMessage, StreamParams};
+
+    fn removed(group: &str, topic: &str) -> LagChange {
+        LagChange::Removed {
+            group: group.to_string(),
+            topic_partition: TopicPartition::new(topic.to_string(), 0),
+        }
+    }
+
+    #[test]
+    fn filter_by_group_and_topic() {
+        let all = StreamParams::default();
+        assert!(all.matches(&removed("g", "t")));
+
+        let group = StreamParams {
+            group: Some("g".to_string()),
+            topic: None,
+        };
+        assert!(group.matches(&removed("g", "t")));
+        assert!(!group.matches(&removed("h", "t")));
+
+        let group_topic = StreamParams {
+            group: Some("g".to_string()),
+            topic: Some("t".to_string()),
+        };
+        assert!(group_topic.matches(&removed("g", "t")));
+        assert!(!group_topic.matches(&removed("g", "u")));
+    }
+
+    #[test]
+    fn messages_as_json() {
+        let lag = StreamMessage::Change(LagChange::Lag {
+            group: "g".to_string(),
+            topic_partition: TopicPartition::new("t".to_string(), 1),
+            lag: Lag::default(),
+        });
+        assert_eq!(lag.event_name(), "lag");
+        let json: serde_json::Value = serde_json::from_str(&lag.to_json()).unwrap();
+        assert_eq!(json["type"], "lag");
+        assert_eq!(json["group"], "g");
+        assert_eq!(json["topic"], "t");
+        assert_eq!(json["partition"], 1);
+        assert_eq!(json["offset_lag"], 0);
+
+        let lagged = StreamMessage::Lagged(Lagged {
+            skipped: 42,
+        });
+        assert_eq!(lagged.event_name(), "lagged");
+        assert_eq!(lagged.to_json(), r#"{"type":"lagged","skipped":42}"#);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::kafka_types::Member;
+use crate::labels_mapping::{Labels, LabelsMapping};
+use crate::lag_register::{GroupWithLag, LagAggregate};
+use crate::prometheus_metrics::{
+    OwnerLabels, LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_MEMBER_CLIENT_ID, LABEL_MEMBER_HOST,
+    LABEL_MEMBER_ID, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE, UNKNOWN_VAL,
+};
+
+const MEASUREMENT_PARTITION: &str = const_format::formatcp!("{NAMESPACE}_consumer_partition");
+const MEASUREMENT_GROUP_TOPIC: &str = const_format::formatcp!("{NAMESPACE}_consumer_group_topic");
+const MEASUREMENT_GROUP: &str = const_format::formatcp!("{NAMESPACE}_consumer_group");
+
+/// Render the lag of every Consumer Group in `lag_by_group` as InfluxDB line protocol, one line per point.
+///
+/// Per-partition points are timestamped with the commit time of the consumed offset, and partitions
+/// without a known lag are skipped. Aggregated points (per group-and-topic, and per group) are
+/// timestamped `now`. Timestamps are in nanoseconds, the default precision of the protocol.
+pub fn render(
+    lag_by_group: &HashMap<String, GroupWithLag>,
+    cluster_id: &str,
+    mapping: &LabelsMapping,
+    owner_labels: OwnerLabels,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let now_ns = nanos(now);
+
+    for (g, gwl) in lag_by_group.iter() {
+        for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+            let Some(lag) = lwo.lag.as_ref() else {
+                continue;
+            };
+
+            let mut tags = mapping.group_topic_labels(g, &tp.topic);
+            tags.insert(LABEL_CLUSTER_ID.into(), cluster_id.into());
+            tags.insert(LABEL_GROUP.into(), g.clone());
+            tags.insert(LABEL_TOPIC.into(), tp.topic.clone());
+            tags.insert(LABEL_PARTITION.into(), tp.partition.to_string());
+            insert_owner_tags(&mut tags, lwo.owner.as_ref(), owner_labels);
+
+            lines.push(line(
+                MEASUREMENT_PARTITION,
+                &tags,
+                &[
+                    ("offset", lag.offset),
+                    ("offset_lag", lag.offset_lag),
+                    ("time_lag_ms", lag.time_lag.num_milliseconds().max(0) as u64),
+                ],
+                nanos(lag.offset_timestamp),
+            ));
+        }
+
+        for (t, agg) in gwl.aggregate_by_topic().iter() {
+            let mut tags = mapping.group_topic_labels(g, t);
+            tags.insert(LABEL_CLUSTER_ID.into(), cluster_id.into());
+            tags.insert(LABEL_GROUP.into(), g.clone());
+            tags.insert(LABEL_TOPIC.into(), t.to_string());
+            lines.push(line(MEASUREMENT_GROUP_TOPIC, &tags, &aggregate_fields(agg), now_ns));
+        }
+
+        let mut tags = mapping.group_labels(g);
+        tags.insert(LABEL_CLUSTER_ID.into(), cluster_id.into());
+        tags.insert(LABEL_GROUP.into(), g.clone());
+        lines.push(line(MEASUREMENT_GROUP, &tags, &aggregate_fields(&gwl.aggregate()), now_ns));
+    }
+
+    lines
+}
+
+fn insert_owner_tags(tags: &mut Labels, owner: Option<&Member>, granularity: OwnerLabels) {
+    let (id, host, client_id) = owner
+        .map(|o| (o.id.as_str(), o.client_host.as_str(), o.client_id.as_str()))
+        .unwrap_or((UNKNOWN_VAL, UNKNOWN_VAL, UNKNOWN_VAL));
+
+    if granularity == OwnerLabels::Full {
+        tags.insert(LABEL_MEMBER_ID.into(), id.into());
+    }
+    if granularity != OwnerLabels::None {
+        tags.insert(LABEL_MEMBER_HOST.into(), host.into());
+        tags.insert(LABEL_MEMBER_CLIENT_ID.into(), client_id.into());
+    }
+}
+
+fn aggregate_fields(agg: &LagAggregate) -> [(&'static str, u64); 4] {
+    [
+        ("offset_lag_sum", agg.offset_lag_sum),
+        ("offset_lag_max", agg.offset_lag_max),
+        ("time_lag_ms_max", agg.time_lag_max.num_milliseconds().max(0) as u64),
+        ("partitions_lagging", agg.partitions_lagging as u64),
+    ]
+}
+
+/// A single line: tags are sorted by key (as [`Labels`] is), as InfluxDB recommends.
+fn line(measurement: &str, tags: &Labels, fields: &[(&str, u64)], timestamp_ns: i64) -> String {
+    let tags: String = tags
+        .iter()
+        .filter(|(_, v)| !v.is_empty())
+        .map(|(k, v)| format!(",{}={}", escape(k), escape(v)))
+        .collect();
+    let fields = fields.iter().map(|(k, v)| format!("{k}={v}i")).collect::<Vec<_>>().join(",");
+
+    format!("{measurement}{tags} {fields} {timestamp_ns}")
+}
+
+/// Escape commas, equal signs and spaces, as required in tag keys and values.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            ',' | '=' | ' ' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn nanos(dt: DateTime<Utc>) -> i64 {
+    dt.timestamp_nanos_opt().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, TimeZone};
+
+    use crate::kafka_types::TopicPartition;
+    use crate::lag_register::{Lag, LagWithOwner};
+
+    use super::*;
+
+    #[test]
+    fn render_lag() {
+        let committed_at = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let now = Utc.timestamp_millis_opt(1_700_000_060_000).unwrap();
+        let lag_by_group = HashMap::from([(
+            "g".to_string(),
+            GroupWithLag {
+                lag_by_topic_partition: HashMap::from([
+                    (
+                        TopicPartition::new("t".to_string(), 0),
+                        LagWithOwner {
+                            lag: Some(Lag {
+                                offset: 90,
+                                offset_timestamp: committed_at,
+                                offset_lag: 10,
+                                time_lag: Duration::milliseconds(500),
+                            }),
+                            ..Default::default()
+                        },
+                    ),
+                    (TopicPartition::new("t".to_string(), 1), LagWithOwner::default()),
+                ]),
+                ..Default::default()
+            },
+        )]);
+
+        let lines =
+            render(&lag_by_group, "c", &LabelsMapping::default(), OwnerLabels::HostClientId, now);
+        assert_eq!(
+            lines,
+            [
+                "kmtd_consumer_partition,cluster_id=c,group=g,member_client_id=UNKNOWN,member_host=UNKNOWN,partition=0,topic=t offset=90i,offset_lag=10i,time_lag_ms=500i 1700000000000000000",
+                "kmtd_consumer_group_topic,cluster_id=c,group=g,topic=t offset_lag_sum=10i,offset_lag_max=10i,time_lag_ms_max=500i,partitions_lagging=1i 1700000060000000000",
+                "kmtd_consumer_group,cluster_id=c,group=g offset_lag_sum=10i,offset_lag_max=10i,time_lag_ms_max=500i,partitions_lagging=1i 1700000060000000000",
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_line() {
+        let tags = Labels::from([
+            ("topic".to_string(), "a b,c=d".to_string()),
+            ("group".to_string(), "g".to_string()),
+            ("team".to_string(), String::new()),
+        ]);
+
+        assert_eq!(
+            line("kmtd_consumer_partition", &tags, &[("offset", 10), ("offset_lag", 2)], 1_000),
+            r"kmtd_consumer_partition,group=g,topic=a\ b\,c\=d offset=10i,offset_lag=2i 1000"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Lag in [InfluxDB line protocol](https://docs.influxdata.com/influxdb/v2/reference/syntax/line-protocol/).
+//!
+//! Served by the HTTP Service at `/metrics/influx`, and optionally pushed periodically to
+//! an InfluxDB write endpoint, for long-term retention of the lag history.
+
+// Inner modules
+mod line;
+
+// Imports
+use std::time::Duration;
+
+use chrono::Utc;
+use prometheus::{register_int_counter_with_registry, IntCounter};
+use reqwest::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    Client, Url,
+};
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::http::HttpServiceState;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const MET_SENT_NAME: &str = "influx_sent_requests_total";
+const MET_SENT_HELP: &str = "InfluxDB write requests sent successfully";
+const MET_FAILED_NAME: &str = "influx_failed_requests_total";
+const MET_FAILED_HELP: &str = "InfluxDB write requests that failed";
+
+/// Configuration of the InfluxDB push.
+#[derive(Debug, Clone)]
+pub struct InfluxConfig {
+    /// Full URL of the write endpoint, including the target database or bucket
+    /// (e.g. `http://influxdb:8086/api/v2/write?org=O&bucket=B`).
+    pub url: Url,
+
+    /// Sent as `Authorization: Token <TOKEN>`.
+    pub token: Option<String>,
+    pub interval: Duration,
+}
+
+/// Render the lag of all Consumer Groups as InfluxDB line protocol, one line per point.
+///
+/// Built from the same [`crate::lag_register::LagRegister`], labels mapping and owner labels
+/// granularity used for the Prometheus metrics.
+pub async fn render_lines(state: &HttpServiceState) -> Vec<String> {
+    let cluster_id = state.cs_reg.get_cluster_id().await;
+    let labels_mapping = state.labels_mapper.current();
+    let lag_by_group = state.lag_reg.lag_by_group.read().await;
+
+    line::render(&lag_by_group, &cluster_id, &labels_mapping, state.owner_labels, Utc::now())
+}
+
+/// Spawn the task that periodically writes the lag to the InfluxDB write endpoint.
+pub fn init(
+    config: InfluxConfig,
+    state: HttpServiceState,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    let register = |name: &str, help: &str| -> IntCounter {
+        register_int_counter_with_registry!(name, help, state.metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {name}"))
+    };
+    let sent = register(MET_SENT_NAME, MET_SENT_HELP);
+    let failed = register(MET_FAILED_NAME, MET_FAILED_HELP);
+
+    let client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("Failed to build InfluxDB HTTP client (fatal)");
+    debug!("Initialized, writing to '{}'", config.url);
+
+    tokio::spawn(async move {
+        let mut interval = interval(config.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = shutdown_token.cancelled() => break,
+            }
+
+            let lines = render_lines(&state).await;
+            if lines.is_empty() {
+                continue;
+            }
+
+            let mut req = client
+                .post(config.url.clone())
+                .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(lines.join("\n"));
+            if let Some(token) = config.token.as_ref() {
+                req = req.header(AUTHORIZATION, format!("Token {token}"));
+            }
+
+            tokio::select! {
+                res = req.send() => match res.and_then(|r| r.error_for_status()) {
+                    Ok(_) => {
+                        trace!("Wrote {} points to '{}'", lines.len(), config.url);
+                        sent.inc();
+                    },
+                    Err(e) => {
+                        warn!("Failed to write to '{}': {e}", config.url);
+                        failed.inc();
+                    },
+                },
+                _ = shutdown_token.cancelled() => break,
+            }
+        }
+
+        info!("Shutting down");
+    })
+}
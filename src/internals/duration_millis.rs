@@ -0,0 +1,14 @@
+//! (De)serialize a [`chrono::Duration`] as an amount of milliseconds.
+//!
+//! Use it via `#[serde(with = "crate::internals::duration_millis")]`.
+
+use chrono::Duration;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(duration.num_milliseconds())
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    i64::deserialize(deserializer).map(Duration::milliseconds)
+}
@@ -1,4 +1,5 @@
 mod awaitable;
+pub mod duration_millis;
 mod emitter;
 
 pub use awaitable::*;
@@ -0,0 +1,100 @@
+//! Produce the lag of Consumer Groups to a Kafka topic.
+//!
+//! This lets stream processors and data lakes consume lag data, without scraping metrics.
+//! Records are keyed by Consumer Group, and produced either periodically or on every lag update.
+
+// Inner modules
+mod record;
+mod sink;
+
+// Exports
+pub use record::LagRecord;
+pub use sink::{build_record, is_replayed, owner_of};
+
+// Imports
+use std::{sync::Arc, time::Duration};
+
+use clap::ValueEnum;
+use prometheus::{register_int_counter_with_registry, IntCounter, Registry};
+use rdkafka::ClientConfig;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::cluster_status::ClusterStatusRegister;
+use crate::lag_register::LagRegister;
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+use record::AVRO_SCHEMA;
+use sink::KafkaSink;
+
+const MET_PRODUCED_NAME: &str = "kafka_sink_produced_records_total";
+const MET_PRODUCED_HELP: &str = "Lag records produced to the Kafka sink topic";
+const MET_FAILED_NAME: &str = "kafka_sink_failed_records_total";
+const MET_FAILED_HELP: &str = "Lag records that failed to be produced to the Kafka sink topic";
+
+/// Encoding of the [`record::LagRecord`]s produced to the sink topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum SinkEncoding {
+    /// JSON object.
+    Json,
+
+    /// Avro binary encoding (the schema is logged at startup).
+    Avro,
+}
+
+/// When [`record::LagRecord`]s are produced to the sink topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum SinkMode {
+    /// Every interval, for each Topic Partition of each Consumer Group.
+    Periodic,
+
+    /// Every time a Consumer Group commits an offset, for that Topic Partition
+    /// (except commits replayed from `__consumer_offsets` at startup).
+    OnChange,
+}
+
+/// Configuration of the [`KafkaSink`].
+#[derive(Debug, Clone)]
+pub struct KafkaSinkConfig {
+    /// Configuration of the Producer, targeting the cluster to produce to.
+    pub client_config: ClientConfig,
+    pub topic: String,
+    pub encoding: SinkEncoding,
+    pub mode: SinkMode,
+
+    /// How often to produce records, in [`SinkMode::Periodic`].
+    pub interval: Duration,
+}
+
+/// Create a [`KafkaSink`] and spawn the task producing [`record::LagRecord`]s to it.
+pub fn init(
+    config: KafkaSinkConfig,
+    cs_reg: Arc<ClusterStatusRegister>,
+    po_reg: Arc<PartitionOffsetsRegister>,
+    lag_reg: Arc<LagRegister>,
+    shutdown_token: CancellationToken,
+    metrics: Arc<Registry>,
+) -> JoinHandle<()> {
+    let produced: IntCounter =
+        register_int_counter_with_registry!(MET_PRODUCED_NAME, MET_PRODUCED_HELP, metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {MET_PRODUCED_NAME}"));
+    let failed: IntCounter =
+        register_int_counter_with_registry!(MET_FAILED_NAME, MET_FAILED_HELP, metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {MET_FAILED_NAME}"));
+
+    if config.encoding == SinkEncoding::Avro {
+        info!("Producing lag records with Avro schema:\n{AVRO_SCHEMA}");
+    }
+    let sink = KafkaSink::new(
+        config.client_config,
+        config.topic.clone(),
+        config.encoding,
+        produced,
+        failed,
+    );
+    let join_handle =
+        sink.spawn(config.mode, config.interval, cs_reg, po_reg, lag_reg, shutdown_token);
+
+    debug!("Initialized, producing to topic '{}'", config.topic);
+    join_handle
+}
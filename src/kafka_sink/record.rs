@@ -0,0 +1,221 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::kafka_types::{Member, TopicPartition};
+use crate::lag_register::Lag;
+
+use super::SinkEncoding;
+
+/// [Avro](https://avro.apache.org/docs/1.11.1/specification/) schema of a [`LagRecord`],
+/// when produced with [`SinkEncoding::Avro`].
+pub const AVRO_SCHEMA: &str = r#"{
+  "type": "record",
+  "name": "LagRecord",
+  "namespace": "io.kafkesc.kommitted",
+  "fields": [
+    { "name": "cluster_id", "type": "string" },
+    { "name": "group", "type": "string" },
+    { "name": "topic", "type": "string" },
+    { "name": "partition", "type": "int" },
+    { "name": "offset", "type": "long" },
+    { "name": "offset_timestamp", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+    { "name": "offset_lag", "type": "long" },
+    { "name": "time_lag_ms", "type": "long" },
+    { "name": "owner", "type": [ "null", {
+      "type": "record",
+      "name": "Member",
+      "fields": [
+        { "name": "id", "type": "string" },
+        { "name": "client_id", "type": "string" },
+        { "name": "client_host", "type": "string" }
+      ]
+    } ] },
+    { "name": "earliest_offset", "type": [ "null", "long" ] },
+    { "name": "latest_offset", "type": [ "null", "long" ] },
+    { "name": "timestamp", "type": { "type": "long", "logicalType": "timestamp-millis" } }
+  ]
+}"#;
+
+/// Lag of a Consumer Group for a Topic Partition, as produced to the sink topic.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LagRecord {
+    pub cluster_id: String,
+    pub group: String,
+    pub topic: String,
+    pub partition: u32,
+
+    /// Offset committed by the Consumer Group.
+    pub offset: u64,
+    pub offset_timestamp: DateTime<Utc>,
+    pub offset_lag: u64,
+    pub time_lag_ms: i64,
+
+    /// Member of the Consumer Group owning the Topic Partition, if any.
+    pub owner: Option<Member>,
+
+    /// Earliest available offset (low watermark) of the Topic Partition, if known.
+    pub earliest_offset: Option<u64>,
+
+    /// Latest available offset (high watermark) of the Topic Partition, if known.
+    pub latest_offset: Option<u64>,
+
+    /// When the record was created.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl LagRecord {
+    pub fn new(
+        cluster_id: &str,
+        group: &str,
+        tp: &TopicPartition,
+        lag: &Lag,
+        owner: Option<Member>,
+        watermarks: (Option<u64>, Option<u64>),
+    ) -> Self {
+        LagRecord {
+            cluster_id: cluster_id.to_string(),
+            group: group.to_string(),
+            topic: tp.topic.clone(),
+            partition: tp.partition,
+            offset: lag.offset,
+            offset_timestamp: lag.offset_timestamp,
+            offset_lag: lag.offset_lag,
+            time_lag_ms: lag.time_lag.num_milliseconds(),
+            owner,
+            earliest_offset: watermarks.0,
+            latest_offset: watermarks.1,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Encode the record as the payload of a Kafka message.
+    pub fn encode(&self, encoding: SinkEncoding) -> Vec<u8> {
+        match encoding {
+            SinkEncoding::Json => {
+                serde_json::to_vec(self).expect("Serializing a LagRecord can't fail")
+            },
+            SinkEncoding::Avro => self.to_avro(),
+        }
+    }
+
+    /// Encode the record in Avro binary encoding, following [`AVRO_SCHEMA`].
+    fn to_avro(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(128);
+        avro_string(&mut buf, &self.cluster_id);
+        avro_string(&mut buf, &self.group);
+        avro_string(&mut buf, &self.topic);
+        avro_long(&mut buf, self.partition as i64);
+        avro_long(&mut buf, self.offset as i64);
+        avro_long(&mut buf, self.offset_timestamp.timestamp_millis());
+        avro_long(&mut buf, self.offset_lag as i64);
+        avro_long(&mut buf, self.time_lag_ms);
+        match self.owner.as_ref() {
+            None => avro_long(&mut buf, 0),
+            Some(m) => {
+                avro_long(&mut buf, 1);
+                avro_string(&mut buf, &m.id);
+                avro_string(&mut buf, &m.client_id);
+                avro_string(&mut buf, &m.client_host);
+            },
+        }
+        avro_optional_long(&mut buf, self.earliest_offset);
+        avro_optional_long(&mut buf, self.latest_offset);
+        avro_long(&mut buf, self.timestamp.timestamp_millis());
+        buf
+    }
+}
+
+/// Avro `int` and `long` are both encoded as zig-zag variable-length integers.
+fn avro_long(buf: &mut Vec<u8>, v: i64) {
+    let mut z = ((v << 1) ^ (v >> 63)) as u64;
+    while z >= 0x80 {
+        buf.push((z as u8) | 0x80);
+        z >>= 7;
+    }
+    buf.push(z as u8);
+}
+
+/// Avro `string` is its length (as `long`), followed by its UTF-8 bytes.
+fn avro_string(buf: &mut Vec<u8>, s: &str) {
+    avro_long(buf, s.len() as i64);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Avro `["null", "long"]` union is the index of the branch (as `long`), followed by its value.
+fn avro_optional_long(buf: &mut Vec<u8>, v: Option<u64>) {
+    match v {
+        None => avro_long(buf, 0),
+        Some(v) => {
+            avro_long(buf, 1);
+            avro_long(buf, v as i64);
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+
+    fn example_record() -> LagRecord {
+        LagRecord {
+            cluster_id: "c".to_string(),
+            group: "g".to_string(),
+            topic: "t".to_string(),
+            partition: 1,
+            offset: 100,
+            offset_timestamp: Utc.timestamp_millis_opt(1).unwrap(),
+            offset_lag: 2,
+            time_lag_ms: Duration::milliseconds(-1).num_milliseconds(),
+            owner: None,
+            earliest_offset: None,
+            latest_offset: Some(64),
+            timestamp: Utc.timestamp_millis_opt(0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn avro_encoding() {
+        let mut buf = vec![];
+        for v in [0, -1, 1, -64, 64, i64::MAX] {
+            avro_long(&mut buf, v);
+        }
+        assert_eq!(
+            buf,
+            [
+                0x00, 0x01, 0x02, 0x7f, 0x80, 0x01, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0x01
+            ]
+        );
+
+        assert_eq!(
+            example_record().encode(SinkEncoding::Avro),
+            [
+                0x02, b'c', // cluster_id
+                0x02, b'g', // group
+                0x02, b't', // topic
+                0x02, // partition
+                0xc8, 0x01, // offset
+                0x02, // offset_timestamp
+                0x04, // offset_lag
+                0x01, // time_lag_ms
+                0x00, // owner
+                0x00, // earliest_offset
+                0x02, 0x80, 0x01, // latest_offset
+                0x00, // timestamp
+            ]
+        );
+    }
+
+    #[test]
+    fn json_encoding() {
+        let json: serde_json::Value =
+            serde_json::from_slice(&example_record().encode(SinkEncoding::Json)).unwrap();
+
+        assert_eq!(json["group"], "g");
+        assert_eq!(json["offset_lag"], 2);
+        assert_eq!(json["owner"], serde_json::Value::Null);
+        assert_eq!(json["latest_offset"], 64);
+    }
+}
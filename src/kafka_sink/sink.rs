@@ -0,0 +1,314 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use prometheus::IntCounter;
+use rdkafka::{
+    producer::{DeliveryFuture, FutureProducer, FutureRecord},
+    ClientConfig,
+};
+use tokio::{
+    sync::broadcast::error::RecvError,
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::cluster_status::ClusterStatusRegister;
+use crate::kafka_types::{Member, TopicPartition};
+use crate::lag_register::{Lag, LagChange, LagRegister};
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+use super::record::LagRecord;
+use super::{SinkEncoding, SinkMode};
+
+/// How long librdkafka can take to deliver a record, before it's considered failed.
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Lag updates of commits older than this are history being replayed
+/// (e.g. from `__consumer_offsets` at startup).
+const MAX_COMMIT_AGE: chrono::Duration = chrono::Duration::minutes(1);
+
+/// Produces [`LagRecord`]s to a Kafka topic, keyed by Consumer Group.
+pub struct KafkaSink {
+    producer: FutureProducer,
+    topic: String,
+    encoding: SinkEncoding,
+    produced: IntCounter,
+    failed: IntCounter,
+}
+
+impl KafkaSink {
+    pub fn new(
+        mut client_config: ClientConfig,
+        topic: String,
+        encoding: SinkEncoding,
+        produced: IntCounter,
+        failed: IntCounter,
+    ) -> Self {
+        let producer = client_config
+            .set("message.timeout.ms", MESSAGE_TIMEOUT.as_millis().to_string())
+            .create()
+            .expect("Failed to allocate Kafka Sink Producer");
+
+        KafkaSink {
+            producer,
+            topic,
+            encoding,
+            produced,
+            failed,
+        }
+    }
+
+    /// Produce the `records`, waiting for all of them to be delivered (or to fail).
+    ///
+    /// Returns how many were delivered.
+    pub async fn produce(&self, records: &[LagRecord]) -> usize {
+        track(self.enqueue(records), &self.produced, &self.failed).await
+    }
+
+    /// Enqueue the `records` to be produced, without waiting for their delivery.
+    ///
+    /// Returns the deliveries of those enqueued, to [`track`].
+    fn enqueue(&self, records: &[LagRecord]) -> Vec<DeliveryFuture> {
+        let mut deliveries = Vec::with_capacity(records.len());
+        for r in records {
+            let payload = r.encode(self.encoding);
+            let fr = FutureRecord::to(&self.topic).key(&r.group).payload(&payload);
+            match self.producer.send_result(fr) {
+                Ok(d) => deliveries.push(d),
+                Err((e, _)) => {
+                    warn!("Failed to enqueue lag record of group '{}': {e}", r.group);
+                    self.failed.inc();
+                },
+            }
+        }
+        deliveries
+    }
+
+    /// Spawn the task producing [`LagRecord`]s, according to the [`SinkMode`].
+    pub fn spawn(
+        self,
+        mode: SinkMode,
+        period: Duration,
+        cs_reg: Arc<ClusterStatusRegister>,
+        po_reg: Arc<PartitionOffsetsRegister>,
+        lag_reg: Arc<LagRegister>,
+        shutdown_token: CancellationToken,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            match mode {
+                SinkMode::Periodic => {
+                    let mut interval = interval(period);
+                    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+                    loop {
+                        tokio::select! {
+                            _ = interval.tick() => {},
+                            _ = shutdown_token.cancelled() => break,
+                        }
+
+                        let records = all_records(&cs_reg, &po_reg, &lag_reg).await;
+                        let delivered = self.produce(&records).await;
+                        debug!("Produced {delivered}/{} lag records", records.len());
+                    }
+                },
+                SinkMode::OnChange => {
+                    let mut changes_rx = lag_reg.subscribe();
+
+                    loop {
+                        let change = tokio::select! {
+                            res = changes_rx.recv() => match res {
+                                Ok(change) => change,
+                                Err(RecvError::Lagged(skipped)) => {
+                                    warn!("Fell behind lag updates: {skipped} not produced");
+                                    continue;
+                                },
+                                Err(RecvError::Closed) => break,
+                            },
+                            _ = shutdown_token.cancelled() => break,
+                        };
+
+                        if let LagChange::Lag {
+                            group,
+                            topic_partition,
+                            lag,
+                        } = change
+                        {
+                            if is_replayed(&lag, Utc::now()) {
+                                continue;
+                            }
+
+                            let owner = owner_of(&lag_reg, &group, &topic_partition).await;
+                            let record = build_record(
+                                &cs_reg,
+                                &po_reg,
+                                &group,
+                                &topic_partition,
+                                &lag,
+                                owner,
+                            )
+                            .await;
+
+                            // Track the delivery in the background, to not hold up the lag updates
+                            let deliveries = self.enqueue(&[record]);
+                            let (produced, failed) = (self.produced.clone(), self.failed.clone());
+                            tokio::spawn(
+                                async move { track(deliveries, &produced, &failed).await },
+                            );
+                        }
+                    }
+                },
+            }
+
+            info!("Shutting down");
+        })
+    }
+}
+
+/// Whether the [`Lag`] update is of a commit being replayed, rather than just made.
+pub fn is_replayed(lag: &Lag, now: DateTime<Utc>) -> bool {
+    now - lag.offset_timestamp > MAX_COMMIT_AGE
+}
+
+/// Wait for the `deliveries` to complete, counting those `produced` and `failed`.
+///
+/// Returns how many were delivered.
+async fn track(
+    deliveries: Vec<DeliveryFuture>,
+    produced: &IntCounter,
+    failed: &IntCounter,
+) -> usize {
+    let mut delivered = 0;
+    for d in deliveries {
+        match d.await {
+            Ok(Ok(_)) => {
+                delivered += 1;
+                produced.inc();
+            },
+            Ok(Err((e, _))) => {
+                warn!("Failed to deliver lag record: {e}");
+                failed.inc();
+            },
+            Err(_) => failed.inc(),
+        }
+    }
+    delivered
+}
+
+/// A [`LagRecord`] for each Topic Partition, of each Consumer Group, with a known [`Lag`].
+async fn all_records(
+    cs_reg: &ClusterStatusRegister,
+    po_reg: &PartitionOffsetsRegister,
+    lag_reg: &LagRegister,
+) -> Vec<LagRecord> {
+    // Collect lag first, to not hold the lock while looking up the watermarks
+    let lags: Vec<(String, TopicPartition, Lag, Option<Member>)> = lag_reg
+        .lag_by_group
+        .read()
+        .await
+        .iter()
+        .flat_map(|(g, gwl)| {
+            gwl.lag_by_topic_partition.iter().filter_map(move |(tp, lwo)| {
+                lwo.lag.as_ref().map(|l| (g.clone(), tp.clone(), l.clone(), lwo.owner.clone()))
+            })
+        })
+        .collect();
+
+    let mut records = Vec::with_capacity(lags.len());
+    for (group, tp, lag, owner) in lags {
+        records.push(build_record(cs_reg, po_reg, &group, &tp, &lag, owner).await);
+    }
+    records
+}
+
+/// Build the [`LagRecord`] of a Topic Partition consumed by a Consumer Group, with its watermarks.
+pub async fn build_record(
+    cs_reg: &ClusterStatusRegister,
+    po_reg: &PartitionOffsetsRegister,
+    group: &str,
+    tp: &TopicPartition,
+    lag: &Lag,
+    owner: Option<Member>,
+) -> LagRecord {
+    let watermarks = (
+        po_reg.get_earliest_available_offset(tp).await.ok(),
+        po_reg.get_latest_available_offset(tp).await.ok(),
+    );
+    LagRecord::new(&cs_reg.get_cluster_id().await, group, tp, lag, owner, watermarks)
+}
+
+/// Current owner of a Topic Partition consumed by a Consumer Group, if any.
+pub async fn owner_of(lag_reg: &LagRegister, group: &str, tp: &TopicPartition) -> Option<Member> {
+    lag_reg
+        .lag_by_group
+        .read()
+        .await
+        .get(group)
+        .and_then(|gwl| gwl.lag_by_topic_partition.get(tp))
+        .and_then(|lwo| lwo.owner.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+    use prometheus::IntCounter;
+    use rdkafka::{
+        consumer::{BaseConsumer, Consumer},
+        mocking::MockCluster,
+        ClientConfig, Message, Offset, TopicPartitionList,
+    };
+
+    use super::*;
+
+    const TOPIC: &str = "kommitted-lag";
+
+    #[tokio::test]
+    async fn produce_to_mock_cluster() {
+        let cluster = MockCluster::new(1).unwrap();
+        cluster.create_topic(TOPIC, 1, 1).unwrap();
+
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", cluster.bootstrap_servers());
+
+        let produced = IntCounter::new("produced", "produced").unwrap();
+        let failed = IntCounter::new("failed", "failed").unwrap();
+        let sink = KafkaSink::new(
+            config.clone(),
+            TOPIC.to_string(),
+            SinkEncoding::Json,
+            produced.clone(),
+            failed.clone(),
+        );
+
+        let lag = Lag {
+            offset: 10,
+            offset_timestamp: Utc::now(),
+            offset_lag: 5,
+            time_lag: Duration::seconds(2),
+        };
+        let records: Vec<LagRecord> = ["group-a", "group-b"]
+            .iter()
+            .map(|g| {
+                let tp = TopicPartition::new("topic".to_string(), 0);
+                LagRecord::new("cluster", g, &tp, &lag, None, (Some(0), Some(15)))
+            })
+            .collect();
+        assert_eq!(sink.produce(&records).await, 2);
+        assert_eq!((produced.get(), failed.get()), (2, 0));
+
+        let consumer: BaseConsumer = config.set("group.id", "test").create().unwrap();
+        let mut tpl = TopicPartitionList::new();
+        tpl.add_partition_offset(TOPIC, 0, Offset::Beginning).unwrap();
+        consumer.assign(&tpl).unwrap();
+
+        for expected_group in ["group-a", "group-b"] {
+            let msg = consumer.poll(std::time::Duration::from_secs(10)).unwrap().unwrap();
+            assert_eq!(msg.key(), Some(expected_group.as_bytes()));
+
+            let json: serde_json::Value = serde_json::from_slice(msg.payload().unwrap()).unwrap();
+            assert_eq!(json["group"], expected_group);
+            assert_eq!(json["time_lag_ms"], 2000);
+            assert_eq!(json["latest_offset"], 15);
+        }
+    }
+}
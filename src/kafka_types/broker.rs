@@ -1,10 +1,11 @@
 use rdkafka::metadata::MetadataBroker;
+use serde::{Deserialize, Serialize};
 
 /// A Brokers that is part of a Kafka cluster.
 ///
 /// It is identified by a unique identifier for the given Cluster,
 /// and the host and port to connect to it.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Serialize, Deserialize)]
 pub struct Broker {
     /// Broker unique identifier, as configured at the Kafka Cluster level.
     /// Note that uniqueness is "expected" by Brokers,
@@ -1,9 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
+use serde::{Deserialize, Serialize};
+
 use crate::kafka_types::TopicPartition;
 
 /// Consumer Group Member
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Serialize, Deserialize)]
 pub struct Member {
     /// Identifier
     pub id: String,
@@ -26,7 +28,7 @@ pub struct MemberWithAssignment {
 }
 
 /// Consumer Group
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Group {
     /// Group name
     pub name: String,
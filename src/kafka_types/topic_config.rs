@@ -0,0 +1,52 @@
+use chrono::Duration;
+use rdkafka::admin::ConfigResource;
+use serde::{Deserialize, Serialize};
+
+const CONFIG_RETENTION_MS: &str = "retention.ms";
+const CONFIG_RETENTION_BYTES: &str = "retention.bytes";
+const CONFIG_CLEANUP_POLICY: &str = "cleanup.policy";
+
+const CLEANUP_POLICY_COMPACT: &str = "compact";
+
+/// Configuration of a Topic that determines how long its messages are retained.
+///
+/// Values are `None` when unknown (e.g. the configuration could not be described).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Serialize, Deserialize)]
+pub struct TopicConfig {
+    /// `retention.ms`: `-1` means unlimited.
+    pub retention_ms: Option<i64>,
+
+    /// `retention.bytes` (per partition): `-1` means unlimited.
+    pub retention_bytes: Option<i64>,
+
+    /// `cleanup.policy`: `delete`, `compact` or both (comma separated).
+    pub cleanup_policy: Option<String>,
+}
+
+impl TopicConfig {
+    /// Whether the Topic is only compacted, in which case its messages are not deleted by retention
+    /// in any predictable way. Topics that are also `delete` are still subject to retention.
+    pub fn is_compact_only(&self) -> bool {
+        self.cleanup_policy.as_ref().is_some_and(|cp| {
+            let mut policies = cp.split(',').map(str::trim).filter(|p| !p.is_empty()).peekable();
+            policies.peek().is_some() && policies.all(|p| p == CLEANUP_POLICY_COMPACT)
+        })
+    }
+
+    /// Time after which messages are deleted, if limited.
+    pub fn retention_time(&self) -> Option<Duration> {
+        self.retention_ms.filter(|ms| *ms >= 0).map(Duration::milliseconds)
+    }
+}
+
+impl From<&ConfigResource> for TopicConfig {
+    fn from(cr: &ConfigResource) -> Self {
+        let value = |name: &str| cr.get(name).and_then(|e| e.value.clone());
+
+        TopicConfig {
+            retention_ms: value(CONFIG_RETENTION_MS).and_then(|v| v.parse().ok()),
+            retention_bytes: value(CONFIG_RETENTION_BYTES).and_then(|v| v.parse().ok()),
+            cleanup_policy: value(CONFIG_CLEANUP_POLICY),
+        }
+    }
+}
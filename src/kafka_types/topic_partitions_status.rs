@@ -1,13 +1,20 @@
 use rdkafka::metadata::{MetadataPartition, MetadataTopic};
+use serde::{Deserialize, Serialize};
+
+use super::TopicConfig;
 
 /// For a given Topic, it describes its status as reported by the Kafka cluster.
 ///
 /// In details, it describes where each partition is, which broker leads each partition,
 /// and which follower broker is in sync with each partition.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Serialize, Deserialize)]
 pub struct TopicPartitionsStatus {
     pub name: String,
     pub partitions: Vec<PartitionStatus>,
+
+    /// Retention configuration of the Topic.
+    #[serde(default)]
+    pub config: TopicConfig,
 }
 
 impl From<&MetadataTopic> for TopicPartitionsStatus {
@@ -15,6 +22,7 @@ impl From<&MetadataTopic> for TopicPartitionsStatus {
         TopicPartitionsStatus {
             name: t.name().to_owned(),
             partitions: t.partitions().iter().map(PartitionStatus::from).collect(),
+            config: TopicConfig::default(),
         }
     }
 }
@@ -22,7 +30,7 @@ impl From<&MetadataTopic> for TopicPartitionsStatus {
 /// For a given Partition, it describes its status as reported by the Kafka cluster.
 ///
 /// The details make sense only in the context of the containing Topic.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Serialize, Deserialize)]
 pub struct PartitionStatus {
     pub id: u32,
     pub leader_broker: u32,
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::lag_register::LagAggregate;
+
+const META_GROUP: &str = "group";
+const META_TOPIC: &str = "topic";
+const META_METRIC: &str = "metric";
+const META_LAG_THRESHOLD: &str = "lagThreshold";
+const META_ACTIVATION_LAG_THRESHOLD: &str = "activationLagThreshold";
+
+const METRIC_OFFSET_LAG: &str = "offsetLag";
+const METRIC_TIME_LAG: &str = "timeLag";
+
+/// Default `lagThreshold`, when the metric is [`ScalerMetric::OffsetLag`] (same as KEDA's Kafka scaler).
+const DEFAULT_OFFSET_LAG_THRESHOLD: f64 = 10.0;
+
+/// Default `lagThreshold`, when the metric is [`ScalerMetric::TimeLag`] (milliseconds).
+const DEFAULT_TIME_LAG_THRESHOLD: f64 = 60_000.0;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ScalerMetadataError {
+    #[error("Missing '{0}' in scaler metadata")]
+    Missing(&'static str),
+
+    #[error("Invalid '{0}' in scaler metadata: {1}")]
+    Invalid(&'static str, String),
+}
+
+/// Lag metric that drives the scaling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalerMetric {
+    /// Sum of the offset lag, across the consumed Topic Partitions.
+    OffsetLag,
+
+    /// Maximum time lag (milliseconds), across the consumed Topic Partitions.
+    TimeLag,
+}
+
+impl ScalerMetric {
+    /// Value of this metric for the given [`LagAggregate`].
+    pub fn value(&self, agg: &LagAggregate) -> f64 {
+        match self {
+            ScalerMetric::OffsetLag => agg.offset_lag_sum as f64,
+            ScalerMetric::TimeLag => agg.time_lag_max.num_milliseconds() as f64,
+        }
+    }
+}
+
+/// Configuration of the scaler, as set in the `metadata` of the trigger of a KEDA `ScaledObject`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalerMetadata {
+    /// Consumer Group to scale.
+    pub group: String,
+
+    /// Topic to consider: if not set, all the Topics consumed by the Consumer Group.
+    pub topic: Option<String>,
+
+    pub metric: ScalerMetric,
+
+    /// Target value of the metric, for each replica.
+    pub lag_threshold: f64,
+
+    /// Value of the metric above which the scaler is active.
+    pub activation_lag_threshold: f64,
+}
+
+impl ScalerMetadata {
+    /// Parse the `scalerMetadata` of a `ScaledObjectRef`.
+    pub fn parse(meta: &HashMap<String, String>) -> Result<Self, ScalerMetadataError> {
+        let group = meta.get(META_GROUP).ok_or(ScalerMetadataError::Missing(META_GROUP))?.clone();
+
+        let metric = match meta.get(META_METRIC).map(String::as_str) {
+            None | Some(METRIC_OFFSET_LAG) => ScalerMetric::OffsetLag,
+            Some(METRIC_TIME_LAG) => ScalerMetric::TimeLag,
+            Some(m) => return Err(ScalerMetadataError::Invalid(META_METRIC, m.to_string())),
+        };
+
+        let lag_threshold = match parse_f64(meta, META_LAG_THRESHOLD)? {
+            Some(lt) if lt <= 0.0 => {
+                return Err(ScalerMetadataError::Invalid(META_LAG_THRESHOLD, lt.to_string()))
+            },
+            Some(lt) => lt,
+            None => match metric {
+                ScalerMetric::OffsetLag => DEFAULT_OFFSET_LAG_THRESHOLD,
+                ScalerMetric::TimeLag => DEFAULT_TIME_LAG_THRESHOLD,
+            },
+        };
+
+        Ok(ScalerMetadata {
+            group,
+            topic: meta.get(META_TOPIC).cloned(),
+            metric,
+            lag_threshold,
+            activation_lag_threshold: parse_f64(meta, META_ACTIVATION_LAG_THRESHOLD)?
+                .unwrap_or(0.0),
+        })
+    }
+
+    /// Name of the metric, as reported to KEDA.
+    pub fn metric_name(&self) -> String {
+        let metric = match self.metric {
+            ScalerMetric::OffsetLag => "offset-lag",
+            ScalerMetric::TimeLag => "time-lag",
+        };
+
+        match self.topic.as_ref() {
+            Some(t) => format!("kommitted-{}-{t}-{metric}", self.group),
+            None => format!("kommitted-{}-{metric}", self.group),
+        }
+    }
+}
+
+fn parse_f64(
+    meta: &HashMap<String, String>,
+    key: &'static str,
+) -> Result<Option<f64>, ScalerMetadataError> {
+    meta.get(key)
+        .map(|v| v.parse::<f64>().map_err(|e| ScalerMetadataError::Invalid(key, e.to_string())))
+        .transpose()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{ScalerMetadata, ScalerMetadataError, ScalerMetric};
+
+    fn meta(kvs: &[(&str, &str)]) -> HashMap<String, String> {
+        kvs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let sm = ScalerMetadata::parse(&meta(&[("group", "g")])).unwrap();
+
+        assert_eq!(sm.group, "g");
+        assert_eq!(sm.topic, None);
+        assert_eq!(sm.metric, ScalerMetric::OffsetLag);
+        assert_eq!(sm.lag_threshold, 10.0);
+        assert_eq!(sm.activation_lag_threshold, 0.0);
+        assert_eq!(sm.metric_name(), "kommitted-g-offset-lag");
+    }
+
+    #[test]
+    fn parse_time_lag() {
+        let sm = ScalerMetadata::parse(&meta(&[
+            ("group", "g"),
+            ("topic", "t"),
+            ("metric", "timeLag"),
+            ("activationLagThreshold", "1000"),
+        ]))
+        .unwrap();
+
+        assert_eq!(sm.metric, ScalerMetric::TimeLag);
+        assert_eq!(sm.lag_threshold, 60_000.0);
+        assert_eq!(sm.activation_lag_threshold, 1000.0);
+        assert_eq!(sm.metric_name(), "kommitted-g-t-time-lag");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(ScalerMetadata::parse(&meta(&[])), Err(ScalerMetadataError::Missing("group")));
+        assert!(matches!(
+            ScalerMetadata::parse(&meta(&[("group", "g"), ("metric", "foo")])),
+            Err(ScalerMetadataError::Invalid("metric", _))
+        ));
+        assert!(matches!(
+            ScalerMetadata::parse(&meta(&[("group", "g"), ("lagThreshold", "0")])),
+            Err(ScalerMetadataError::Invalid("lagThreshold", _))
+        ));
+        assert!(matches!(
+            ScalerMetadata::parse(&meta(&[("group", "g"), ("lagThreshold", "x")])),
+            Err(ScalerMetadataError::Invalid("lagThreshold", _))
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+//! [KEDA](https://keda.sh/) External Scaler, to scale consumers on Kubernetes based on their lag.
+//!
+//! Implements the [`ExternalScaler` gRPC protocol](https://keda.sh/docs/latest/concepts/external-scalers/),
+//! answering from the [`LagRegister`] instead of having KEDA query the Kafka cluster.
+
+// Inner modules
+mod metadata;
+mod scaler;
+
+#[allow(clippy::all)]
+mod externalscaler {
+    tonic::include_proto!("externalscaler");
+}
+
+// Imports
+use std::{net::SocketAddr, sync::Arc};
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::Server;
+
+use crate::lag_register::LagRegister;
+
+use externalscaler::external_scaler_server::ExternalScalerServer;
+use scaler::KedaScaler;
+
+/// Spawn the gRPC server of the KEDA External Scaler, listening on `listen_on`.
+pub fn init(
+    listen_on: SocketAddr,
+    lag_reg: Arc<LagRegister>,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    let scaler = KedaScaler::new(lag_reg, shutdown_token.clone());
+
+    let join_handle = tokio::spawn(async move {
+        info!("KEDA External Scaler listening on {listen_on}");
+        let res = Server::builder()
+            .add_service(ExternalScalerServer::new(scaler))
+            .serve_with_shutdown(listen_on, shutdown_token.cancelled_owned())
+            .await;
+
+        if let Err(e) = res {
+            error!("KEDA External Scaler failed: {e}");
+        }
+    });
+
+    debug!("Initialized");
+    join_handle
+}
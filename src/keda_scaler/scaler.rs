@@ -0,0 +1,163 @@
+use std::{pin::Pin, sync::Arc};
+
+use tokio::{
+    sync::mpsc,
+    time::{interval, Duration, MissedTickBehavior},
+};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tokio_util::sync::CancellationToken;
+use tonic::{Request, Response, Status};
+
+use super::externalscaler::{
+    external_scaler_server::ExternalScaler, GetMetricSpecResponse, GetMetricsRequest,
+    GetMetricsResponse, IsActiveResponse, MetricSpec, MetricValue, ScaledObjectRef,
+};
+use super::metadata::ScalerMetadata;
+
+use crate::lag_register::LagRegister;
+
+/// How often the activity of a scaler is checked, while streaming it to KEDA.
+const STREAM_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Size of the channel used to stream activity to KEDA.
+const STREAM_CHANNEL_SIZE: usize = 1;
+
+/// Implementation of the KEDA `ExternalScaler` gRPC service, answering from the [`LagRegister`].
+pub struct KedaScaler {
+    lag_reg: Arc<LagRegister>,
+    shutdown_token: CancellationToken,
+}
+
+impl KedaScaler {
+    pub fn new(lag_reg: Arc<LagRegister>, shutdown_token: CancellationToken) -> Self {
+        Self {
+            lag_reg,
+            shutdown_token,
+        }
+    }
+}
+
+/// Parse the [`ScalerMetadata`] of a [`ScaledObjectRef`].
+#[allow(clippy::result_large_err)] //< `Status` is what gRPC handlers return anyway
+fn parse_metadata(sor: &ScaledObjectRef) -> Result<ScalerMetadata, Status> {
+    ScalerMetadata::parse(&sor.scaler_metadata).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+/// Measure the current value of the metric described by the [`ScalerMetadata`].
+async fn measure(lag_reg: &LagRegister, sm: &ScalerMetadata) -> Result<f64, Status> {
+    let lag_by_group = lag_reg.lag_by_group.read().await;
+    let gwl = lag_by_group
+        .get(&sm.group)
+        .ok_or_else(|| Status::not_found(format!("Unknown group '{}'", sm.group)))?;
+
+    let agg = match sm.topic.as_ref() {
+        None => gwl.aggregate(),
+        Some(t) => gwl.aggregate_by_topic().remove(t.as_str()).ok_or_else(|| {
+            Status::not_found(format!("Group '{}' does not consume topic '{t}'", sm.group))
+        })?,
+    };
+
+    Ok(sm.metric.value(&agg))
+}
+
+async fn is_active(lag_reg: &LagRegister, sm: &ScalerMetadata) -> Result<bool, Status> {
+    Ok(measure(lag_reg, sm).await? > sm.activation_lag_threshold)
+}
+
+#[tonic::async_trait]
+impl ExternalScaler for KedaScaler {
+    async fn is_active(
+        &self,
+        request: Request<ScaledObjectRef>,
+    ) -> Result<Response<IsActiveResponse>, Status> {
+        let sm = parse_metadata(request.get_ref())?;
+
+        Ok(Response::new(IsActiveResponse {
+            result: is_active(&self.lag_reg, &sm).await?,
+        }))
+    }
+
+    type StreamIsActiveStream =
+        Pin<Box<dyn Stream<Item = Result<IsActiveResponse, Status>> + Send + 'static>>;
+
+    async fn stream_is_active(
+        &self,
+        request: Request<ScaledObjectRef>,
+    ) -> Result<Response<Self::StreamIsActiveStream>, Status> {
+        let sm = parse_metadata(request.get_ref())?;
+        let (tx, rx) = mpsc::channel(STREAM_CHANNEL_SIZE);
+
+        // Push the activity to KEDA every time it changes, until KEDA disconnects
+        let lag_reg = self.lag_reg.clone();
+        let shutdown_token = self.shutdown_token.clone();
+        tokio::spawn(async move {
+            let mut interval = interval(STREAM_CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            let mut last_active = None;
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        let res = is_active(&lag_reg, &sm).await;
+                        let active = res.as_ref().ok().copied();
+                        if res.is_ok() && active == last_active {
+                            continue;
+                        }
+                        last_active = active;
+
+                        let res = res.map(|result| IsActiveResponse { result });
+                        if tx.send(res).await.is_err() {
+                            debug!("Stream of activity of group '{}' closed", sm.group);
+                            break;
+                        }
+                    },
+                    _ = tx.closed() => {
+                        debug!("Stream of activity of group '{}' closed", sm.group);
+                        break;
+                    },
+                    _ = shutdown_token.cancelled() => {
+                        break;
+                    },
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn get_metric_spec(
+        &self,
+        request: Request<ScaledObjectRef>,
+    ) -> Result<Response<GetMetricSpecResponse>, Status> {
+        let sm = parse_metadata(request.get_ref())?;
+
+        Ok(Response::new(GetMetricSpecResponse {
+            metric_specs: vec![MetricSpec {
+                metric_name: sm.metric_name(),
+                target_size: sm.lag_threshold.ceil() as i64,
+                target_size_float: sm.lag_threshold,
+            }],
+        }))
+    }
+
+    async fn get_metrics(
+        &self,
+        request: Request<GetMetricsRequest>,
+    ) -> Result<Response<GetMetricsResponse>, Status> {
+        let sor = request
+            .get_ref()
+            .scaled_object_ref
+            .as_ref()
+            .ok_or_else(|| Status::invalid_argument("Missing 'scaledObjectRef'"))?;
+        let sm = parse_metadata(sor)?;
+        let value = measure(&self.lag_reg, &sm).await?;
+
+        Ok(Response::new(GetMetricsResponse {
+            metric_values: vec![MetricValue {
+                metric_name: sm.metric_name(),
+                metric_value: value.round() as i64,
+                metric_value_float: value,
+            }],
+        }))
+    }
+}
@@ -6,7 +6,13 @@ use rdkafka::{
     error::KafkaResult,
     ClientConfig, ClientContext, Message, Offset, TopicPartitionList,
 };
-use tokio::{sync::mpsc, task::JoinHandle, time::Duration};
+use std::collections::HashMap;
+
+use tokio::{
+    sync::{mpsc, watch},
+    task::{block_in_place, JoinHandle},
+    time::{interval, Duration, Instant},
+};
 use tokio_util::sync::CancellationToken;
 
 use crate::constants::{KOMMITTED_CONSUMER_OFFSETS_CONSUMER, KONSUMER_OFFSETS_DATA_TOPIC};
@@ -14,6 +20,15 @@ use crate::internals::Emitter;
 
 const CHANNEL_SIZE: usize = 10_000;
 
+/// How often the position of the partitions still being replayed is checked.
+const CATCH_UP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// After this long without any record, the partitions still being replayed are considered caught up.
+///
+/// The records right before a high watermark may never be delivered: they can be transaction
+/// control markers, or gaps left by compaction.
+const CATCH_UP_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Emits [`KonsumerOffsetsData`] via a provided [`mpsc::channel`].
 ///
 /// It wraps a Kafka Client, consumes the `__consumer_offsets` topic, and emits its records
@@ -22,15 +37,23 @@ const CHANNEL_SIZE: usize = 10_000;
 /// It shuts down when the provided [`CancellationToken`] is cancelled.
 pub struct KonsumerOffsetsDataEmitter {
     consumer_client_config: ClientConfig,
+    caught_up_tx: watch::Sender<bool>,
 }
 
 impl KonsumerOffsetsDataEmitter {
     pub fn new(client_config: ClientConfig) -> Self {
         Self {
             consumer_client_config: client_config,
+            caught_up_tx: watch::channel(false).0,
         }
     }
 
+    /// Receives `true` once consumption of `__consumer_offsets`, replayed from the earliest offsets,
+    /// reaches the high watermarks found at assignment (or goes idle): from then on, emitted data is live.
+    pub fn caught_up(&self) -> watch::Receiver<bool> {
+        self.caught_up_tx.subscribe()
+    }
+
     /// Sets the desired Kafka Configuration on the given [`ClientConfig`] object.
     ///
     /// Ref: https://github.com/confluentinc/librdkafka/blob/master/CONFIGURATION.md.
@@ -50,12 +73,13 @@ impl KonsumerOffsetsDataEmitter {
         client_config
     }
 
+    /// Returns the high watermark of each partition that has records to replay.
     async fn assign_and_seek_to_earliest_all_partitions(
         consumer: &KonsumerOffsetsDataConsumer,
         topic: &str,
-    ) -> KafkaResult<()> {
+    ) -> KafkaResult<HashMap<i32, i64>> {
         // Fetch topic metadata
-        let meta = consumer.fetch_metadata(Some(topic), Duration::from_secs(5))?;
+        let meta = block_in_place(|| consumer.fetch_metadata(Some(topic), Duration::from_secs(5)))?;
         let topic_meta = meta.topics().first().ok_or(KafkaError::Subscription(format!(
             "Unable to (self)assign '{}' and seek to earliest offsets",
             topic
@@ -64,23 +88,35 @@ impl KonsumerOffsetsDataEmitter {
         // Prepare desired assignment, setting offset to earliest available for each partition
         let mut desired_assignment =
             TopicPartitionList::with_capacity(topic_meta.partitions().len());
+        let mut high_watermarks = HashMap::with_capacity(topic_meta.partitions().len());
         for partition_meta in topic_meta.partitions().iter() {
-            let (earliest, _) = consumer.fetch_watermarks(
-                topic,
-                partition_meta.id(),
-                Duration::from_millis(500),
-            )?;
+            let (earliest, latest) = block_in_place(|| {
+                consumer.fetch_watermarks(topic, partition_meta.id(), Duration::from_millis(500))
+            })?;
             desired_assignment.add_partition_offset(
                 topic,
                 partition_meta.id(),
                 Offset::Offset(earliest),
             )?;
+            if latest > earliest {
+                high_watermarks.insert(partition_meta.id(), latest);
+            }
         }
 
         // Finally, self-assign
         consumer.assign(&desired_assignment)?;
 
-        Ok(())
+        Ok(high_watermarks)
+    }
+
+    /// Stops tracking the replay of `partition`, once its `position` (i.e. the next offset to consume)
+    /// reaches the high watermark. Returns `true` if that was the last partition being replayed.
+    fn replayed_up_to(replaying: &mut HashMap<i32, i64>, partition: i32, position: i64) -> bool {
+        if replaying.get(&partition).is_some_and(|hw| position >= *hw) {
+            replaying.remove(&partition);
+            return replaying.is_empty();
+        }
+        false
     }
 }
 
@@ -149,21 +185,36 @@ impl Emitter for KonsumerOffsetsDataEmitter {
                 .expect("Failed to create Consumer Client");
 
         let (sx, rx) = mpsc::channel::<KonsumerOffsetsData>(CHANNEL_SIZE);
+        let caught_up_tx = self.caught_up_tx.clone();
 
         let join_handle = tokio::spawn(async move {
-            match Self::assign_and_seek_to_earliest_all_partitions(&consumer_client, KONSUMER_OFFSETS_DATA_TOPIC).await
+            // Partitions still being replayed, with the high watermark to reach
+            let mut replaying = match Self::assign_and_seek_to_earliest_all_partitions(
+                &consumer_client,
+                KONSUMER_OFFSETS_DATA_TOPIC,
+            )
+            .await
             {
-                Ok(_) => info!(
-                    "(Self) Assigned all partitions of {KONSUMER_OFFSETS_DATA_TOPIC} and sought offsets to earliest"
-                ),
+                Ok(hw) => {
+                    info!("(Self) Assigned all partitions of {KONSUMER_OFFSETS_DATA_TOPIC} and sought offsets to earliest");
+                    hw
+                },
                 Err(e) => panic!("Failed to (self) assign '{KONSUMER_OFFSETS_DATA_TOPIC}': {e}"),
+            };
+            if replaying.is_empty() {
+                caught_up_tx.send_replace(true);
             }
 
+            let mut catch_up_check = interval(CATCH_UP_CHECK_INTERVAL);
+            let mut last_received = Instant::now();
+
             loop {
                 tokio::select! {
                     r_msg = consumer_client.recv() => {
                         match r_msg {
                             Ok(m) => {
+                                last_received = Instant::now();
+
                                 match konsumer_offsets::KonsumerOffsetsData::try_from_bytes(m.key(), m.payload()) {
                                     Ok(kod) => {
                                         if let Err(e) = Self::emit(&sx, kod).await {
@@ -174,12 +225,48 @@ impl Emitter for KonsumerOffsetsDataEmitter {
                                         error!("Failed to consume from {}: {e}", KONSUMER_OFFSETS_DATA_TOPIC);
                                     }
                                 }
+
+                                if Self::replayed_up_to(&mut replaying, m.partition(), m.offset() + 1) {
+                                    info!("Caught up with the replay of {KONSUMER_OFFSETS_DATA_TOPIC}");
+                                    caught_up_tx.send_replace(true);
+                                }
                             },
                             Err(e) => {
                                 error!("Failed to fetch cluster metadata: {e}");
                             }
                         }
                     }
+                    _ = catch_up_check.tick(), if !replaying.is_empty() => {
+                        // The position moves past records that are never delivered (e.g. control markers)
+                        let mut caught_up = false;
+                        match consumer_client.position() {
+                            Ok(positions) => {
+                                for e in positions.elements() {
+                                    if let Offset::Offset(position) = e.offset() {
+                                        caught_up |= Self::replayed_up_to(&mut replaying, e.partition(), position);
+                                    }
+                                }
+                            },
+                            Err(e) => {
+                                warn!("Failed to get the position of {KONSUMER_OFFSETS_DATA_TOPIC}: {e}");
+                            }
+                        }
+
+                        if !caught_up && last_received.elapsed() >= CATCH_UP_IDLE_TIMEOUT {
+                            warn!(
+                                "No records from {KONSUMER_OFFSETS_DATA_TOPIC} for {}s: considering {} partitions still replayed as caught up",
+                                CATCH_UP_IDLE_TIMEOUT.as_secs(),
+                                replaying.len()
+                            );
+                            replaying.clear();
+                            caught_up = true;
+                        }
+
+                        if caught_up {
+                            info!("Caught up with the replay of {KONSUMER_OFFSETS_DATA_TOPIC}");
+                            caught_up_tx.send_replace(true);
+                        }
+                    }
                     _ = shutdown_token.cancelled() => {
                         info!("Shutting down");
                         break;
@@ -2,7 +2,7 @@ mod emitter;
 
 use konsumer_offsets::KonsumerOffsetsData;
 use rdkafka::ClientConfig;
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::{mpsc::Receiver, watch};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
@@ -10,13 +10,18 @@ use crate::internals::Emitter;
 
 pub use emitter::KonsumerOffsetsDataEmitter;
 
+/// Spawn the [`KonsumerOffsetsDataEmitter`].
+///
+/// Besides the emitted [`KonsumerOffsetsData`], returns a [`watch::Receiver`] that turns `true`
+/// once the replay of `__consumer_offsets` has caught up (see [`KonsumerOffsetsDataEmitter::caught_up`]).
 pub fn init(
     admin_client_config: ClientConfig,
     shutdown_token: CancellationToken,
-) -> (Receiver<KonsumerOffsetsData>, JoinHandle<()>) {
+) -> (Receiver<KonsumerOffsetsData>, watch::Receiver<bool>, JoinHandle<()>) {
     let konsumer_offsets_data_emitter = KonsumerOffsetsDataEmitter::new(admin_client_config);
+    let caught_up_rx = konsumer_offsets_data_emitter.caught_up();
     let (kod_rx, kod_join) = konsumer_offsets_data_emitter.spawn(shutdown_token);
 
     debug!("Initialized");
-    (kod_rx, kod_join)
+    (kod_rx, caught_up_rx, kod_join)
 }
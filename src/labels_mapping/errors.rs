@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum LabelsMappingError {
+    /// Reading the mapping file failed.
+    #[error("Labels mapping file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content of the mapping file could not be parsed.
+    #[error("Labels mapping parsing failed: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    /// A rule of the mapping has an invalid regular expression.
+    #[error("Labels mapping regex is invalid: {0}")]
+    Regex(#[from] regex::Error),
+
+    /// A rule of the mapping sets a label with an invalid name.
+    #[error("Label name '{0}' is not a valid Prometheus label name")]
+    InvalidLabelName(String),
+
+    /// A rule of the mapping sets a label that Kommitted already applies.
+    #[error("Label name '{0}' is reserved")]
+    ReservedLabelName(String),
+}
+
+pub type LabelsMappingResult<T> = Result<T, LabelsMappingError>;
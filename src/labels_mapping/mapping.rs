@@ -0,0 +1,253 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::prometheus_metrics::compat::{
+    LABEL_CLIENT_ID, LABEL_CLUSTER_NAME, LABEL_CONSUMERGROUP, LABEL_CONSUMER_ID,
+};
+use crate::prometheus_metrics::{
+    LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_MEMBER_CLIENT_ID, LABEL_MEMBER_HOST, LABEL_MEMBER_ID,
+    LABEL_PARTITION, LABEL_SLO, LABEL_TOPIC, LABEL_WINDOW,
+};
+
+use super::errors::{LabelsMappingError, LabelsMappingResult};
+
+/// Labels that Kommitted applies itself, natively or via a compatibility profile, and that a mapping cannot set.
+const RESERVED_LABELS: [&str; 13] = [
+    LABEL_CLUSTER_ID,
+    LABEL_GROUP,
+    LABEL_TOPIC,
+    LABEL_PARTITION,
+    LABEL_MEMBER_ID,
+    LABEL_MEMBER_HOST,
+    LABEL_MEMBER_CLIENT_ID,
+    LABEL_SLO,
+    LABEL_WINDOW,
+    LABEL_CLUSTER_NAME,
+    LABEL_CONSUMER_ID,
+    LABEL_CLIENT_ID,
+    LABEL_CONSUMERGROUP,
+];
+
+/// Extra labels, sorted by name.
+pub type Labels = BTreeMap<String, String>;
+
+/// A rule of the mapping, as it appears in the mapping file.
+#[derive(Debug, Deserialize)]
+struct RawRule {
+    regex: String,
+    labels: Labels,
+}
+
+/// Content of the mapping file.
+#[derive(Debug, Default, Deserialize)]
+struct RawMapping {
+    #[serde(default)]
+    group: Vec<RawRule>,
+
+    #[serde(default)]
+    topic: Vec<RawRule>,
+}
+
+/// A rule of the mapping: names matching `regex` get `labels` applied.
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    labels: Labels,
+}
+
+impl TryFrom<RawRule> for Rule {
+    type Error = LabelsMappingError;
+
+    fn try_from(raw: RawRule) -> LabelsMappingResult<Self> {
+        validate_labels(&raw.labels)?;
+
+        Ok(Rule {
+            regex: Regex::new(&raw.regex)?,
+            labels: raw.labels,
+        })
+    }
+}
+
+/// Mapping from Consumer Group and Topic names to extra labels.
+///
+/// It's defined in TOML, with an array of rules for groups and one for topics:
+///
+/// ```toml
+/// [[group]]
+/// regex = "^payments-"
+/// labels = { team = "payments", tier = "1" }
+///
+/// [[topic]]
+/// regex = "^orders\\."
+/// labels = { team = "orders" }
+/// ```
+///
+/// Static labels are applied to everything, then all the matching rules are applied, in order:
+/// if more than one sets the same label, the last one wins.
+/// When both a group and a topic are involved, group labels win.
+#[derive(Debug, Default)]
+pub struct LabelsMapping {
+    static_labels: Labels,
+    group_rules: Vec<Rule>,
+    topic_rules: Vec<Rule>,
+}
+
+impl LabelsMapping {
+    /// Create a [`LabelsMapping`] without rules, that only applies static labels.
+    pub fn new(static_labels: Labels) -> LabelsMappingResult<Self> {
+        validate_labels(&static_labels)?;
+
+        Ok(LabelsMapping {
+            static_labels,
+            ..Default::default()
+        })
+    }
+
+    /// Parse a [`LabelsMapping`] from its TOML definition, in addition to static labels.
+    pub fn parse(toml_str: &str, static_labels: Labels) -> LabelsMappingResult<Self> {
+        validate_labels(&static_labels)?;
+        let raw: RawMapping = toml::from_str(toml_str)?;
+
+        Ok(LabelsMapping {
+            static_labels,
+            group_rules: raw.group.into_iter().map(Rule::try_from).collect::<Result<_, _>>()?,
+            topic_rules: raw.topic.into_iter().map(Rule::try_from).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Read and parse a [`LabelsMapping`] from the file at `path`, in addition to static labels.
+    pub fn read(path: &Path, static_labels: Labels) -> LabelsMappingResult<Self> {
+        Self::parse(&fs::read_to_string(path)?, static_labels)
+    }
+
+    /// Labels applied to everything.
+    pub fn static_labels(&self) -> &Labels {
+        &self.static_labels
+    }
+
+    /// Labels for the given Consumer Group.
+    pub fn group_labels(&self, group: &str) -> Labels {
+        apply_rules(&self.group_rules, group, self.static_labels.clone())
+    }
+
+    /// Labels for the given Topic.
+    pub fn topic_labels(&self, topic: &str) -> Labels {
+        apply_rules(&self.topic_rules, topic, self.static_labels.clone())
+    }
+
+    /// Labels for the given Topic, consumed by the given Consumer Group.
+    pub fn group_topic_labels(&self, group: &str, topic: &str) -> Labels {
+        apply_rules(&self.group_rules, group, self.topic_labels(topic))
+    }
+}
+
+fn apply_rules(rules: &[Rule], name: &str, mut labels: Labels) -> Labels {
+    for rule in rules.iter().filter(|r| r.regex.is_match(name)) {
+        labels.extend(rule.labels.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    labels
+}
+
+fn validate_labels(labels: &Labels) -> LabelsMappingResult<()> {
+    for name in labels.keys() {
+        if RESERVED_LABELS.contains(&name.as_str()) {
+            return Err(LabelsMappingError::ReservedLabelName(name.clone()));
+        }
+        if !is_valid_label_name(name) {
+            return Err(LabelsMappingError::InvalidLabelName(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// As per Prometheus [data model](https://prometheus.io/docs/concepts/data_model/#metric-names-and-labels).
+fn is_valid_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {},
+        _ => return false,
+    }
+
+    !name.starts_with("__") && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Renders [`Labels`] in Prometheus exposition format, as a fragment to append to other labels.
+pub fn render_labels(labels: &Labels) -> String {
+    labels
+        .iter()
+        .map(|(k, v)| {
+            let v = v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!(",{k}=\"{v}\"")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAPPING: &str = r#"
+        [[group]]
+        regex = "^payments-"
+        labels = { team = "payments", tier = "1" }
+
+        [[group]]
+        regex = "-critical$"
+        labels = { tier = "0" }
+
+        [[topic]]
+        regex = "^orders\\."
+        labels = { team = "orders", env = "prod" }
+    "#;
+
+    #[test]
+    fn should_apply_matching_rules_in_order() {
+        let static_labels = Labels::from([("env".to_string(), "dev".to_string())]);
+        let mapping = LabelsMapping::parse(MAPPING, static_labels).unwrap();
+
+        let labels = mapping.group_labels("payments-ledger-critical");
+        assert_eq!(labels.get("team").unwrap(), "payments");
+        assert_eq!(labels.get("tier").unwrap(), "0");
+
+        assert_eq!(mapping.group_labels("shipping").len(), 1);
+        assert_eq!(mapping.topic_labels("orders.created").len(), 2);
+
+        let labels = mapping.group_topic_labels("payments-ledger", "orders.created");
+        assert_eq!(labels.get("team").unwrap(), "payments");
+        assert_eq!(labels.get("env").unwrap(), "prod");
+        assert_eq!(render_labels(&labels), ",env=\"prod\",team=\"payments\",tier=\"1\"");
+
+        let labels = mapping.group_topic_labels("shipping", "returns");
+        assert_eq!(render_labels(&labels), ",env=\"dev\"");
+    }
+
+    #[test]
+    fn should_reject_invalid_mappings() {
+        let reserved = "[[topic]]\nregex = \".*\"\nlabels = { group = \"x\" }";
+        assert!(matches!(
+            LabelsMapping::parse(reserved, Labels::new()),
+            Err(LabelsMappingError::ReservedLabelName(_))
+        ));
+
+        let reserved_by_compat = "[[group]]\nregex = \".*\"\nlabels = { consumergroup = \"x\" }";
+        assert!(matches!(
+            LabelsMapping::parse(reserved_by_compat, Labels::new()),
+            Err(LabelsMappingError::ReservedLabelName(_))
+        ));
+
+        let invalid = "[[topic]]\nregex = \".*\"\nlabels = { \"1team\" = \"x\" }";
+        assert!(matches!(
+            LabelsMapping::parse(invalid, Labels::new()),
+            Err(LabelsMappingError::InvalidLabelName(_))
+        ));
+
+        let bad_regex = "[[group]]\nregex = \"(\"\nlabels = { team = \"x\" }";
+        assert!(matches!(
+            LabelsMapping::parse(bad_regex, Labels::new()),
+            Err(LabelsMappingError::Regex(_))
+        ));
+    }
+}
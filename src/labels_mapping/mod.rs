@@ -0,0 +1,159 @@
+//! Mapping from Consumer Group and Topic names to extra labels (e.g. `team`, `tier`, `env`).
+//!
+//! The mapping is read from a file, and reloaded at runtime whenever the file changes
+//! (or when the configuration points to a different file).
+
+// Inner modules
+mod errors;
+mod mapping;
+
+// Exports
+pub use mapping::{render_labels, Labels, LabelsMapping};
+
+// Imports
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use tokio::{
+    task::JoinHandle,
+    time::{interval, Duration, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use errors::LabelsMappingResult;
+
+/// How often the mapping file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Holds the current [`LabelsMapping`], and allows to reload it at runtime.
+#[derive(Debug, Default)]
+pub struct LabelsMapper {
+    path: RwLock<Option<PathBuf>>,
+    static_labels: Labels,
+    current: RwLock<Arc<LabelsMapping>>,
+}
+
+impl LabelsMapper {
+    /// Create a new [`LabelsMapper`], parsing the [`LabelsMapping`] from its `inline` definition,
+    /// or reading it from the file at `path` (if any).
+    ///
+    /// The `static_labels` are applied to everything, in addition to the mapping.
+    pub fn new(
+        path: Option<PathBuf>,
+        inline: Option<&str>,
+        static_labels: Labels,
+    ) -> LabelsMappingResult<Self> {
+        let mapping = Self::load_with(path.as_deref(), inline, static_labels.clone())?;
+
+        Ok(LabelsMapper {
+            path: RwLock::new(path),
+            static_labels,
+            current: RwLock::new(Arc::new(mapping)),
+        })
+    }
+
+    /// The current [`LabelsMapping`].
+    pub fn current(&self) -> Arc<LabelsMapping> {
+        self.current.read().expect("Labels mapping lock poisoned").clone()
+    }
+
+    /// Reload the [`LabelsMapping`] from its file.
+    ///
+    /// If the new mapping is invalid, the current one is left in place.
+    pub fn reload(&self) -> LabelsMappingResult<()> {
+        if let Some(p) = self.path() {
+            let mapping = self.load(Some(&p), None)?;
+            *self.current.write().expect("Labels mapping lock poisoned") = Arc::new(mapping);
+            info!("Reloaded labels mapping from {p:?}");
+        }
+
+        Ok(())
+    }
+
+    /// Parse the [`LabelsMapping`] from its `inline` definition, or read it from the file at `path`
+    /// (if any), without applying it.
+    pub fn load(
+        &self,
+        path: Option<&Path>,
+        inline: Option<&str>,
+    ) -> LabelsMappingResult<LabelsMapping> {
+        Self::load_with(path, inline, self.static_labels.clone())
+    }
+
+    /// Apply the `mapping`, [`Self::load`]ed from `path`: from now on, that's the file reloaded
+    /// (none, if it was defined inline).
+    pub fn apply(&self, path: Option<PathBuf>, mapping: LabelsMapping) {
+        *self.path.write().expect("Labels mapping lock poisoned") = path;
+        *self.current.write().expect("Labels mapping lock poisoned") = Arc::new(mapping);
+    }
+
+    fn load_with(
+        path: Option<&Path>,
+        inline: Option<&str>,
+        static_labels: Labels,
+    ) -> LabelsMappingResult<LabelsMapping> {
+        match (inline, path) {
+            (Some(toml_str), _) => LabelsMapping::parse(toml_str, static_labels),
+            (None, Some(p)) => LabelsMapping::read(p, static_labels),
+            (None, None) => LabelsMapping::new(static_labels),
+        }
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        self.path.read().expect("Labels mapping lock poisoned").clone()
+    }
+
+    fn modified_at(&self) -> Option<SystemTime> {
+        self.path().and_then(|p| p.metadata().and_then(|m| m.modified()).ok())
+    }
+}
+
+/// Create a [`LabelsMapper`], and spawn a task that reloads its mapping file (if any)
+/// every time it's modified. A mapping defined `inline` takes the place of the file.
+///
+/// # Panics
+///
+/// If the mapping is given, but it's not a valid [`LabelsMapping`],
+/// or if the static labels are not valid.
+pub fn init(
+    path: Option<PathBuf>,
+    inline: Option<&str>,
+    static_labels: Labels,
+    shutdown_token: CancellationToken,
+) -> (Arc<LabelsMapper>, JoinHandle<()>) {
+    let mapper = Arc::new(
+        LabelsMapper::new(path, inline, static_labels)
+            .unwrap_or_else(|e| panic!("Failed to load labels mapping: {e}")),
+    );
+
+    let mapper_clone = mapper.clone();
+    let join_handle = tokio::spawn(async move {
+        let mut last_modified = mapper_clone.modified_at();
+        let mut interval = interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let modified = mapper_clone.modified_at();
+                    if modified != last_modified {
+                        last_modified = modified;
+                        if let Err(e) = mapper_clone.reload() {
+                            error!("Failed to reload labels mapping (keeping current): {e}");
+                        }
+                    }
+                },
+                _ = shutdown_token.cancelled() => {
+                    info!("Shutting down");
+                    break;
+                },
+            }
+        }
+    });
+
+    debug!("Initialized");
+    (mapper, join_handle)
+}
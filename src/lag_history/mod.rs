@@ -0,0 +1,37 @@
+// Inner modules
+mod register;
+mod sample;
+
+// Exports
+pub use register::LagHistoryRegister;
+
+// Imports
+use std::sync::Arc;
+
+use chrono::Duration;
+use prometheus::Registry;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::lag_register::LagRegister;
+
+pub fn init(
+    lag_reg: Arc<LagRegister>,
+    retention: Duration,
+    resolution: Duration,
+    max_samples: usize,
+    shutdown_token: CancellationToken,
+    metrics: Arc<Registry>,
+) -> (LagHistoryRegister, JoinHandle<()>) {
+    let (lh_reg, lh_join) = LagHistoryRegister::new(
+        lag_reg,
+        retention,
+        resolution,
+        max_samples,
+        shutdown_token,
+        metrics,
+    );
+
+    debug!("Initialized");
+    (lh_reg, lh_join)
+}
@@ -0,0 +1,272 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use prometheus::{register_int_gauge_with_registry, IntGauge, Registry};
+use serde::Serialize;
+use tokio::{
+    sync::RwLock,
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use super::sample::{downsample, GroupLagSample, LagSample};
+
+use crate::kafka_types::TopicPartition;
+use crate::lag_register::LagRegister;
+
+const MET_SERIES_NAME: &str = "lag_history_series";
+const MET_SERIES_HELP: &str = "Amount of group topic partitions whose lag history is tracked";
+const MET_SERIES_DROPPED_NAME: &str = "lag_history_series_dropped";
+const MET_SERIES_DROPPED_HELP: &str =
+    "Amount of group topic partitions whose lag history is not tracked, because of the samples limit";
+
+/// History of the [`LagSample`]s of a Consumer Group.
+#[derive(Debug, Default)]
+struct GroupLagHistory {
+    aggregate: VecDeque<GroupLagSample>,
+    by_topic_partition: HashMap<TopicPartition, VecDeque<LagSample>>,
+}
+
+/// Time-range selection of the history of a Consumer Group, as returned by [`LagHistoryRegister::query`].
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupLagHistoryRange {
+    pub group: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub step_ms: i64,
+    pub aggregate: Vec<GroupLagSample>,
+    pub partitions: Vec<PartitionLagHistoryRange>,
+}
+
+/// Time-range selection of the history of a Consumer Group, for a specific Topic Partition.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionLagHistoryRange {
+    #[serde(flatten)]
+    pub topic_partition: TopicPartition,
+    pub samples: Vec<LagSample>,
+}
+
+/// Holds a bounded, downsampled history of the [`LagRegister`] content.
+///
+/// Every `resolution`, the current [`crate::lag_register::Lag`] of each Consumer Group
+/// is sampled, for each Topic Partition and as an aggregate across all of them.
+/// Samples older than `retention` are discarded.
+///
+/// To bound memory usage, the overall amount of samples held is limited:
+/// once the limit is reached, new Topic Partitions are not tracked.
+pub struct LagHistoryRegister {
+    history: Arc<RwLock<HashMap<String, GroupLagHistory>>>,
+    retention: Duration,
+    resolution: Duration,
+}
+
+impl LagHistoryRegister {
+    /// Create a new [`Self`], and spawn the task that samples the given [`LagRegister`].
+    ///
+    /// # Arguments
+    ///
+    /// * `lag_reg` - The [`LagRegister`] to sample
+    /// * `retention` - How long to keep samples for
+    /// * `resolution` - How often to take samples
+    /// * `max_samples` - Maximum amount of samples to hold, across all Consumer Groups
+    /// * `shutdown_token` - A [`CancellationToken`] that, when cancelled, will make the sampling task terminate
+    pub fn new(
+        lag_reg: Arc<LagRegister>,
+        retention: Duration,
+        resolution: Duration,
+        max_samples: usize,
+        shutdown_token: CancellationToken,
+        metrics: Arc<Registry>,
+    ) -> (Self, JoinHandle<()>) {
+        let lhr = Self {
+            history: Arc::new(RwLock::new(HashMap::new())),
+            retention,
+            resolution,
+        };
+
+        let metric_series: IntGauge =
+            register_int_gauge_with_registry!(MET_SERIES_NAME, MET_SERIES_HELP, metrics)
+                .unwrap_or_else(|_| panic!("Failed to create metric: {MET_SERIES_NAME}"));
+        let metric_series_dropped: IntGauge = register_int_gauge_with_registry!(
+            MET_SERIES_DROPPED_NAME,
+            MET_SERIES_DROPPED_HELP,
+            metrics
+        )
+        .unwrap_or_else(|_| panic!("Failed to create metric: {MET_SERIES_DROPPED_NAME}"));
+
+        // Each series holds (at most) 1 sample per `resolution`, for the whole `retention`:
+        // this determines how many series fit in `max_samples`.
+        let samples_per_series =
+            (retention.num_milliseconds() / resolution.num_milliseconds().max(1)).max(1) as usize;
+        let max_series = max_samples / samples_per_series;
+        info!(
+            "Lag history: {samples_per_series} samples per series, up to {max_series} series (partitions)"
+        );
+
+        let history_clone = lhr.history.clone();
+        let tick = resolution.to_std().expect("Lag history resolution must be positive (fatal)");
+        let join_handle = tokio::spawn(async move {
+            let mut interval = interval(tick);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        let (series, dropped) = sample(
+                            &lag_reg,
+                            &history_clone,
+                            Utc::now(),
+                            retention,
+                            samples_per_series,
+                            max_series,
+                        ).await;
+                        metric_series.set(series as i64);
+                        metric_series_dropped.set(dropped as i64);
+                    },
+                    _ = shutdown_token.cancelled() => {
+                        info!("Shutting down");
+                        break;
+                    },
+                }
+            }
+        });
+
+        (lhr, join_handle)
+    }
+
+    /// How long samples are kept for.
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// How often samples are taken.
+    pub fn resolution(&self) -> Duration {
+        self.resolution
+    }
+
+    /// Select the history of a Consumer Group, in the time range `[from, to]`, downsampled to `step`.
+    ///
+    /// Returns `None` if the Consumer Group has no history.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Consumer Group to select the history of
+    /// * `from` - Beginning of the time range (inclusive)
+    /// * `to` - End of the time range (inclusive)
+    /// * `step` - At most one sample is returned per `step`
+    pub async fn query(
+        &self,
+        group: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        step: Duration,
+    ) -> Option<GroupLagHistoryRange> {
+        let r_guard = self.history.read().await;
+        let glh = r_guard.get(group)?;
+
+        let mut partitions = glh
+            .by_topic_partition
+            .iter()
+            .map(|(tp, samples)| PartitionLagHistoryRange {
+                topic_partition: tp.clone(),
+                samples: downsample(samples, from, to, step).into_iter().cloned().collect(),
+            })
+            .collect::<Vec<PartitionLagHistoryRange>>();
+        partitions.sort_by(|a, b| a.topic_partition.cmp(&b.topic_partition));
+
+        Some(GroupLagHistoryRange {
+            group: group.to_string(),
+            from,
+            to,
+            step_ms: step.num_milliseconds(),
+            aggregate: downsample(&glh.aggregate, from, to, step).into_iter().cloned().collect(),
+            partitions,
+        })
+    }
+}
+
+/// Take a sample of the current content of the [`LagRegister`], and discard the expired ones.
+///
+/// Returns the amount of series (partitions) tracked, and the amount of those that were not,
+/// because of the `max_series` limit.
+async fn sample(
+    lag_reg: &LagRegister,
+    history: &RwLock<HashMap<String, GroupLagHistory>>,
+    now: DateTime<Utc>,
+    retention: Duration,
+    samples_per_series: usize,
+    max_series: usize,
+) -> (usize, usize) {
+    let expire_before = now - retention;
+
+    let mut w_guard = history.write().await;
+    let mut series: usize = w_guard.values().map(|glh| glh.by_topic_partition.len()).sum();
+    let mut dropped = 0;
+
+    for (group, gwl) in lag_reg.lag_by_group.read().await.iter() {
+        let glh = w_guard.entry(group.clone()).or_default();
+
+        let mut agg = GroupLagSample {
+            at: now,
+            offset_lag_total: 0,
+            time_lag_max: Duration::zero(),
+            partitions: 0,
+        };
+
+        for (tp, lag) in gwl
+            .lag_by_topic_partition
+            .iter()
+            .filter_map(|(tp, lwo)| lwo.lag.as_ref().map(|l| (tp, l)))
+        {
+            agg.offset_lag_total += lag.offset_lag;
+            agg.time_lag_max = agg.time_lag_max.max(lag.time_lag);
+            agg.partitions += 1;
+
+            if !glh.by_topic_partition.contains_key(tp) {
+                if series >= max_series {
+                    dropped += 1;
+                    continue;
+                }
+                series += 1;
+                glh.by_topic_partition
+                    .insert(tp.clone(), VecDeque::with_capacity(samples_per_series));
+            }
+
+            if let Some(samples) = glh.by_topic_partition.get_mut(tp) {
+                push_bounded(samples, LagSample::new(now, lag), samples_per_series);
+            }
+        }
+
+        push_bounded(&mut glh.aggregate, agg, samples_per_series);
+    }
+
+    // Discard expired samples, and the history that is left empty
+    w_guard.retain(|_, glh| {
+        glh.by_topic_partition.retain(|_, samples| {
+            expire(samples, |s| s.at < expire_before);
+            !samples.is_empty()
+        });
+        expire(&mut glh.aggregate, |s| s.at < expire_before);
+        !glh.aggregate.is_empty()
+    });
+    series = w_guard.values().map(|glh| glh.by_topic_partition.len()).sum();
+
+    (series, dropped)
+}
+
+fn push_bounded<T>(queue: &mut VecDeque<T>, item: T, capacity: usize) {
+    while queue.len() >= capacity {
+        queue.pop_front();
+    }
+    queue.push_back(item);
+}
+
+fn expire<T>(queue: &mut VecDeque<T>, is_expired: impl Fn(&T) -> bool) {
+    while queue.front().is_some_and(&is_expired) {
+        queue.pop_front();
+    }
+}
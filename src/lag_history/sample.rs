@@ -0,0 +1,147 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::internals::duration_millis;
+use crate::lag_register::Lag;
+
+/// A sample of the [`Lag`] of a Consumer Group for a specific Topic Partition, taken at a point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LagSample {
+    /// [`DateTime<Utc>`] when this sample was taken.
+    pub at: DateTime<Utc>,
+
+    /// See [`Lag`]'s `offset`.
+    pub offset: u64,
+
+    /// See [`Lag`]'s `offset_lag`.
+    pub offset_lag: u64,
+
+    /// See [`Lag`]'s `time_lag`.
+    #[serde(rename = "time_lag_ms", with = "duration_millis")]
+    pub time_lag: Duration,
+}
+
+impl LagSample {
+    pub fn new(at: DateTime<Utc>, lag: &Lag) -> Self {
+        Self {
+            at,
+            offset: lag.offset,
+            offset_lag: lag.offset_lag,
+            time_lag: lag.time_lag,
+        }
+    }
+}
+
+/// A sample of the [`Lag`] of a Consumer Group, aggregated across all its Topic Partitions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupLagSample {
+    /// [`DateTime<Utc>`] when this sample was taken.
+    pub at: DateTime<Utc>,
+
+    /// Sum of the `offset_lag` of all the Topic Partitions.
+    pub offset_lag_total: u64,
+
+    /// Maximum `time_lag` across all the Topic Partitions.
+    #[serde(rename = "time_lag_max_ms", with = "duration_millis")]
+    pub time_lag_max: Duration,
+
+    /// Amount of Topic Partitions the sample was aggregated from.
+    pub partitions: usize,
+}
+
+/// Something that was sampled at a specific point in time.
+pub trait Sampled {
+    fn at(&self) -> DateTime<Utc>;
+}
+
+impl Sampled for LagSample {
+    fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+}
+
+impl Sampled for GroupLagSample {
+    fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+}
+
+/// Select the samples in the time range `[from, to]`, downsampled to at most one every `step`.
+///
+/// The range is divided in consecutive buckets of `step` duration, starting at `from`:
+/// for each bucket, only the latest sample that falls in it is returned.
+///
+/// # Arguments
+///
+/// * `samples` - Samples to select from, sorted by [`Sampled::at`]
+/// * `from` - Beginning of the time range (inclusive)
+/// * `to` - End of the time range (inclusive)
+/// * `step` - Duration of each bucket: must be greater than zero
+pub fn downsample<'a, S: Sampled>(
+    samples: impl IntoIterator<Item = &'a S>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step: Duration,
+) -> Vec<&'a S> {
+    let step_ms = step.num_milliseconds().max(1);
+
+    let mut res: Vec<&S> = Vec::new();
+    let mut last_bucket = None;
+    for s in samples.into_iter().filter(|s| s.at() >= from && s.at() <= to) {
+        let bucket = (s.at() - from).num_milliseconds() / step_ms;
+        if last_bucket == Some(bucket) {
+            // Same bucket: the latest sample replaces the previous one
+            res.pop();
+        }
+        res.push(s);
+        last_bucket = Some(bucket);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Duration, Utc};
+
+    use crate::lag_history::sample::{downsample, LagSample};
+
+    fn sample_at(ms: i64) -> LagSample {
+        LagSample {
+            at: DateTime::<Utc>::from_timestamp_millis(ms).unwrap(),
+            offset: ms as u64,
+            offset_lag: 0,
+            time_lag: Duration::zero(),
+        }
+    }
+
+    #[test]
+    fn downsample_keeps_latest_per_step() {
+        let samples: Vec<LagSample> = (0..10).map(|i| sample_at(i * 1000)).collect();
+
+        let res = downsample(
+            &samples,
+            DateTime::<Utc>::from_timestamp_millis(0).unwrap(),
+            DateTime::<Utc>::from_timestamp_millis(9000).unwrap(),
+            Duration::seconds(3),
+        );
+
+        let offsets: Vec<u64> = res.iter().map(|s| s.offset).collect();
+        assert_eq!(offsets, vec![2000, 5000, 8000, 9000]);
+    }
+
+    #[test]
+    fn downsample_respects_range() {
+        let samples: Vec<LagSample> = (0..10).map(|i| sample_at(i * 1000)).collect();
+
+        let res = downsample(
+            &samples,
+            DateTime::<Utc>::from_timestamp_millis(2500).unwrap(),
+            DateTime::<Utc>::from_timestamp_millis(6000).unwrap(),
+            Duration::seconds(1),
+        );
+
+        let offsets: Vec<u64> = res.iter().map(|s| s.offset).collect();
+        assert_eq!(offsets, vec![3000, 4000, 5000, 6000]);
+    }
+}
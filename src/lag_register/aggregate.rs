@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use chrono::Duration;
+
+use super::register::{GroupWithLag, LagWithOwner};
+
+/// Aggregation of the [`super::Lag`] of multiple Topic Partitions consumed by a Consumer Group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LagAggregate {
+    /// Sum of the `offset_lag` of the Topic Partitions with a known lag.
+    pub offset_lag_sum: u64,
+
+    /// Maximum `offset_lag` of the Topic Partitions with a known lag.
+    pub offset_lag_max: u64,
+
+    /// Sum of the consumed `offset` of the Topic Partitions with a known lag.
+    pub offset_sum: u64,
+
+    /// Maximum `time_lag` of the Topic Partitions with a known lag.
+    pub time_lag_max: Duration,
+
+    /// Topic Partitions with a known lag.
+    pub partitions_with_lag: usize,
+
+    /// Topic Partitions with a known lag, that is greater than zero.
+    pub partitions_lagging: usize,
+
+    /// Topic Partitions owned by a member of the Consumer Group.
+    pub partitions_owned: usize,
+
+    /// Topic Partitions not owned by any member of the Consumer Group.
+    pub partitions_unowned: usize,
+}
+
+impl Default for LagAggregate {
+    fn default() -> Self {
+        Self {
+            offset_lag_sum: 0,
+            offset_lag_max: 0,
+            offset_sum: 0,
+            time_lag_max: Duration::zero(),
+            partitions_with_lag: 0,
+            partitions_lagging: 0,
+            partitions_owned: 0,
+            partitions_unowned: 0,
+        }
+    }
+}
+
+impl LagAggregate {
+    /// Add the given [`LagWithOwner`] to the aggregation.
+    pub fn add(&mut self, lwo: &LagWithOwner) {
+        if let Some(l) = &lwo.lag {
+            self.offset_lag_sum += l.offset_lag;
+            self.offset_lag_max = self.offset_lag_max.max(l.offset_lag);
+            self.offset_sum += l.offset;
+            self.time_lag_max = self.time_lag_max.max(l.time_lag);
+            self.partitions_with_lag += 1;
+            if l.offset_lag > 0 {
+                self.partitions_lagging += 1;
+            }
+        }
+
+        if lwo.owner.is_some() {
+            self.partitions_owned += 1;
+        } else {
+            self.partitions_unowned += 1;
+        }
+    }
+}
+
+impl GroupWithLag {
+    /// Aggregate the lag of all the Topic Partitions consumed by this Consumer Group.
+    pub fn aggregate(&self) -> LagAggregate {
+        let mut agg = LagAggregate::default();
+        for lwo in self.lag_by_topic_partition.values() {
+            agg.add(lwo);
+        }
+        agg
+    }
+
+    /// Aggregate the lag of the Topic Partitions consumed by this Consumer Group, by Topic.
+    pub fn aggregate_by_topic(&self) -> BTreeMap<&str, LagAggregate> {
+        let mut res: BTreeMap<&str, LagAggregate> = BTreeMap::new();
+        for (tp, lwo) in self.lag_by_topic_partition.iter() {
+            res.entry(tp.topic.as_str()).or_default().add(lwo);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::{Duration, Utc};
+
+    use crate::kafka_types::{Member, TopicPartition};
+    use crate::lag_register::{GroupWithLag, Lag, LagWithOwner};
+
+    fn lwo(offset_lag: Option<u64>, time_lag_ms: i64, owned: bool) -> LagWithOwner {
+        LagWithOwner {
+            lag: offset_lag.map(|ol| Lag {
+                offset: 1000,
+                offset_timestamp: Utc::now(),
+                offset_lag: ol,
+                time_lag: Duration::milliseconds(time_lag_ms),
+            }),
+            owner: owned.then(Member::default),
+            ..Default::default()
+        }
+    }
+
+    fn example_group() -> GroupWithLag {
+        GroupWithLag {
+            lag_by_topic_partition: HashMap::from([
+                (TopicPartition::new("a".to_string(), 0), lwo(Some(10), 100, true)),
+                (TopicPartition::new("a".to_string(), 1), lwo(Some(0), 0, true)),
+                (TopicPartition::new("a".to_string(), 2), lwo(None, 0, false)),
+                (TopicPartition::new("b".to_string(), 0), lwo(Some(5), 700, false)),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn aggregate_group() {
+        let agg = example_group().aggregate();
+
+        assert_eq!(agg.offset_lag_sum, 15);
+        assert_eq!(agg.offset_lag_max, 10);
+        assert_eq!(agg.offset_sum, 3000);
+        assert_eq!(agg.time_lag_max, Duration::milliseconds(700));
+        assert_eq!(agg.partitions_with_lag, 3);
+        assert_eq!(agg.partitions_lagging, 2);
+        assert_eq!(agg.partitions_owned, 2);
+        assert_eq!(agg.partitions_unowned, 2);
+    }
+
+    #[test]
+    fn aggregate_group_by_topic() {
+        let gwl = example_group();
+        let aggs = gwl.aggregate_by_topic();
+
+        assert_eq!(aggs.len(), 2);
+
+        let a = aggs.get("a").unwrap();
+        assert_eq!(a.offset_lag_sum, 10);
+        assert_eq!(a.time_lag_max, Duration::milliseconds(100));
+        assert_eq!(a.partitions_with_lag, 2);
+        assert_eq!(a.partitions_lagging, 1);
+        assert_eq!(a.partitions_owned, 2);
+        assert_eq!(a.partitions_unowned, 1);
+
+        let b = aggs.get("b").unwrap();
+        assert_eq!(b.offset_lag_sum, 5);
+        assert_eq!(b.partitions_owned, 0);
+        assert_eq!(b.partitions_unowned, 1);
+    }
+}
@@ -0,0 +1,88 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::kafka_types::{Member, TopicPartition};
+
+use super::Lag;
+
+/// Amount of [`LagChange`]s buffered for each subscriber: subscribers that fall further behind
+/// skip the oldest changes, instead of slowing down the [`super::LagRegister`].
+pub(super) const CHANGES_CAPACITY: usize = 4096;
+
+/// Change of an entry of the [`super::LagRegister`], published to its subscribers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LagChange {
+    /// The Consumer Group committed an offset, and its [`Lag`] was updated.
+    Lag {
+        group: String,
+
+        #[serde(flatten)]
+        topic_partition: TopicPartition,
+
+        #[serde(flatten)]
+        lag: Lag,
+    },
+
+    /// The member of the Consumer Group owning the Topic Partition changed.
+    Owner {
+        group: String,
+
+        #[serde(flatten)]
+        topic_partition: TopicPartition,
+
+        owner: Option<Member>,
+    },
+
+    /// The Consumer Group no longer consumes the Topic Partition.
+    Removed {
+        group: String,
+
+        #[serde(flatten)]
+        topic_partition: TopicPartition,
+    },
+}
+
+impl LagChange {
+    pub fn group(&self) -> &str {
+        match self {
+            LagChange::Lag {
+                group,
+                ..
+            }
+            | LagChange::Owner {
+                group,
+                ..
+            }
+            | LagChange::Removed {
+                group,
+                ..
+            } => group,
+        }
+    }
+
+    pub fn topic_partition(&self) -> &TopicPartition {
+        match self {
+            LagChange::Lag {
+                topic_partition,
+                ..
+            }
+            | LagChange::Owner {
+                topic_partition,
+                ..
+            }
+            | LagChange::Removed {
+                topic_partition,
+                ..
+            } => topic_partition,
+        }
+    }
+}
+
+/// Publish a [`LagChange`], only building it if there is any subscriber to receive it.
+pub(super) fn publish(changes: &broadcast::Sender<LagChange>, change: impl FnOnce() -> LagChange) {
+    if changes.receiver_count() > 0 {
+        // Can only fail if all the subscribers are gone in the meantime
+        let _ = changes.send(change());
+    }
+}
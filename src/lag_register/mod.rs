@@ -1,21 +1,28 @@
+mod aggregate;
+mod change;
 mod register;
 
 use std::sync::Arc;
 
+use chrono::Duration;
 use konsumer_offsets::KonsumerOffsetsData;
 use tokio::sync::mpsc::Receiver;
 
 use crate::consumer_groups::ConsumerGroups;
 use crate::partition_offsets::PartitionOffsetsRegister;
 
-pub use register::{Lag, LagRegister};
+pub use aggregate::LagAggregate;
+pub use change::LagChange;
+pub use register::{GroupWithLag, Lag, LagRegister, LagWithOwner};
 
 pub fn init(
     cg_rx: Receiver<ConsumerGroups>,
     kod_rx: Receiver<KonsumerOffsetsData>,
     po_reg: Arc<PartitionOffsetsRegister>,
+    status_window: usize,
+    status_min_distance: Duration,
 ) -> LagRegister {
-    let l_reg = LagRegister::new(cg_rx, kod_rx, po_reg);
+    let l_reg = LagRegister::new(cg_rx, kod_rx, po_reg, status_window, status_min_distance);
 
     debug!("Initialized");
     l_reg
@@ -1,24 +1,27 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     sync::Arc,
 };
 
 use chrono::{DateTime, Duration, Utc};
 use konsumer_offsets::{GroupMetadata, KonsumerOffsetsData, OffsetCommit};
 use log::Level::Trace;
-use tokio::sync::{mpsc, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc, RwLock};
 
 use crate::constants::KOMMITTED_CONSUMER_OFFSETS_CONSUMER;
 use crate::consumer_groups::ConsumerGroups;
-use crate::internals::Awaitable;
-use crate::kafka_types::{Group, Member, TopicPartition};
+use crate::internals::{duration_millis, Awaitable};
+use crate::kafka_types::{Group, GroupWithMembers, Member, MemberWithAssignment, TopicPartition};
 use crate::partition_offsets::PartitionOffsetsRegister;
 
+use super::change::{publish, LagChange, CHANGES_CAPACITY};
+
 /// Describes the "lag" (or "latency"), and it's usually paired with a Consumer [`GroupWithMembers`].
 ///
 /// Additionally, it carries the "context" of the lag, including the offsets like the one
 /// it was measured against, the earliest and the latest (tracked and available).
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Lag {
     /// Offset that a given Consumer [`GroupWithMembers`] is at when consuming a specific [`TopicPartition`].
     pub(crate) offset: u64,
@@ -30,6 +33,7 @@ pub struct Lag {
     pub(crate) offset_lag: u64,
 
     /// Estimated time latency between the Consumer [`GroupWithMembers`] consuming a specific [`TopicPartition`], and the [`DateTime<Utc>`] when the high watermark (end offset) was produced.
+    #[serde(rename = "time_lag_ms", with = "duration_millis")]
     pub(crate) time_lag: Duration,
 }
 
@@ -44,10 +48,38 @@ impl Default for Lag {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
 pub struct LagWithOwner {
     pub(crate) lag: Option<Lag>,
     pub(crate) owner: Option<Member>,
+
+    /// Sliding window of the most recent [`Lag`]s (oldest first), one per offset commit.
+    ///
+    /// Used to evaluate the status of the consumer: see [`crate::consumer_status`].
+    #[serde(default, skip_serializing_if = "VecDeque::is_empty")]
+    pub(crate) window: VecDeque<Lag>,
+}
+
+impl LagWithOwner {
+    /// Set the latest [`Lag`], also pushing it into the sliding `window` of `window_size`.
+    ///
+    /// While the latest [`Lag`] in the `window` is less than `min_distance` after the one before it,
+    /// it gets replaced instead: the window keeps the most recent commit, while the older ones
+    /// are at least `min_distance` apart.
+    fn push_lag(&mut self, lag: Lag, window_size: usize, min_distance: Duration) {
+        let len = self.window.len();
+        if len >= 2
+            && self.window[len - 1].offset_timestamp - self.window[len - 2].offset_timestamp
+                < min_distance
+        {
+            self.window.pop_back();
+        }
+        while self.window.len() >= window_size {
+            self.window.pop_front();
+        }
+        self.window.push_back(lag.clone());
+        self.lag = Some(lag);
+    }
 }
 
 /// Describes the "lag" (or "latency") of a specific Consumer [`GroupWithMembers`] in respect to a collection of [`TopicPartition`] that it consumes.
@@ -58,9 +90,41 @@ pub struct GroupWithLag {
     pub(crate) lag_by_topic_partition: HashMap<TopicPartition, LagWithOwner>,
 }
 
+impl GroupWithLag {
+    /// Rebuild the [`GroupWithMembers`], from the owners of the consumed Topic Partitions.
+    ///
+    /// Members without any Topic Partition assigned are not known, and so are not included.
+    pub fn members(&self) -> GroupWithMembers {
+        let mut members: HashMap<String, MemberWithAssignment> = HashMap::new();
+        for (tp, lwo) in self.lag_by_topic_partition.iter() {
+            if let Some(owner) = lwo.owner.as_ref() {
+                members
+                    .entry(owner.id.clone())
+                    .or_insert_with(|| MemberWithAssignment {
+                        member: owner.clone(),
+                        assignment: HashSet::new(),
+                    })
+                    .assignment
+                    .insert(tp.clone());
+            }
+        }
+
+        GroupWithMembers {
+            group: self.group.clone(),
+            members,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct LagRegister {
     pub(crate) lag_by_group: Arc<RwLock<HashMap<String, GroupWithLag>>>,
+
+    /// Size of the sliding window of [`Lag`]s kept in each [`LagWithOwner`].
+    pub(crate) status_window: usize,
+
+    /// Publishes the [`LagChange`]s, as the register is updated.
+    changes: broadcast::Sender<LagChange>,
 }
 
 impl LagRegister {
@@ -68,29 +132,34 @@ impl LagRegister {
         mut cg_rx: mpsc::Receiver<ConsumerGroups>,
         mut kod_rx: mpsc::Receiver<KonsumerOffsetsData>,
         po_reg: Arc<PartitionOffsetsRegister>,
+        status_window: usize,
+        status_min_distance: Duration,
     ) -> Self {
         let lr = LagRegister {
             lag_by_group: Arc::new(RwLock::new(HashMap::default())),
+            status_window,
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
         };
 
         let lag_by_group_clone = lr.lag_by_group.clone();
+        let changes_clone = lr.changes.clone();
 
         tokio::spawn(async move {
             loop {
                 tokio::select! {
                     Some(cg) = cg_rx.recv() => {
                         trace!("Processing {} reporting {} Groups", std::any::type_name::<ConsumerGroups>(), cg.groups.len());
-                        process_consumer_groups(cg, lag_by_group_clone.clone()).await;
+                        process_consumer_groups(cg, lag_by_group_clone.clone(), &changes_clone).await;
                     },
                     Some(kod) = kod_rx.recv() => {
                         match kod {
                             KonsumerOffsetsData::OffsetCommit(oc) => {
                                 trace!("Processing {} of Group '{}' for Topic Partition '{}:{}'", std::any::type_name::<OffsetCommit>(), oc.group, oc.topic, oc.partition);
-                                process_offset_commit(oc, lag_by_group_clone.clone(), po_reg.clone()).await;
+                                process_offset_commit(oc, lag_by_group_clone.clone(), po_reg.clone(), status_window, status_min_distance, &changes_clone).await;
                             },
                             KonsumerOffsetsData::GroupMetadata(gm) => {
                                 debug!("Processing {} of Group '{}' with {} Members", std::any::type_name::<GroupMetadata>(), gm.group, gm.members.len());
-                                process_group_metadata(gm, lag_by_group_clone.clone()).await;
+                                process_group_metadata(gm, lag_by_group_clone.clone(), &changes_clone).await;
                             }
                         }
                     },
@@ -120,11 +189,42 @@ impl LagRegister {
 
         lr
     }
+
+    /// Restore a previously known collection of [`GroupWithLag`].
+    ///
+    /// Groups already known to the register are left untouched, as their data
+    /// is assumed to be more recent: only unknown Groups are restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - [`GroupWithLag`]s to restore, usually read from a snapshot
+    pub async fn restore(&self, groups: Vec<GroupWithLag>) -> usize {
+        let mut w_guard = self.lag_by_group.write().await;
+
+        let mut restored = 0;
+        for gwl in groups.into_iter() {
+            if let Entry::Vacant(e) = w_guard.entry(gwl.group.name.clone()) {
+                e.insert(gwl);
+                restored += 1;
+            }
+        }
+
+        restored
+    }
+
+    /// Subscribe to the [`LagChange`]s of the register.
+    ///
+    /// Changes are buffered for each subscriber: one that falls too far behind
+    /// skips the oldest changes, and is told how many via [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<LagChange> {
+        self.changes.subscribe()
+    }
 }
 
 async fn process_consumer_groups(
     cg: ConsumerGroups,
     lag_register_groups: Arc<RwLock<HashMap<String, GroupWithLag>>>,
+    changes: &broadcast::Sender<LagChange>,
 ) {
     for (group_name, group_with_members) in cg.groups.into_iter() {
         // Ignore own consumer of `__consumer_offsets` topic.
@@ -137,8 +237,8 @@ async fn process_consumer_groups(
         // Organise all the Group Members by the TopicPartition they own
         let members_by_topic_partition = group_with_members
             .members
-            .into_iter()
-            .flat_map(|(_, mwa)| {
+            .into_values()
+            .flat_map(|mwa| {
                 mwa.assignment
                     .into_iter()
                     .map(|tp| (tp, mwa.member.clone()))
@@ -148,6 +248,14 @@ async fn process_consumer_groups(
 
         // Insert or update "group name -> group with lag" map entries
         if let Entry::Vacant(e) = w_guard.entry(group_name.clone()) {
+            for (tp, m) in members_by_topic_partition.iter() {
+                publish(changes, || LagChange::Owner {
+                    group: group_name.clone(),
+                    topic_partition: tp.clone(),
+                    owner: Some(m.clone()),
+                });
+            }
+
             e.insert(GroupWithLag {
                 group: group_with_members.group,
                 // Given this is a new Group,
@@ -177,19 +285,30 @@ async fn process_consumer_groups(
             gwl.group = group_with_members.group;
 
             // Remove from map of LagWithOwner the entries with key TopicPartition not owner by any member of this group
-            gwl.lag_by_topic_partition.retain(|tp, _| members_by_topic_partition.contains_key(tp));
+            gwl.lag_by_topic_partition.retain(|tp, _| {
+                let owned = members_by_topic_partition.contains_key(tp);
+                if !owned {
+                    publish(changes, || LagChange::Removed {
+                        group: group_name.clone(),
+                        topic_partition: tp.clone(),
+                    });
+                }
+                owned
+            });
 
             // Create or Update a entries `TopicPartition -> LagWithOwner`:
             // either update the owner Member of an existing one,
             // or create a new entry with no Lag set.
             for (tp, m) in members_by_topic_partition.into_iter() {
-                gwl.lag_by_topic_partition
-                    .entry(tp)
-                    .and_modify(|lwo| lwo.owner = Some(m.clone()))
-                    .or_insert_with(|| LagWithOwner {
-                        owner: Some(m),
-                        ..Default::default()
+                let lwo = gwl.lag_by_topic_partition.entry(tp.clone()).or_default();
+                if lwo.owner.as_ref() != Some(&m) {
+                    publish(changes, || LagChange::Owner {
+                        group: group_name.clone(),
+                        topic_partition: tp,
+                        owner: Some(m.clone()),
                     });
+                    lwo.owner = Some(m);
+                }
             }
         };
     }
@@ -199,6 +318,9 @@ async fn process_offset_commit(
     oc: OffsetCommit,
     lag_register_groups: Arc<RwLock<HashMap<String, GroupWithLag>>>,
     po_reg: Arc<PartitionOffsetsRegister>,
+    status_window: usize,
+    status_min_distance: Duration,
+    changes: &broadcast::Sender<LagChange>,
 ) {
     // Ignore own consumer of `__consumer_offsets` topic.
     if oc.group == KOMMITTED_CONSUMER_OFFSETS_CONSUMER {
@@ -211,6 +333,20 @@ async fn process_offset_commit(
         Some(gwl) => {
             let tp = TopicPartition::new(oc.topic, oc.partition as u32);
 
+            // Ignore commits older than the Lag we already know about
+            // (e.g. restored from a snapshot, while `__consumer_offsets` is being replayed).
+            if let Some(Some(known)) = gwl.lag_by_topic_partition.get(&tp).map(|lwo| &lwo.lag) {
+                if known.offset_timestamp > oc.commit_timestamp {
+                    trace!(
+                        "Ignoring {} of Group '{}' for Topic Partition '{}': older than known Lag",
+                        std::any::type_name::<OffsetCommit>(),
+                        oc.group,
+                        tp
+                    );
+                    return;
+                }
+            }
+
             // Prepare all the Lag fields
             let l = Lag {
                 offset: oc.offset as u64,
@@ -239,13 +375,16 @@ async fn process_offset_commit(
             // Create or update entry `TopicPartition -> LagWithOwner`:
             // either update the Lag of an existing one,
             // or create a new entry with no owner set.
-            gwl.lag_by_topic_partition
-                .entry(tp)
-                .and_modify(|lwo| lwo.lag = Some(l.clone()))
-                .or_insert_with(|| LagWithOwner {
-                    lag: Some(l),
-                    owner: None,
-                });
+            publish(changes, || LagChange::Lag {
+                group: oc.group.clone(),
+                topic_partition: tp.clone(),
+                lag: l.clone(),
+            });
+            gwl.lag_by_topic_partition.entry(tp).or_default().push_lag(
+                l,
+                status_window,
+                status_min_distance,
+            );
         },
         None => {
             warn!(
@@ -260,6 +399,7 @@ async fn process_offset_commit(
 async fn process_group_metadata(
     gm: GroupMetadata,
     lag_register_groups: Arc<RwLock<HashMap<String, GroupWithLag>>>,
+    changes: &broadcast::Sender<LagChange>,
 ) {
     // Ignore own consumer of `__consumer_offsets` topic.
     if gm.group == KOMMITTED_CONSUMER_OFFSETS_CONSUMER {
@@ -310,12 +450,28 @@ async fn process_group_metadata(
             //
             // NOTE: The new ones that are NOT YET in the map, will be added when an
             // OffsetCommit for this Group and this Topic-Partition is received and Lag calculated.
-            gwl.lag_by_topic_partition.retain(|tp, _| new_tp_to_owner.contains_key(tp));
+            gwl.lag_by_topic_partition.retain(|tp, _| {
+                let owned = new_tp_to_owner.contains_key(tp);
+                if !owned {
+                    publish(changes, || LagChange::Removed {
+                        group: gm.group.clone(),
+                        topic_partition: tp.clone(),
+                    });
+                }
+                owned
+            });
 
             // For all the Topic-Partition in the GroupMetadata, set the Member that owns it
             for (tp, owner) in new_tp_to_owner.into_iter() {
                 if let Some(lwo) = gwl.lag_by_topic_partition.get_mut(&tp) {
-                    lwo.owner = Some(owner)
+                    if lwo.owner.as_ref() != Some(&owner) {
+                        publish(changes, || LagChange::Owner {
+                            group: gm.group.clone(),
+                            topic_partition: tp,
+                            owner: Some(owner.clone()),
+                        });
+                        lwo.owner = Some(owner)
+                    }
                 }
             }
         },
@@ -332,6 +488,43 @@ async fn process_group_metadata(
 impl Awaitable for LagRegister {
     async fn is_ready(&self) -> bool {
         // TODO https://github.com/kafkesc/kommitted/issues/59
-        self.lag_by_group.read().await.len() > 0
+        !self.lag_by_group.read().await.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lag_at(seconds: i64) -> Lag {
+        Lag {
+            offset: seconds as u64,
+            offset_timestamp: DateTime::from_timestamp(seconds, 0).unwrap(),
+            offset_lag: 1,
+            time_lag: Duration::zero(),
+        }
+    }
+
+    fn window_offsets(lwo: &LagWithOwner) -> Vec<u64> {
+        lwo.window.iter().map(|l| l.offset).collect()
+    }
+
+    #[test]
+    fn push_lag_keeps_min_distance() {
+        let mut lwo = LagWithOwner::default();
+        for s in [0, 1, 2, 3, 5, 20, 21, 22] {
+            lwo.push_lag(lag_at(s), 10, Duration::seconds(5));
+        }
+
+        // The latest commit is always in the window, replacing the previous one if too close
+        assert_eq!(window_offsets(&lwo), [0, 5, 20, 22]);
+        assert_eq!(lwo.lag.as_ref().unwrap().offset, 22);
+
+        // Without a minimum distance, every commit enters the window, up to its size
+        let mut lwo = LagWithOwner::default();
+        for s in [0, 1, 2, 3, 4] {
+            lwo.push_lag(lag_at(s), 3, Duration::zero());
+        }
+        assert_eq!(window_offsets(&lwo), [2, 3, 4]);
     }
 }
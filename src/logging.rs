@@ -1,10 +1,59 @@
+use std::sync::{OnceLock, RwLock};
+
+use log::{Log, Metadata, Record};
+
 pub const LOG_FILTER_ENV_VAR: &str = "KOMMITTED_LOG";
 
+/// The logger in use, set once by [`init`].
+static LOGGER: OnceLock<ReloadableLogger> = OnceLock::new();
+
+/// Wraps a [`env_logger::Logger`], so that it can be replaced at runtime.
+struct ReloadableLogger {
+    inner: RwLock<env_logger::Logger>,
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.read().expect("Logger lock poisoned").enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.read().expect("Logger lock poisoned").log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.read().expect("Logger lock poisoned").flush()
+    }
+}
+
 /// Log level will be configured based on the given `verbosity_level`.
 ///
 /// If the env var `KOMMITTED_LOG` is set, that will take precedence and configuration
 /// will be based on the rules described [here](https://docs.rs/env_logger/latest/env_logger/#enabling-logging).
 pub fn init(verbosity_level: i8) {
+    let logger = LOGGER.get_or_init(|| ReloadableLogger {
+        inner: RwLock::new(build_logger(verbosity_level)),
+    });
+    log::set_logger(logger).expect("Logger initialized more than once");
+    log::set_max_level(logger.inner.read().expect("Logger lock poisoned").filter());
+
+    info!("Configured log level: {}", log::max_level().as_str());
+}
+
+/// Replace the log level configured by [`init`], based on the given `verbosity_level`.
+pub fn set_verbosity_level(verbosity_level: i8) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+
+    let new_logger = build_logger(verbosity_level);
+    log::set_max_level(new_logger.filter());
+    *logger.inner.write().expect("Logger lock poisoned") = new_logger;
+
+    info!("Reconfigured log level: {}", log::max_level().as_str());
+}
+
+fn build_logger(verbosity_level: i8) -> env_logger::Logger {
     let default_log_level = match verbosity_level {
         i8::MIN..=-2 => "OFF",
         -1 => log::Level::Error.as_str(),
@@ -15,8 +64,5 @@ pub fn init(verbosity_level: i8) {
     };
 
     let logger_env = env_logger::Env::default().filter_or(LOG_FILTER_ENV_VAR, default_log_level);
-    let mut logger_builder = env_logger::Builder::from_env(logger_env);
-    logger_builder.init();
-
-    info!("Configured log level: {}", log::max_level().as_str());
+    env_logger::Builder::from_env(logger_env).build()
 }
@@ -1,100 +1,183 @@
 #[macro_use]
 extern crate log;
 
+mod alerts;
 mod cli;
 mod cluster_status;
+mod clusters;
+mod config;
 mod constants;
 mod consumer_groups;
+mod consumer_rates;
+mod consumer_status;
+mod data_loss;
+mod external_metrics;
+mod file_sink;
 mod http;
+mod influx;
 mod internals;
+mod kafka_sink;
 mod kafka_types;
+mod keda_scaler;
 mod konsumer_offsets_data;
+mod labels_mapping;
+mod lag_history;
 mod lag_register;
 mod logging;
+mod otlp;
 mod partition_offsets;
 mod prometheus_metrics;
+mod remote_write;
+mod scaling;
+mod slo;
+mod snapshot;
+mod statsd;
 
-use clap::Parser;
 use std::{error::Error, sync::Arc};
 
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::watch,
+};
 use tokio_util::sync::CancellationToken;
 
+use crate::alerts::AlertRules;
 use crate::cli::Cli;
-use crate::internals::Awaitable;
+use crate::clusters::ClusterSpec;
+use crate::labels_mapping::LabelsMapper;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let cli = parse_cli_and_init_logging();
-    let admin_client_config = cli.build_client_config();
+    let (cli, config_file) = parse_cli_and_init_logging();
+    let cli = Arc::new(cli);
     let shutdown_token = build_shutdown_token();
 
-    // Init `prometheus_metrics` module
-    let prom_reg = prometheus_metrics::init(admin_client_config.clone(), cli.cluster_id.clone());
-    let prom_reg_arc = Arc::new(prom_reg);
-
-    // Init `cluster_status` module, and await registry to be ready
-    let (cs_reg, cs_join) = cluster_status::init(
-        admin_client_config.clone(),
-        cli.cluster_id.clone(),
-        shutdown_token.clone(),
-        prom_reg_arc.clone(),
-    );
-    cs_reg.await_ready(shutdown_token.clone()).await?;
-    let cs_reg_arc = Arc::new(cs_reg);
-
-    // Init `partition_offsets` module, and await registry to be ready
-    let (po_reg, po_join) = partition_offsets::init(
-        admin_client_config.clone(),
-        cli.offsets_history,
-        cli.offsets_history_ready_at,
-        cs_reg_arc.clone(),
+    // Init `labels_mapping` module
+    let (labels_mapper, lm_join) = labels_mapping::init(
+        cli.labels_mapping.clone(),
+        cli.labels_mapping_inline.as_deref(),
+        cli.static_labels.iter().cloned().collect(),
         shutdown_token.clone(),
-        prom_reg_arc.clone(),
     );
-    po_reg.await_ready(shutdown_token.clone()).await?;
-    let po_reg_arc = Arc::new(po_reg);
 
-    // Init `konsumer_offsets_data` module
-    let (kod_rx, kod_join) =
-        konsumer_offsets_data::init(admin_client_config.clone(), shutdown_token.clone());
+    // Load alert rules, shared by the `alerts` module of every cluster
+    let (alert_rules_tx, alert_rules) =
+        alerts::init_rules(cli.alert_rules.as_deref(), cli.alert_rules_inline.as_deref());
 
-    // Init `consumer_groups` module
-    let (cg_rx, cg_join) = consumer_groups::init(
-        admin_client_config.clone(),
+    // Init `config` module, to reload the configuration at runtime
+    let config_join = config::init(
+        cli.clone(),
+        config_file,
+        labels_mapper.clone(),
+        alert_rules_tx,
         shutdown_token.clone(),
-        prom_reg_arc.clone(),
     );
 
-    // Init `lag_register` module, and await registry to be ready
-    let lag_reg = lag_register::init(cg_rx, kod_rx, po_reg_arc.clone());
-    lag_reg.await_ready(shutdown_token.clone()).await?;
-    let lag_reg_arc = Arc::new(lag_reg);
-
-    // Init `http` module
-    let http_fut = http::init(
-        cli.listen_on(),
-        cs_reg_arc.clone(),
-        po_reg_arc.clone(),
-        lag_reg_arc.clone(),
-        shutdown_token.clone(),
-        prom_reg_arc.clone(),
-    );
+    if cli.clusters.is_some() {
+        run_multi_cluster(cli, labels_mapper, alert_rules, shutdown_token).await;
+    } else {
+        run_single_cluster(cli, labels_mapper, alert_rules, shutdown_token).await?;
+    }
 
-    // Join all the async tasks, then let it terminate
-    let _ = tokio::join!(cs_join, po_join, kod_join, cg_join, http_fut);
+    let _ = tokio::join!(lm_join, config_join);
 
     info!("Shutdown!");
     std::process::exit(exit_code::SUCCESS);
 }
 
-fn parse_cli_and_init_logging() -> Cli {
-    // Parse command line input and initialize logging
-    let cli = Cli::parse();
+/// Monitor the Kafka Cluster at '--brokers': it must become ready before anything is served.
+async fn run_single_cluster(
+    cli: Arc<Cli>,
+    labels_mapper: Arc<LabelsMapper>,
+    alert_rules: watch::Receiver<Arc<AlertRules>>,
+    shutdown_token: CancellationToken,
+) -> Result<(), Box<dyn Error>> {
+    let admin_client_config = cli.build_client_config();
+
+    // Load the TLS certificate and key of the `external_metrics` module first, to fail fast
+    let em_tls_config = match (&cli.external_metrics_tls_cert, &cli.external_metrics_tls_key) {
+        (Some(cert), Some(key)) if cli.external_metrics_listen_on().is_some() => {
+            Some(external_metrics::load_tls_config(cert, key).await?)
+        },
+        _ => None,
+    };
+
+    // Init the pipeline of the cluster, and await its registers to be ready
+    let spec = ClusterSpec {
+        cluster_id: cli.cluster_id.clone(),
+        snapshot_path: cli.snapshot_path.clone(),
+        kafka_sink: cli.kafka_sink_config(admin_client_config.clone()),
+        file_sink: cli.file_sink_config(),
+        client_config: admin_client_config,
+    };
+    let pipeline =
+        clusters::start(&cli, spec, labels_mapper, alert_rules, shutdown_token.clone()).await?;
+
+    // Init `keda_scaler` module, if a port was given
+    let keda_join = cli.keda_scaler_listen_on().map(|listen_on| {
+        keda_scaler::init(listen_on, pipeline.state.lag_reg.clone(), shutdown_token.clone())
+    });
+
+    // Init `external_metrics` module, if a port was given
+    let em_join = cli.external_metrics_listen_on().map(|listen_on| {
+        external_metrics::init(
+            listen_on,
+            em_tls_config.expect("Required by '--external-metrics-port'"),
+            pipeline.state.lag_reg.clone(),
+            shutdown_token.clone(),
+        )
+    });
+
+    // Init `http` module
+    let http_fut = http::init(cli.listen_on(), pipeline.state.clone(), shutdown_token.clone());
+
+    // Join all the async tasks
+    let _ = tokio::join!(pipeline.join(), http_fut);
+    if let Some(keda_join) = keda_join {
+        let _ = keda_join.await;
+    }
+    if let Some(em_join) = em_join {
+        let _ = em_join.await;
+    }
+
+    Ok(())
+}
+
+/// Monitor all the Kafka Clusters in '--clusters': each starts (or fails) independently,
+/// while the HTTP Service serves the ones that are ready.
+async fn run_multi_cluster(
+    cli: Arc<Cli>,
+    labels_mapper: Arc<LabelsMapper>,
+    alert_rules: watch::Receiver<Arc<AlertRules>>,
+    shutdown_token: CancellationToken,
+) {
+    let path = cli.clusters.clone().expect("Checked by caller");
+    let configs = clusters::read(&path)
+        .unwrap_or_else(|e| panic!("Failed to load clusters from '{}': {e}", path.display()));
+
+    // Init `clusters` module
+    let (cluster_set, cluster_joins) =
+        clusters::init(cli.clone(), configs, labels_mapper, alert_rules, shutdown_token.clone());
+
+    // Init `http` module
+    let http_fut = http::init_multi(cli.listen_on(), cluster_set, shutdown_token.clone());
+
+    // Join all the async tasks
+    let _ = tokio::join!(http_fut, async {
+        for j in cluster_joins {
+            let _ = j.await;
+        }
+    });
+}
+
+fn parse_cli_and_init_logging() -> (Cli, config::ConfigFile) {
+    // Parse command line input (and configuration file) and initialize logging
+    let (cli, config_file) = config::parse_cli();
     logging::init(cli.verbosity_level());
 
     trace!("Created:\n{:#?}", cli);
 
-    cli
+    (cli, config_file)
 }
 
 fn build_shutdown_token() -> CancellationToken {
@@ -104,14 +187,24 @@ fn build_shutdown_token() -> CancellationToken {
     // when it's time to shutdown, cancels the token and all
     // other holders of a clone will be notified to being shutdown sequence.
     //
-    // NOTE: This handler will be listening on its own dedicated thread.
+    // NOTE: SIGHUP doesn't shutdown, but reloads the configuration (see `config` module).
     let shutdown_token_clone = shutdown_token.clone();
-    if let Err(e) = ctrlc::set_handler(move || {
+    tokio::spawn(async move {
+        let mut sigterm = match signal(SignalKind::terminate()) {
+            Ok(sigterm) => sigterm,
+            Err(e) => {
+                error!("Failed to register signal handler: {e}");
+                return;
+            },
+        };
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {},
+            _ = sigterm.recv() => {},
+        }
         info!("Beginning shutdown...");
         shutdown_token_clone.cancel();
-    }) {
-        error!("Failed to register signal handler: {e}");
-    }
+    });
 
     // Return a CancellationToken that can notify other parts of the system.
     shutdown_token
@@ -0,0 +1,120 @@
+use std::{str::FromStr, time::Duration};
+
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    metrics_service_client::MetricsServiceClient, ExportMetricsServiceRequest,
+};
+use prost::Message;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
+    Client, Url,
+};
+use tonic::{
+    metadata::{MetadataKey, MetadataMap, MetadataValue},
+    transport::{Channel, ClientTlsConfig},
+};
+
+use crate::cli::KVPair;
+
+use super::OtlpProtocol;
+
+/// How long an export can take, before it's considered failed.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CONTENT_TYPE_PROTOBUF: &str = "application/x-protobuf";
+
+/// Pushes [`ExportMetricsServiceRequest`]s to an OTLP endpoint.
+pub enum Exporter {
+    /// OTLP/HTTP, with binary protobuf payload.
+    Http {
+        client: Client,
+        url: Url,
+        headers: HeaderMap,
+    },
+
+    /// OTLP/gRPC.
+    Grpc {
+        client: MetricsServiceClient<Channel>,
+        metadata: MetadataMap,
+    },
+}
+
+impl Exporter {
+    /// Create an [`Exporter`] for the `endpoint`, sending the `headers` with every export.
+    ///
+    /// # Panics
+    ///
+    /// If the `endpoint` or the `headers` are invalid.
+    pub fn new(protocol: OtlpProtocol, endpoint: Url, headers: &[KVPair]) -> Self {
+        match protocol {
+            OtlpProtocol::Http => Exporter::Http {
+                client: Client::builder()
+                    .timeout(EXPORT_TIMEOUT)
+                    .build()
+                    .expect("Failed to build OTLP HTTP client (fatal)"),
+                url: endpoint,
+                headers: headers
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            HeaderName::from_str(k).expect("Invalid OTLP header name"),
+                            HeaderValue::from_str(v).expect("Invalid OTLP header value"),
+                        )
+                    })
+                    .collect(),
+            },
+            OtlpProtocol::Grpc => {
+                let mut channel = Channel::from_shared(endpoint.to_string())
+                    .expect("Invalid OTLP gRPC endpoint")
+                    .timeout(EXPORT_TIMEOUT);
+                if endpoint.scheme() == "https" {
+                    channel = channel
+                        .tls_config(ClientTlsConfig::new().with_webpki_roots())
+                        .expect("Failed to configure TLS for OTLP gRPC endpoint");
+                }
+
+                let mut metadata = MetadataMap::new();
+                for (k, v) in headers {
+                    metadata.insert(
+                        MetadataKey::from_str(&k.to_lowercase()).expect("Invalid OTLP header name"),
+                        MetadataValue::from_str(v).expect("Invalid OTLP header value"),
+                    );
+                }
+
+                Exporter::Grpc {
+                    client: MetricsServiceClient::new(channel.connect_lazy()),
+                    metadata,
+                }
+            },
+        }
+    }
+
+    pub async fn export(&mut self, request: ExportMetricsServiceRequest) -> Result<(), String> {
+        match self {
+            Exporter::Http {
+                client,
+                url,
+                headers,
+            } => {
+                client
+                    .post(url.clone())
+                    .headers(headers.clone())
+                    .header(CONTENT_TYPE, CONTENT_TYPE_PROTOBUF)
+                    .body(request.encode_to_vec())
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| e.to_string())?;
+            },
+            Exporter::Grpc {
+                client,
+                metadata,
+            } => {
+                let mut req = tonic::Request::new(request);
+                *req.metadata_mut() = metadata.clone();
+                client.export(req).await.map_err(|s| s.to_string())?;
+            },
+        }
+
+        Ok(())
+    }
+}
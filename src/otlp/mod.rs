@@ -0,0 +1,102 @@
+//! Push metrics to an [OpenTelemetry](https://opentelemetry.io/) collector, over OTLP.
+//!
+//! Both the bespoke metrics and the ones in the [`prometheus::Registry`] are translated
+//! into OTLP gauges, sums and histograms, and pushed periodically.
+
+// Inner modules
+mod exporter;
+mod translate;
+
+// Imports
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use reqwest::Url;
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::cli::KVPair;
+use crate::http::{render_bespoke_metrics, HttpServiceState};
+use crate::prometheus_metrics::exposition;
+
+use exporter::Exporter;
+use translate::{resource_metrics, Timestamps};
+
+/// Transport protocol of OTLP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum OtlpProtocol {
+    /// OTLP/HTTP, with binary protobuf payload.
+    Http,
+
+    /// OTLP/gRPC.
+    Grpc,
+}
+
+/// Configuration of the OTLP export.
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    /// For [`OtlpProtocol::Http`], the full URL of the metrics endpoint (e.g. `http://collector:4318/v1/metrics`).
+    pub endpoint: Url,
+    pub protocol: OtlpProtocol,
+    pub interval: Duration,
+
+    /// Headers (or gRPC metadata) sent with every export, e.g. for authentication.
+    pub headers: Vec<KVPair>,
+}
+
+/// Spawn the task that periodically pushes all the metrics to the OTLP endpoint.
+///
+/// The metrics are the same served by the HTTP Service, so they are built from its `state`.
+pub fn init(
+    config: OtlpConfig,
+    state: HttpServiceState,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    let mut exporter = Exporter::new(config.protocol, config.endpoint.clone(), &config.headers);
+    let start = unix_nanos();
+    debug!("Initialized, exporting to '{}' via {:?}", config.endpoint, config.protocol);
+
+    tokio::spawn(async move {
+        let mut interval = interval(config.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = shutdown_token.cancelled() => break,
+            }
+
+            let cluster_id = state.cs_reg.get_cluster_id().await;
+            let bespoke = render_bespoke_metrics(&state).await;
+            let request = ExportMetricsServiceRequest {
+                resource_metrics: vec![resource_metrics(
+                    &cluster_id,
+                    &exposition::parse(bespoke.iter().map(String::as_str)),
+                    &state.metrics.gather(),
+                    Timestamps {
+                        start,
+                        now: unix_nanos(),
+                    },
+                )],
+            };
+
+            tokio::select! {
+                res = exporter.export(request) => match res {
+                    Ok(()) => trace!("Exported metrics to '{}'", config.endpoint),
+                    Err(e) => warn!("Failed to export metrics to '{}': {e}", config.endpoint),
+                },
+                _ = shutdown_token.cancelled() => break,
+            }
+        }
+
+        info!("Shutting down");
+    })
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+}
@@ -0,0 +1,263 @@
+use opentelemetry_proto::tonic::{
+    common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue},
+    metrics::v1::{
+        metric::Data, number_data_point, AggregationTemporality, Gauge, Histogram,
+        HistogramDataPoint, Metric, NumberDataPoint, ResourceMetrics, ScopeMetrics, Sum,
+    },
+    resource::v1::Resource,
+};
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+
+use crate::prometheus_metrics::exposition::{Family, FamilyType};
+use crate::prometheus_metrics::LABEL_CLUSTER_ID;
+
+const ATTR_SERVICE_NAME: &str = "service.name";
+const ATTR_SERVICE_VERSION: &str = "service.version";
+
+/// Timestamps of the data points, in nanoseconds since the Unix epoch.
+#[derive(Debug, Clone, Copy)]
+pub struct Timestamps {
+    /// Start of the cumulative counters and histograms: when the exporter started.
+    pub start: u64,
+
+    /// Time of the data points without an explicit timestamp.
+    pub now: u64,
+}
+
+/// Translate the bespoke metric [`Family`]s and the [`Registry`](prometheus::Registry)
+/// [`MetricFamily`]s into OTLP [`ResourceMetrics`].
+///
+/// The `cluster_id` is a resource attribute, so it's dropped from the data points attributes.
+pub fn resource_metrics(
+    cluster_id: &str,
+    bespoke: &[Family],
+    registry: &[MetricFamily],
+    ts: Timestamps,
+) -> ResourceMetrics {
+    let metrics = bespoke
+        .iter()
+        .filter_map(|f| from_family(f, ts))
+        .chain(registry.iter().filter_map(|mf| from_metric_family(mf, ts)))
+        .collect();
+
+    ResourceMetrics {
+        resource: Some(Resource {
+            attributes: vec![
+                key_value(ATTR_SERVICE_NAME, env!("CARGO_PKG_NAME")),
+                key_value(ATTR_SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
+                key_value(LABEL_CLUSTER_ID, cluster_id),
+            ],
+            dropped_attributes_count: 0,
+        }),
+        scope_metrics: vec![ScopeMetrics {
+            scope: Some(InstrumentationScope {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                ..Default::default()
+            }),
+            metrics,
+            schema_url: String::new(),
+        }],
+        schema_url: String::new(),
+    }
+}
+
+fn from_family(family: &Family, ts: Timestamps) -> Option<Metric> {
+    let data_points = family
+        .samples
+        .iter()
+        .map(|s| NumberDataPoint {
+            attributes: attributes(s.labels.iter().map(|(k, v)| (k.as_str(), v.as_str()))),
+            start_time_unix_nano: ts.start,
+            time_unix_nano: s.timestamp_ms.map_or(ts.now, |ms| ms as u64 * 1_000_000),
+            value: Some(number_data_point::Value::AsDouble(s.value)),
+            ..Default::default()
+        })
+        .collect();
+
+    let data = match family.family_type {
+        FamilyType::Counter => Data::Sum(Sum {
+            data_points,
+            aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            is_monotonic: true,
+        }),
+        FamilyType::Gauge | FamilyType::Untyped => Data::Gauge(Gauge {
+            data_points,
+        }),
+        FamilyType::Histogram | FamilyType::Summary => {
+            debug!("Skipping bespoke metric '{}': unsupported type", family.name);
+            return None;
+        },
+    };
+
+    Some(metric(&family.name, &family.help, data))
+}
+
+fn from_metric_family(mf: &MetricFamily, ts: Timestamps) -> Option<Metric> {
+    let number_point = |m: &prometheus::proto::Metric, v: f64| NumberDataPoint {
+        attributes: label_pairs_attributes(m.get_label()),
+        start_time_unix_nano: ts.start,
+        time_unix_nano: ts.now,
+        value: Some(number_data_point::Value::AsDouble(v)),
+        ..Default::default()
+    };
+
+    let data = match mf.get_field_type() {
+        MetricType::COUNTER => Data::Sum(Sum {
+            data_points: mf
+                .get_metric()
+                .iter()
+                .map(|m| number_point(m, m.get_counter().get_value()))
+                .collect(),
+            aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            is_monotonic: true,
+        }),
+        MetricType::GAUGE | MetricType::UNTYPED => Data::Gauge(Gauge {
+            data_points: mf
+                .get_metric()
+                .iter()
+                .map(|m| number_point(m, m.get_gauge().get_value()))
+                .collect(),
+        }),
+        MetricType::HISTOGRAM => Data::Histogram(Histogram {
+            data_points: mf
+                .get_metric()
+                .iter()
+                .map(|m| {
+                    let h = m.get_histogram();
+                    let (explicit_bounds, bucket_counts) = buckets(
+                        h.get_bucket()
+                            .iter()
+                            .map(|b| (b.get_upper_bound(), b.get_cumulative_count())),
+                        h.get_sample_count(),
+                    );
+                    HistogramDataPoint {
+                        attributes: label_pairs_attributes(m.get_label()),
+                        start_time_unix_nano: ts.start,
+                        time_unix_nano: ts.now,
+                        count: h.get_sample_count(),
+                        sum: Some(h.get_sample_sum()),
+                        bucket_counts,
+                        explicit_bounds,
+                        ..Default::default()
+                    }
+                })
+                .collect(),
+            aggregation_temporality: AggregationTemporality::Cumulative as i32,
+        }),
+        MetricType::SUMMARY => {
+            debug!("Skipping metric '{}': unsupported type", mf.get_name());
+            return None;
+        },
+    };
+
+    Some(metric(mf.get_name(), mf.get_help(), data))
+}
+
+/// Turn Prometheus cumulative buckets `(upper_bound, cumulative_count)` into OTLP explicit bounds
+/// and per-bucket counts: OTLP has an implicit `+Inf` bucket, counting what's left up to `count`.
+fn buckets(cumulative: impl Iterator<Item = (f64, u64)>, count: u64) -> (Vec<f64>, Vec<u64>) {
+    let mut bounds = Vec::new();
+    let mut counts = Vec::new();
+    let mut previous = 0;
+    for (upper_bound, cumulative_count) in cumulative.filter(|(ub, _)| ub.is_finite()) {
+        bounds.push(upper_bound);
+        counts.push(cumulative_count.saturating_sub(previous));
+        previous = cumulative_count;
+    }
+    counts.push(count.saturating_sub(previous));
+
+    (bounds, counts)
+}
+
+fn metric(name: &str, help: &str, data: Data) -> Metric {
+    Metric {
+        name: name.to_string(),
+        description: help.to_string(),
+        data: Some(data),
+        ..Default::default()
+    }
+}
+
+fn label_pairs_attributes(labels: &[LabelPair]) -> Vec<KeyValue> {
+    attributes(labels.iter().map(|lp| (lp.get_name(), lp.get_value())))
+}
+
+fn attributes<'a>(labels: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<KeyValue> {
+    labels.filter(|(k, _)| *k != LABEL_CLUSTER_ID).map(|(k, v)| key_value(k, v)).collect()
+}
+
+fn key_value(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prometheus::{Histogram, HistogramOpts, IntCounter, Registry};
+
+    use crate::prometheus_metrics::exposition::parse;
+
+    use super::*;
+
+    const TS: Timestamps = Timestamps {
+        start: 1_000,
+        now: 2_000,
+    };
+
+    #[test]
+    fn translate_bespoke_and_registry() {
+        let bespoke = parse([
+            "# HELP kmtd_lag The lag.",
+            "# TYPE kmtd_lag gauge",
+            r#"kmtd_lag{cluster_id="c",group="g"} 42 3"#,
+            "# HELP kmtd_incidents_total Incidents.",
+            "# TYPE kmtd_incidents_total counter",
+            r#"kmtd_incidents_total{cluster_id="c",group="g"} 1"#,
+        ]);
+
+        let registry = Registry::new();
+        let counter = IntCounter::new("records_total", "Records.").unwrap();
+        let histogram = Histogram::with_opts(
+            HistogramOpts::new("write_ms", "Write time.").buckets(vec![1.0, 10.0]),
+        )
+        .unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+        counter.inc();
+        for v in [0.5, 5.0, 50.0, 60.0] {
+            histogram.observe(v);
+        }
+
+        let rm = resource_metrics("c", &bespoke, &registry.gather(), TS);
+        assert!(rm.resource.unwrap().attributes.contains(&key_value(LABEL_CLUSTER_ID, "c")));
+
+        let metrics = &rm.scope_metrics[0].metrics;
+        assert_eq!(metrics.len(), 4);
+
+        let Some(Data::Gauge(g)) = &metrics[0].data else {
+            panic!("Expected a gauge");
+        };
+        assert_eq!(g.data_points[0].attributes, vec![key_value("group", "g")]);
+        assert_eq!(g.data_points[0].time_unix_nano, 3_000_000);
+        assert_eq!(g.data_points[0].value, Some(number_data_point::Value::AsDouble(42.0)));
+
+        let Some(Data::Sum(s)) = &metrics[1].data else {
+            panic!("Expected a sum");
+        };
+        assert!(s.is_monotonic);
+        assert_eq!(s.data_points[0].time_unix_nano, TS.now);
+
+        let Some(Data::Histogram(h)) = &metrics.iter().find(|m| m.name == "write_ms").unwrap().data
+        else {
+            panic!("Expected a histogram");
+        };
+        assert_eq!(h.data_points[0].explicit_bounds, vec![1.0, 10.0]);
+        assert_eq!(h.data_points[0].bucket_counts, vec![1, 1, 2]);
+        assert_eq!(h.data_points[0].count, 4);
+    }
+}
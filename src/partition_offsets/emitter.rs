@@ -8,7 +8,7 @@ use prometheus::{
 use rdkafka::{admin::AdminClient, client::DefaultClientContext, ClientConfig};
 use tokio::{
     sync::mpsc,
-    task::JoinHandle,
+    task::{block_in_place, JoinHandle},
     time::{interval, Duration},
 };
 use tokio_util::sync::CancellationToken;
@@ -129,8 +129,9 @@ impl Emitter for PartitionOffsetsEmitter {
                         // Fetch Partition Watermarks and update timer metrics
                         let timer =
                             metric_cg_fetch.with_label_values(&[&t, &p.to_string()]).start_timer();
-                        let res_watermarks =
-                            admin_client.inner().fetch_watermarks(&t, p as i32, FETCH_TIMEOUT);
+                        let res_watermarks = block_in_place(|| {
+                            admin_client.inner().fetch_watermarks(&t, p as i32, FETCH_TIMEOUT)
+                        });
                         timer.observe_duration();
 
                         match res_watermarks {
@@ -257,6 +257,24 @@ impl PartitionLagEstimator {
         self.latest_tracked_offset().map(|ko| ko.offset)
     }
 
+    /// Iterate over all the [`TrackedOffset`]s, from the earliest to the latest.
+    pub fn tracked_offsets(&self) -> impl Iterator<Item = &TrackedOffset> {
+        self.latest_tracked_offsets.iter()
+    }
+
+    /// Estimate the rate at which offsets are produced (per second), across all the [`TrackedOffset`]s.
+    pub fn estimate_produce_rate(&self) -> PartitionOffsetsResult<f64> {
+        let earliest = self.earliest_tracked_offset()?;
+        let latest = self.latest_tracked_offset()?;
+
+        let elapsed_ms = (latest.at - earliest.at).num_milliseconds();
+        if elapsed_ms <= 0 {
+            return Err(PartitionOffsetsError::LagEstimatorNotReady);
+        }
+
+        Ok(latest.offset.saturating_sub(earliest.offset) as f64 * 1000.0 / elapsed_ms as f64)
+    }
+
     /// Get a reference to the earliest [`TrackedOffset`].
     pub fn earliest_tracked_offset(&self) -> PartitionOffsetsResult<&TrackedOffset> {
         self.latest_tracked_offsets.front().ok_or(PartitionOffsetsError::LagEstimatorNotReady)
@@ -441,6 +459,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn estimate_produce_rate() {
+        let (off, ts) = example_tracked_offsets();
+
+        // Setup estimator with example input
+        let mut estimator = PartitionLagEstimator::new(10);
+        assert!(estimator.estimate_produce_rate().is_err());
+        for (idx, offset) in off.iter().enumerate() {
+            estimator.update(10, *offset, utc_from_ms(ts[idx]).unwrap());
+        }
+
+        // 1213 offsets produced in 1200 seconds
+        let rate = estimator.estimate_produce_rate().unwrap();
+        assert!((rate - 1213.0 / 1200.0).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn discard_old_tracked_offsets() {
         let mut estimator = PartitionLagEstimator::new(5);
@@ -7,7 +7,8 @@ mod tracked_offset;
 
 // Exports
 pub use emitter::PartitionOffsetsEmitter;
-pub use register::PartitionOffsetsRegister;
+pub use register::{PartitionOffsetsHistory, PartitionOffsetsRegister};
+pub use tracked_offset::TrackedOffset;
 
 // Imports
 use prometheus::Registry;
@@ -2,6 +2,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use chrono::{DateTime, Duration, Utc};
 use prometheus::{register_int_gauge_vec_with_registry, IntGaugeVec, Registry};
+use serde::{Deserialize, Serialize};
 use tokio::sync::{mpsc::Receiver, RwLock};
 
 use super::emitter::PartitionOffset;
@@ -10,17 +11,29 @@ use super::lag_estimator::PartitionLagEstimator;
 
 use crate::internals::Awaitable;
 use crate::kafka_types::TopicPartition;
-use crate::partition_offsets::tracked_offset::TrackedOffset;
+use crate::partition_offsets::TrackedOffset;
 use crate::prometheus_metrics::{LABEL_PARTITION, LABEL_TOPIC};
 
 const MET_USAGE_NAME: &str = "partition_offsets_register_usage";
 const MET_USAGE_HELP: &str = "Amount of offsets tracked per topic partition";
 
+/// History of the offsets of a [`TopicPartition`], as tracked by its [`PartitionLagEstimator`].
+///
+/// This is what is needed to rebuild a [`PartitionLagEstimator`] from scratch.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PartitionOffsetsHistory {
+    #[serde(flatten)]
+    pub topic_partition: TopicPartition,
+    pub earliest_available_offset: Option<u64>,
+    pub tracked_offsets: Vec<TrackedOffset>,
+}
+
 /// Holds the offset of all Topic Partitions in the Kafka Cluster, and can estimate lag of Consumers.
 ///
 /// This is where a tracked Consumer Group, at a tracked offset in time, can get it's lag estimated.
 pub struct PartitionOffsetsRegister {
     estimators: Arc<RwLock<HashMap<TopicPartition, RwLock<PartitionLagEstimator>>>>,
+    offsets_history: usize,
     ready_at: f64,
 
     // Prometheus Metrics
@@ -47,6 +60,7 @@ impl PartitionOffsetsRegister {
     ) -> Self {
         let por = Self {
             estimators: Arc::new(RwLock::new(HashMap::new())),
+            offsets_history,
             ready_at,
             metric_usage: register_int_gauge_vec_with_registry!(
                 MET_USAGE_NAME,
@@ -175,6 +189,28 @@ impl PartitionOffsetsRegister {
             .estimate_time_lag(consumed_offset, consumed_offset_datetime)
     }
 
+    /// Estimate the rate at which offsets are produced (per second) to specific [`TopicPartition`].
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_partition` - Topic Partition we want to know the produce rate of
+    pub async fn estimate_produce_rate(
+        &self,
+        topic_partition: &TopicPartition,
+    ) -> PartitionOffsetsResult<f64> {
+        self.estimators
+            .read()
+            .await
+            .get(topic_partition)
+            .ok_or(PartitionOffsetsError::LagEstimatorNotFound(
+                topic_partition.topic.to_string(),
+                topic_partition.partition,
+            ))?
+            .read()
+            .await
+            .estimate_produce_rate()
+    }
+
     /// Get the earliest tracked offset of specific [`TopicPartition`].
     ///
     /// # Arguments
@@ -261,6 +297,72 @@ impl PartitionOffsetsRegister {
             .latest_available_offset()
     }
 
+    /// Export the [`PartitionOffsetsHistory`] of every [`TopicPartition`] tracked by the register.
+    pub async fn export_history(&self) -> Vec<PartitionOffsetsHistory> {
+        let r_guard = self.estimators.read().await;
+
+        let mut res = Vec::with_capacity(r_guard.len());
+        for (tp, est_rwlock) in r_guard.iter() {
+            let est = est_rwlock.read().await;
+            res.push(PartitionOffsetsHistory {
+                topic_partition: tp.clone(),
+                earliest_available_offset: est.earliest_available_offset().ok(),
+                tracked_offsets: est.tracked_offsets().cloned().collect(),
+            });
+        }
+
+        res
+    }
+
+    /// Restore previously exported [`PartitionOffsetsHistory`].
+    ///
+    /// For each [`TopicPartition`], a new [`PartitionLagEstimator`] is built out of the restored
+    /// history; then, any offset tracked so far is applied on top of it: this way, restored data
+    /// can only precede what was tracked after launch.
+    ///
+    /// Returns the amount of [`TopicPartition`] restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - [`PartitionOffsetsHistory`]s to restore, usually read from a snapshot
+    pub async fn restore_history(&self, history: Vec<PartitionOffsetsHistory>) -> usize {
+        let mut w_guard = self.estimators.write().await;
+
+        let mut restored = 0;
+        for poh in history.into_iter() {
+            // Without an earliest available offset there is nothing meaningful to restore
+            let Some(earliest_available) = poh.earliest_available_offset else {
+                continue;
+            };
+
+            let mut est = PartitionLagEstimator::new(self.offsets_history);
+            for to in poh.tracked_offsets.into_iter() {
+                est.update(earliest_available, to.offset, to.at);
+            }
+
+            // Re-apply what was tracked since launch, if anything
+            if let Some(live_rwlock) = w_guard.get(&poh.topic_partition) {
+                let live = live_rwlock.read().await;
+                let live_earliest_available =
+                    live.earliest_available_offset().unwrap_or(earliest_available);
+                for to in live.tracked_offsets() {
+                    est.update(live_earliest_available, to.offset, to.at);
+                }
+            }
+
+            self.metric_usage
+                .with_label_values(&[
+                    &poh.topic_partition.topic,
+                    &poh.topic_partition.partition.to_string(),
+                ])
+                .set(est.usage() as i64);
+            w_guard.insert(poh.topic_partition, RwLock::new(est));
+            restored += 1;
+        }
+
+        restored
+    }
+
     /// Get some basic registry usage stats.
     ///
     /// Returns the usage of the internal [`PartitionLagEstimator`]s, as `(min, max, avg, count)` tuple.
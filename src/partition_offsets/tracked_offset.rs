@@ -1,12 +1,13 @@
 use std::cmp::Ordering;
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 /// An Offset in a Topic Partition, and the date-time at which it is tracked.
 ///
 /// This is used to represent concepts like
 /// "the timestamp at which a Topic Partition offset was produced".
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Serialize, Deserialize)]
 pub struct TrackedOffset {
     pub offset: u64,
     pub at: DateTime<Utc>,
@@ -0,0 +1,41 @@
+use const_format::formatcp;
+
+use crate::lag_register::LagAggregate;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_lag_offset_sum");
+const HELP: &str =
+    formatcp!("{HEADER_HELP} {NAME} The sum of the difference (lag) between the last produced offset and the last consumed offset, across the partitions consumed by the consumer group. NOTE: omitted if no partition lag is known.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    agg: &LagAggregate,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    // Without any known partition lag, there is nothing to aggregate
+    if agg.partitions_with_lag == 0 {
+        return;
+    }
+
+    let value = agg.offset_lag_sum;
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
@@ -0,0 +1,36 @@
+use const_format::formatcp;
+
+use crate::lag_register::LagAggregate;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_partitions_unowned");
+const HELP: &str =
+    formatcp!("{HEADER_HELP} {NAME} The amount of partitions consumed by the consumer group, not owned by any member of the consumer group.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    agg: &LagAggregate,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    let value = agg.partitions_unowned;
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
@@ -0,0 +1,39 @@
+use const_format::formatcp;
+
+use crate::slo::SloStatus;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_SLO, LABEL_WINDOW, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_slo_burn_rate");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} How fast the consumer group is consuming the error budget of the service level objective, over the window: 1 means it would be exactly consumed by the end of the objective window. NOTE: omitted for windows without lag updates.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    status: &SloStatus,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    let group = &status.group;
+    let slo = &status.slo;
+
+    for (window, value) in status.burn_rates.iter() {
+        res.push(format!(
+            "{NAME}\
+            {{\
+                {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+                {LABEL_GROUP}=\"{group}\",\
+                {LABEL_SLO}=\"{slo}\",\
+                {LABEL_WINDOW}=\"{window}\"\
+                {extra_labels}\
+            }} \
+            {value}"
+        ));
+    }
+}
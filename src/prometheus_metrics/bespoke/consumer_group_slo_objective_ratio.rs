@@ -0,0 +1,37 @@
+use const_format::formatcp;
+
+use crate::slo::SloStatus;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_SLO, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_slo_objective_ratio");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The ratio (between 0 and 1) of lag updates of the consumer group that must be within the service level objective.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    status: &SloStatus,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    let group = &status.group;
+    let slo = &status.slo;
+    let value = status.target;
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\",\
+            {LABEL_SLO}=\"{slo}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
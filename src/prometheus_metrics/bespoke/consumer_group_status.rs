@@ -0,0 +1,35 @@
+use const_format::formatcp;
+
+use crate::consumer_status::ConsumerStatus;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_status");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The worst status across the partitions consumed by the consumer group. NOTE: '0=NOTFOUND, 1=OK, 2=WARN, 3=ERR, 4=STOP, 5=STALL'.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    status: ConsumerStatus,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    let value = status.code();
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
@@ -0,0 +1,35 @@
+use const_format::formatcp;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_COUNTER};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_group_topic_data_loss_incidents_total");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The amount of times a partition of the topic consumed by the consumer group had offsets deleted before they were consumed. NOTE: omitted until the first incident.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_COUNTER}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    topic: &str,
+    incidents: u64,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    let value = incidents;
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\",\
+            {LABEL_TOPIC}=\"{topic}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
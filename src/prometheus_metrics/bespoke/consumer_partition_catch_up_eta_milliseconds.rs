@@ -0,0 +1,42 @@
+use const_format::formatcp;
+
+use crate::consumer_rates::PartitionRates;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_partition_catch_up_eta_milliseconds");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The estimated time for the lag of the consumer group on the topic partition to reach zero, given the current produce and consume rates, expressed in milliseconds. NOTE: '-1' means 'never', as the consumer is slower than the producers; omitted if unknown.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    topic: &str,
+    partition: u32,
+    extra_labels: &str,
+    rates: &PartitionRates,
+    res: &mut Vec<String>,
+) {
+    let Some(eta) = rates.catch_up_eta else {
+        return;
+    };
+    let value = eta.as_millis();
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\",\
+            {LABEL_TOPIC}=\"{topic}\",\
+            {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
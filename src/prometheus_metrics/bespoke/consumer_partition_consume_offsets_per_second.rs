@@ -0,0 +1,41 @@
+use const_format::formatcp;
+
+use crate::consumer_rates::PartitionRates;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_partition_consume_offsets_per_second");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The rate at which the consumer group consumes the topic partition, estimated from its most recent commits, expressed in offsets per second. NOTE: omitted if unknown.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    topic: &str,
+    partition: u32,
+    extra_labels: &str,
+    rates: &PartitionRates,
+    res: &mut Vec<String>,
+) {
+    let Some(value) = rates.consume_rate else {
+        return;
+    };
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\",\
+            {LABEL_TOPIC}=\"{topic}\",\
+            {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
@@ -0,0 +1,39 @@
+use const_format::formatcp;
+
+use crate::data_loss::DataLoss;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_partition_data_loss_offsets");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The amount of offsets deleted from the topic partition before the consumer group consumed them, as its committed offset is behind the earliest available offset. NOTE: omitted if no data loss is ongoing.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    data_loss: &DataLoss,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    let group = &data_loss.group;
+    let topic = &data_loss.topic_partition.topic;
+    let partition = data_loss.topic_partition.partition;
+    let value = data_loss.offsets_lost;
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\",\
+            {LABEL_TOPIC}=\"{topic}\",\
+            {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
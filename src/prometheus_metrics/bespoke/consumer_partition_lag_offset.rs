@@ -1,13 +1,9 @@
 use const_format::formatcp;
 
-use crate::kafka_types::Member;
 use crate::lag_register::Lag;
 
-use super::super::{
-    LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_MEMBER_CLIENT_ID, LABEL_MEMBER_HOST, LABEL_MEMBER_ID,
-    LABEL_PARTITION, LABEL_TOPIC, NAMESPACE,
-};
-use super::{normalize_owner_data, HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
 
 const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_partition_lag_offset");
 const HELP: &str =
@@ -24,12 +20,10 @@ pub(crate) fn append_metric(
     group: &str,
     topic: &str,
     partition: u32,
-    owner: Option<&Member>,
+    extra_labels: &str,
     lag: Option<&Lag>,
     res: &mut Vec<String>,
 ) {
-    let (member_id, member_host, member_client_id) = normalize_owner_data(owner);
-
     let value_and_ts = if let Some(l) = lag {
         format!("{} {}", l.offset_lag, l.offset_timestamp.timestamp_millis())
     } else {
@@ -42,10 +36,8 @@ pub(crate) fn append_metric(
             {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
             {LABEL_GROUP}=\"{group}\",\
             {LABEL_TOPIC}=\"{topic}\",\
-            {LABEL_PARTITION}=\"{partition}\",\
-            {LABEL_MEMBER_ID}=\"{member_id}\",\
-            {LABEL_MEMBER_HOST}=\"{member_host}\",\
-            {LABEL_MEMBER_CLIENT_ID}=\"{member_client_id}\"\
+            {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
         }} \
         {value_and_ts}"
     ));
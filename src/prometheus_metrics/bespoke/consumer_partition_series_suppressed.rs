@@ -0,0 +1,29 @@
+use const_format::formatcp;
+
+use super::super::{LABEL_CLUSTER_ID, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_partition_series_suppressed");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The amount of consumed topic partitions whose consumer partition metrics are suppressed, because of the consumer partitions limit.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    suppressed: usize,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\"\
+            {extra_labels}\
+        }} \
+        {suppressed}"
+    ));
+}
@@ -0,0 +1,39 @@
+use const_format::formatcp;
+
+use crate::consumer_status::ConsumerStatus;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_partition_status");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The status of the consumer of the topic partition, evaluated over the window of its most recent commits. NOTE: '0=NOTFOUND, 1=OK, 2=WARN, 3=ERR, 4=STOP, 5=STALL'.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    topic: &str,
+    partition: u32,
+    extra_labels: &str,
+    status: ConsumerStatus,
+    res: &mut Vec<String>,
+) {
+    let value = status.code();
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\",\
+            {LABEL_TOPIC}=\"{topic}\",\
+            {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
@@ -0,0 +1,38 @@
+use chrono::Duration;
+use const_format::formatcp;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_GROUP, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_consumer_partition_time_to_data_loss_milliseconds");
+const HELP: &str = formatcp!("{HEADER_HELP} {NAME} The estimated time until retention deletes messages of the topic partition not yet consumed by the consumer group, based on the topic time retention (retention.ms), expressed in milliseconds. NOTE: omitted for compact-only topics, unlimited time retention or no lag; '0' means data is being lost.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    group: &str,
+    topic: &str,
+    partition: u32,
+    extra_labels: &str,
+    time_to_data_loss: Duration,
+    res: &mut Vec<String>,
+) {
+    let value = time_to_data_loss.num_milliseconds();
+
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_GROUP}=\"{group}\",\
+            {LABEL_TOPIC}=\"{topic}\",\
+            {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
+        }} \
+        {value}"
+    ));
+}
@@ -1,22 +1,55 @@
+pub mod consumer_group_lag_milliseconds_max;
+pub mod consumer_group_lag_offset_sum;
+pub mod consumer_group_partitions_lagging;
+pub mod consumer_group_partitions_owned;
+pub mod consumer_group_partitions_unowned;
+pub mod consumer_group_slo_burn_rate;
+pub mod consumer_group_slo_error_budget_remaining_ratio;
+pub mod consumer_group_slo_events_good_total;
+pub mod consumer_group_slo_events_total;
+pub mod consumer_group_slo_objective_ratio;
+pub mod consumer_group_status;
+pub mod consumer_group_topic_data_loss_incidents_total;
+pub mod consumer_group_topic_lag_milliseconds_max;
+pub mod consumer_group_topic_lag_offset_sum;
+pub mod consumer_group_topic_partitions_lagging;
+pub mod consumer_group_topic_partitions_owned;
+pub mod consumer_group_topic_partitions_unowned;
+pub mod consumer_partition_catch_up_eta_milliseconds;
+pub mod consumer_partition_consume_offsets_per_second;
+pub mod consumer_partition_data_loss_offsets;
 pub mod consumer_partition_lag_milliseconds;
 pub mod consumer_partition_lag_offset;
 pub mod consumer_partition_offset;
+pub mod consumer_partition_series_suppressed;
+pub mod consumer_partition_status;
+pub mod consumer_partition_time_to_data_loss_milliseconds;
 pub mod partition_earliest_available_offset;
 pub mod partition_earliest_tracked_offset;
 pub mod partition_latest_available_offset;
 pub mod partition_latest_tracked_offset;
+pub mod partition_produce_offsets_per_second;
 
-use crate::kafka_types::Member;
-use crate::lag_register::{Lag, LagRegister};
+use std::collections::HashMap;
 
-use super::UNKNOWN_VAL;
+use chrono::Duration;
 
-#[allow(unused)]
-const TYPE_COUNTER: &str = "counter";
-const TYPE_GAUGE: &str = "gauge";
+use crate::consumer_rates::PartitionRates;
+use crate::consumer_status::GroupStatus;
+use crate::kafka_types::{Member, TopicPartition};
+use crate::labels_mapping::{render_labels, LabelsMapping};
+use crate::lag_register::{GroupWithLag, Lag, LagAggregate, LagWithOwner};
 
-const HEADER_HELP: &str = "# HELP";
-const HEADER_TYPE: &str = "# TYPE";
+use super::{
+    OwnerLabels, SeriesDropPolicy, LABEL_MEMBER_CLIENT_ID, LABEL_MEMBER_HOST, LABEL_MEMBER_ID,
+    UNKNOWN_VAL,
+};
+
+pub(super) const TYPE_COUNTER: &str = "counter";
+pub(super) const TYPE_GAUGE: &str = "gauge";
+
+pub(crate) const HEADER_HELP: &str = "# HELP";
+pub(super) const HEADER_TYPE: &str = "# TYPE";
 
 fn normalize_owner_data(opt_owner: Option<&Member>) -> (&str, &str, &str) {
     if let Some(o) = opt_owner {
@@ -26,34 +59,413 @@ fn normalize_owner_data(opt_owner: Option<&Member>) -> (&str, &str, &str) {
     }
 }
 
-type IterLagRegisterFn = fn(
+/// Renders the owner labels, at the given granularity, as a fragment to append to the other labels.
+fn render_owner_labels(opt_owner: Option<&Member>, granularity: OwnerLabels) -> String {
+    let (member_id, member_host, member_client_id) = normalize_owner_data(opt_owner);
+
+    match granularity {
+        OwnerLabels::Full => format!(
+            ",{LABEL_MEMBER_ID}=\"{member_id}\",\
+            {LABEL_MEMBER_HOST}=\"{member_host}\",\
+            {LABEL_MEMBER_CLIENT_ID}=\"{member_client_id}\""
+        ),
+        OwnerLabels::HostClientId => format!(
+            ",{LABEL_MEMBER_HOST}=\"{member_host}\",\
+            {LABEL_MEMBER_CLIENT_ID}=\"{member_client_id}\""
+        ),
+        OwnerLabels::None => String::new(),
+    }
+}
+
+/// A Topic Partition consumed by a Consumer Group, selected to be rendered as consumer partition metrics.
+pub struct ConsumerPartition<'a> {
+    pub(super) group: &'a str,
+    pub(super) tp: &'a TopicPartition,
+    pub(super) lwo: &'a LagWithOwner,
+    pub(super) extra_labels: String,
+}
+
+/// Selects the [`ConsumerPartition`]s to render, enforcing the (optional) limit of consumed Topic Partitions.
+///
+/// When the limit is exceeded, the [`SeriesDropPolicy`] decides which ones are dropped.
+/// Each selected [`ConsumerPartition`] gets its owner labels, and labels from the [`LabelsMapping`].
+/// Returns the selected [`ConsumerPartition`]s, and the amount that was suppressed.
+pub fn select_consumer_partitions<'a>(
+    lag_by_group: &'a HashMap<String, GroupWithLag>,
+    owner_labels: OwnerLabels,
+    mapping: &LabelsMapping,
+    partitions_limit: Option<usize>,
+    drop_policy: SeriesDropPolicy,
+) -> (Vec<ConsumerPartition<'a>>, usize) {
+    let mut selected: Vec<(&str, &TopicPartition, &LagWithOwner)> = lag_by_group
+        .iter()
+        .flat_map(|(g, gwl)| {
+            gwl.lag_by_topic_partition.iter().map(move |(tp, lwo)| (g.as_str(), tp, lwo))
+        })
+        .collect();
+
+    let mut suppressed = 0;
+    if let Some(limit) = partitions_limit {
+        if selected.len() > limit {
+            match drop_policy {
+                // Ties are broken by name, so the same series are kept across scrapes
+                SeriesDropPolicy::LowestLagFirst => selected.sort_by_key(|(g, tp, lwo)| {
+                    (std::cmp::Reverse(lwo.lag.as_ref().map(|l| l.offset_lag)), *g, *tp)
+                }),
+                SeriesDropPolicy::LastByName => selected.sort_by_key(|(g, tp, _)| (*g, *tp)),
+            }
+
+            suppressed = selected.len() - limit;
+            selected.truncate(limit);
+        }
+    }
+
+    let mut mapped_labels: HashMap<(&str, &str), String> = HashMap::new();
+    let selected = selected
+        .into_iter()
+        .map(|(group, tp, lwo)| {
+            let mapped = mapped_labels
+                .entry((group, tp.topic.as_str()))
+                .or_insert_with(|| render_labels(&mapping.group_topic_labels(group, &tp.topic)));
+
+            ConsumerPartition {
+                group,
+                tp,
+                lwo,
+                extra_labels: render_owner_labels(lwo.owner.as_ref(), owner_labels) + mapped,
+            }
+        })
+        .collect();
+
+    (selected, suppressed)
+}
+
+type IterConsumerPartitionsFn = fn(
     cluster_id: &str,
     group: &str,
     topic: &str,
     partition: u32,
-    owner: Option<&Member>,
+    extra_labels: &str,
     lag: Option<&Lag>,
     res: &mut Vec<String>,
 );
 
-/// Helper to iterate over the content of a [`LagRegister`], to apply a given [`IterLagRegisterFn`].
-pub async fn iter_lag_reg(
-    lag_reg: &LagRegister,
+/// Helper to iterate over selected [`ConsumerPartition`]s, to apply a given [`IterConsumerPartitionsFn`].
+pub fn iter_consumer_partitions(
+    consumer_partitions: &[ConsumerPartition],
     metrics_vec: &mut Vec<String>,
     cluster_id: &str,
-    ilrf: IterLagRegisterFn,
+    icpf: IterConsumerPartitionsFn,
 ) {
-    for (g, gwl) in lag_reg.lag_by_group.read().await.iter() {
-        for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
-            ilrf(
-                cluster_id,
-                g,
-                tp.topic.as_ref(),
-                tp.partition,
-                lwo.owner.as_ref(),
-                lwo.lag.as_ref(),
-                metrics_vec,
+    for cp in consumer_partitions {
+        icpf(
+            cluster_id,
+            cp.group,
+            cp.tp.topic.as_ref(),
+            cp.tp.partition,
+            cp.extra_labels.as_ref(),
+            cp.lwo.lag.as_ref(),
+            metrics_vec,
+        );
+    }
+}
+
+/// Helper to iterate over selected [`ConsumerPartition`]s, to append their `consumer_partition_status` metric.
+pub fn iter_consumer_partitions_status(
+    consumer_partitions: &[ConsumerPartition],
+    metrics_vec: &mut Vec<String>,
+    cluster_id: &str,
+    group_statuses: &HashMap<String, GroupStatus>,
+) {
+    for cp in consumer_partitions {
+        let status = group_statuses
+            .get(cp.group)
+            .and_then(|gs| gs.partitions.get(cp.tp))
+            .copied()
+            .unwrap_or_default();
+
+        consumer_partition_status::append_metric(
+            cluster_id,
+            cp.group,
+            cp.tp.topic.as_ref(),
+            cp.tp.partition,
+            cp.extra_labels.as_ref(),
+            status,
+            metrics_vec,
+        );
+    }
+}
+
+/// Helper to iterate over selected [`ConsumerPartition`]s, to append their `consumer_partition_time_to_data_loss_milliseconds` metric.
+///
+/// [`ConsumerPartition`]s without a prediction are skipped.
+pub fn iter_consumer_partitions_time_to_data_loss(
+    consumer_partitions: &[ConsumerPartition],
+    metrics_vec: &mut Vec<String>,
+    cluster_id: &str,
+    predictions: &HashMap<(&str, &TopicPartition), Duration>,
+) {
+    for cp in consumer_partitions {
+        let Some(ttdl) = predictions.get(&(cp.group, cp.tp)) else {
+            continue;
+        };
+
+        consumer_partition_time_to_data_loss_milliseconds::append_metric(
+            cluster_id,
+            cp.group,
+            cp.tp.topic.as_ref(),
+            cp.tp.partition,
+            cp.extra_labels.as_ref(),
+            *ttdl,
+            metrics_vec,
+        );
+    }
+}
+
+type IterConsumerPartitionsRatesFn = fn(
+    cluster_id: &str,
+    group: &str,
+    topic: &str,
+    partition: u32,
+    extra_labels: &str,
+    rates: &PartitionRates,
+    res: &mut Vec<String>,
+);
+
+/// Helper to iterate over selected [`ConsumerPartition`]s and their [`PartitionRates`], to apply a given [`IterConsumerPartitionsRatesFn`].
+///
+/// [`ConsumerPartition`]s without [`PartitionRates`] are skipped.
+pub fn iter_consumer_partitions_rates(
+    consumer_partitions: &[ConsumerPartition],
+    metrics_vec: &mut Vec<String>,
+    cluster_id: &str,
+    rates: &HashMap<(&str, &TopicPartition), PartitionRates>,
+    icprf: IterConsumerPartitionsRatesFn,
+) {
+    for cp in consumer_partitions {
+        let Some(r) = rates.get(&(cp.group, cp.tp)) else {
+            continue;
+        };
+
+        icprf(
+            cluster_id,
+            cp.group,
+            cp.tp.topic.as_ref(),
+            cp.tp.partition,
+            cp.extra_labels.as_ref(),
+            r,
+            metrics_vec,
+        );
+    }
+}
+
+/// A Consumer Group, with its [`LagAggregate`] and labels from the [`LabelsMapping`].
+pub struct GroupAggregate<'a> {
+    group: &'a str,
+    agg: LagAggregate,
+    extra_labels: String,
+}
+
+/// Aggregates the lag of each Consumer Group, once for all the per-group metrics.
+pub fn aggregate_groups<'a>(
+    lag_by_group: &'a HashMap<String, GroupWithLag>,
+    mapping: &LabelsMapping,
+) -> Vec<GroupAggregate<'a>> {
+    lag_by_group
+        .iter()
+        .map(|(g, gwl)| GroupAggregate {
+            group: g,
+            agg: gwl.aggregate(),
+            extra_labels: render_labels(&mapping.group_labels(g)),
+        })
+        .collect()
+}
+
+/// A Topic consumed by a Consumer Group, with its [`LagAggregate`] and labels from the [`LabelsMapping`].
+pub struct GroupTopicAggregate<'a> {
+    group: &'a str,
+    topic: &'a str,
+    agg: LagAggregate,
+    extra_labels: String,
+}
+
+/// Aggregates the lag of each Topic consumed by each Consumer Group, once for all the per-group-and-topic metrics.
+pub fn aggregate_group_topics<'a>(
+    lag_by_group: &'a HashMap<String, GroupWithLag>,
+    mapping: &LabelsMapping,
+) -> Vec<GroupTopicAggregate<'a>> {
+    lag_by_group
+        .iter()
+        .flat_map(|(g, gwl)| {
+            gwl.aggregate_by_topic().into_iter().map(move |(t, agg)| GroupTopicAggregate {
+                group: g,
+                topic: t,
+                agg,
+                extra_labels: render_labels(&mapping.group_topic_labels(g, t)),
+            })
+        })
+        .collect()
+}
+
+type IterGroupAggregateFn = fn(
+    cluster_id: &str,
+    group: &str,
+    agg: &LagAggregate,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+);
+
+/// Helper to iterate over [`GroupAggregate`]s, to apply a given [`IterGroupAggregateFn`].
+pub fn iter_group_aggregates(
+    aggregates: &[GroupAggregate],
+    metrics_vec: &mut Vec<String>,
+    cluster_id: &str,
+    igaf: IterGroupAggregateFn,
+) {
+    for ga in aggregates {
+        igaf(cluster_id, ga.group, &ga.agg, &ga.extra_labels, metrics_vec);
+    }
+}
+
+type IterGroupTopicAggregateFn = fn(
+    cluster_id: &str,
+    group: &str,
+    topic: &str,
+    agg: &LagAggregate,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+);
+
+/// Helper to iterate over [`GroupTopicAggregate`]s, to apply a given [`IterGroupTopicAggregateFn`].
+pub fn iter_group_topic_aggregates(
+    aggregates: &[GroupTopicAggregate],
+    metrics_vec: &mut Vec<String>,
+    cluster_id: &str,
+    igtaf: IterGroupTopicAggregateFn,
+) {
+    for gta in aggregates {
+        igtaf(cluster_id, gta.group, gta.topic, &gta.agg, &gta.extra_labels, metrics_vec);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Consumer Groups `a` and `b`, each consuming partitions 0 and 1 of Topic `t`,
+    /// lagging by the given offsets.
+    fn lag_by_group(lags: [(&str, u32, u64); 4]) -> HashMap<String, GroupWithLag> {
+        let mut lag_by_group: HashMap<String, GroupWithLag> = HashMap::new();
+        for (group, partition, offset_lag) in lags {
+            let lwo = LagWithOwner {
+                lag: Some(Lag {
+                    offset: 0,
+                    offset_timestamp: Default::default(),
+                    offset_lag,
+                    time_lag: Duration::zero(),
+                }),
+                ..Default::default()
+            };
+            lag_by_group
+                .entry(group.to_string())
+                .or_default()
+                .lag_by_topic_partition
+                .insert(TopicPartition::new("t".to_string(), partition), lwo);
+        }
+        lag_by_group
+    }
+
+    fn select(
+        lag_by_group: &HashMap<String, GroupWithLag>,
+        partitions_limit: Option<usize>,
+        drop_policy: SeriesDropPolicy,
+    ) -> (Vec<(String, u32)>, usize) {
+        let (selected, suppressed) = select_consumer_partitions(
+            lag_by_group,
+            OwnerLabels::None,
+            &LabelsMapping::default(),
+            partitions_limit,
+            drop_policy,
+        );
+        let mut selected: Vec<(String, u32)> =
+            selected.iter().map(|cp| (cp.group.to_string(), cp.tp.partition)).collect();
+        selected.sort();
+        (selected, suppressed)
+    }
+
+    fn series(s: &[(&str, u32)]) -> Vec<(String, u32)> {
+        s.iter().map(|(g, p)| (g.to_string(), *p)).collect()
+    }
+
+    #[test]
+    fn select_within_limit() {
+        let lbg = lag_by_group([("a", 0, 1), ("a", 1, 2), ("b", 0, 3), ("b", 1, 4)]);
+        let all = series(&[("a", 0), ("a", 1), ("b", 0), ("b", 1)]);
+
+        assert_eq!(select(&lbg, None, SeriesDropPolicy::LowestLagFirst), (all.clone(), 0));
+        assert_eq!(select(&lbg, Some(4), SeriesDropPolicy::LowestLagFirst), (all.clone(), 0));
+        assert_eq!(select(&lbg, Some(4), SeriesDropPolicy::LastByName), (all, 0));
+    }
+
+    #[test]
+    fn drop_lowest_lag_first() {
+        let lbg = lag_by_group([("a", 0, 1), ("a", 1, 20), ("b", 0, 30), ("b", 1, 4)]);
+
+        assert_eq!(
+            select(&lbg, Some(3), SeriesDropPolicy::LowestLagFirst),
+            (series(&[("a", 1), ("b", 0), ("b", 1)]), 1)
+        );
+        assert_eq!(
+            select(&lbg, Some(1), SeriesDropPolicy::LowestLagFirst),
+            (series(&[("b", 0)]), 3)
+        );
+        assert_eq!(select(&lbg, Some(0), SeriesDropPolicy::LowestLagFirst), (vec![], 4));
+    }
+
+    #[test]
+    fn drop_lowest_lag_first_breaks_ties_by_name() {
+        // Each map is seeded differently: the outcome must not depend on its iteration order
+        for _ in 0..10 {
+            let lbg = lag_by_group([("a", 0, 5), ("a", 1, 5), ("b", 0, 5), ("b", 1, 9)]);
+            assert_eq!(
+                select(&lbg, Some(2), SeriesDropPolicy::LowestLagFirst),
+                (series(&[("a", 0), ("b", 1)]), 2)
+            );
+            assert_eq!(
+                select(&lbg, Some(3), SeriesDropPolicy::LowestLagFirst),
+                (series(&[("a", 0), ("a", 1), ("b", 1)]), 1)
             );
         }
     }
+
+    #[test]
+    fn drop_last_by_name() {
+        let lbg = lag_by_group([("a", 0, 1), ("a", 1, 20), ("b", 0, 30), ("b", 1, 4)]);
+
+        assert_eq!(
+            select(&lbg, Some(3), SeriesDropPolicy::LastByName),
+            (series(&[("a", 0), ("a", 1), ("b", 0)]), 1)
+        );
+        assert_eq!(select(&lbg, Some(1), SeriesDropPolicy::LastByName), (series(&[("a", 0)]), 3));
+    }
+
+    #[test]
+    fn aggregate_once_per_group_and_topic() {
+        let lbg = lag_by_group([("a", 0, 1), ("a", 1, 20), ("b", 0, 30), ("b", 1, 4)]);
+
+        let mut groups: Vec<(&str, u64)> = aggregate_groups(&lbg, &LabelsMapping::default())
+            .iter()
+            .map(|ga| (ga.group, ga.agg.offset_lag_sum))
+            .collect();
+        groups.sort();
+        assert_eq!(groups, [("a", 21), ("b", 34)]);
+
+        let mut group_topics: Vec<(&str, &str, u64)> =
+            aggregate_group_topics(&lbg, &LabelsMapping::default())
+                .iter()
+                .map(|gta| (gta.group, gta.topic, gta.agg.offset_lag_sum))
+                .collect();
+        group_topics.sort();
+        assert_eq!(group_topics, [("a", "t", 21), ("b", "t", 34)]);
+    }
 }
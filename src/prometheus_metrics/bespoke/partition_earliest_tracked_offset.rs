@@ -19,6 +19,7 @@ pub(crate) fn append_metric(
     partition: u32,
     offset: u64,
     offset_timestamp_utc_ms: i64,
+    extra_labels: &str,
     res: &mut Vec<String>,
 ) {
     res.push(format!(
@@ -27,6 +28,7 @@ pub(crate) fn append_metric(
             {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
             {LABEL_TOPIC}=\"{topic}\",\
             {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
         }} \
         {offset} \
         {offset_timestamp_utc_ms}"
@@ -18,6 +18,7 @@ pub(crate) fn append_metric(
     topic: &str,
     partition: u32,
     offset: u64,
+    extra_labels: &str,
     res: &mut Vec<String>,
 ) {
     res.push(format!(
@@ -26,6 +27,7 @@ pub(crate) fn append_metric(
             {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
             {LABEL_TOPIC}=\"{topic}\",\
             {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
         }} \
         {offset}"
     ));
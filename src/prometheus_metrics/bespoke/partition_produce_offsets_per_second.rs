@@ -0,0 +1,34 @@
+use const_format::formatcp;
+
+use super::super::{LABEL_CLUSTER_ID, LABEL_PARTITION, LABEL_TOPIC, NAMESPACE};
+use super::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+const NAME: &str = formatcp!("{NAMESPACE}_kafka_partition_produce_offsets_per_second");
+const HELP: &str =
+    formatcp!("{HEADER_HELP} {NAME} The rate at which the topic partition is produced to, estimated from its tracked offsets, expressed in offsets per second.");
+const TYPE: &str = formatcp!("{HEADER_TYPE} {NAME} {TYPE_GAUGE}");
+
+pub(crate) fn append_headers(res: &mut Vec<String>) {
+    res.push(HELP.into());
+    res.push(TYPE.into());
+}
+
+pub(crate) fn append_metric(
+    cluster_id: &str,
+    topic: &str,
+    partition: u32,
+    produce_rate: f64,
+    extra_labels: &str,
+    res: &mut Vec<String>,
+) {
+    res.push(format!(
+        "{NAME}\
+        {{\
+            {LABEL_CLUSTER_ID}=\"{cluster_id}\",\
+            {LABEL_TOPIC}=\"{topic}\",\
+            {LABEL_PARTITION}=\"{partition}\"\
+            {extra_labels}\
+        }} \
+        {produce_rate}"
+    ));
+}
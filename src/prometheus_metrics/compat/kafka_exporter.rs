@@ -0,0 +1,212 @@
+//! Metrics compatible with [kafka_exporter](https://github.com/danielqsj/kafka_exporter).
+//!
+//! NOTE: kafka_exporter does not label its metrics with the cluster they refer to.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::kafka_types::TopicPartition;
+use crate::labels_mapping::{render_labels, LabelsMapping};
+use crate::lag_register::GroupWithLag;
+
+use super::super::bespoke::ConsumerPartition;
+use super::{append_gauge_headers, Watermarks};
+
+const CURRENT_OFFSET: &str = "kafka_consumergroup_current_offset";
+const CURRENT_OFFSET_SUM: &str = "kafka_consumergroup_current_offset_sum";
+const LAG: &str = "kafka_consumergroup_lag";
+const LAG_SUM: &str = "kafka_consumergroup_lag_sum";
+const MEMBERS: &str = "kafka_consumergroup_members";
+const TOPIC_PARTITIONS: &str = "kafka_topic_partitions";
+const TOPIC_PARTITION_CURRENT_OFFSET: &str = "kafka_topic_partition_current_offset";
+const TOPIC_PARTITION_OLDEST_OFFSET: &str = "kafka_topic_partition_oldest_offset";
+
+/// Append all the kafka_exporter compatible metrics to `res`.
+///
+/// Metrics of each Topic Partition are only appended for the selected `consumer_partitions`
+/// (see [`super::super::bespoke::select_consumer_partitions`]), that must carry no owner labels.
+pub fn append_metrics(
+    consumer_partitions: &[ConsumerPartition],
+    lag_by_group: &HashMap<String, GroupWithLag>,
+    watermarks: &BTreeMap<TopicPartition, Watermarks>,
+    mapping: &LabelsMapping,
+    res: &mut Vec<String>,
+) {
+    // Consumer Group metrics
+    let mut offsets = Vec::new();
+    let mut lags = Vec::new();
+    let mut offset_sums = Vec::new();
+    let mut lag_sums = Vec::new();
+    let mut members = Vec::new();
+    for cp in consumer_partitions {
+        let Some(l) = cp.lwo.lag.as_ref() else {
+            continue;
+        };
+
+        let labels = format!(
+            "consumergroup=\"{}\",topic=\"{}\",partition=\"{}\"{}",
+            cp.group, cp.tp.topic, cp.tp.partition, cp.extra_labels,
+        );
+        offsets.push(format!("{CURRENT_OFFSET}{{{labels}}} {}", l.offset));
+        lags.push(format!("{LAG}{{{labels}}} {}", l.offset_lag));
+    }
+    for (g, gwl) in lag_by_group.iter() {
+        for (t, agg) in
+            gwl.aggregate_by_topic().iter().filter(|(_, agg)| agg.partitions_with_lag > 0)
+        {
+            let labels = format!(
+                "consumergroup=\"{g}\",topic=\"{t}\"{}",
+                render_labels(&mapping.group_topic_labels(g, t))
+            );
+            offset_sums.push(format!("{CURRENT_OFFSET_SUM}{{{labels}}} {}", agg.offset_sum));
+            lag_sums.push(format!("{LAG_SUM}{{{labels}}} {}", agg.offset_lag_sum));
+        }
+
+        let members_count = gwl
+            .lag_by_topic_partition
+            .values()
+            .filter_map(|lwo| lwo.owner.as_ref().map(|o| o.id.as_str()))
+            .collect::<HashSet<&str>>()
+            .len();
+        let labels = format!("consumergroup=\"{g}\"{}", render_labels(&mapping.group_labels(g)));
+        members.push(format!("{MEMBERS}{{{labels}}} {members_count}"));
+    }
+    append_gauge_headers(
+        CURRENT_OFFSET,
+        "Current Offset of a ConsumerGroup at Topic/Partition",
+        res,
+    );
+    res.append(&mut offsets);
+    append_gauge_headers(
+        CURRENT_OFFSET_SUM,
+        "Current Offset of a ConsumerGroup at Topic for all partitions",
+        res,
+    );
+    res.append(&mut offset_sums);
+    append_gauge_headers(LAG, "Current Approximate Lag of a ConsumerGroup at Topic/Partition", res);
+    res.append(&mut lags);
+    append_gauge_headers(
+        LAG_SUM,
+        "Current Approximate Lag of a ConsumerGroup at Topic for all partitions",
+        res,
+    );
+    res.append(&mut lag_sums);
+    append_gauge_headers(MEMBERS, "Amount of members in a consumer group", res);
+    res.append(&mut members);
+
+    // Topic metrics
+    let mut topic_labels: HashMap<&str, String> = HashMap::new();
+    let mut partitions_by_topic: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut current_offsets = Vec::new();
+    let mut oldest_offsets = Vec::new();
+    for (tp, wm) in watermarks.iter() {
+        *partitions_by_topic.entry(tp.topic.as_str()).or_default() += 1;
+
+        let extra_labels = topic_labels
+            .entry(tp.topic.as_str())
+            .or_insert_with(|| render_labels(&mapping.topic_labels(&tp.topic)));
+        let labels = format!("topic=\"{}\",partition=\"{}\"{extra_labels}", tp.topic, tp.partition);
+
+        if let Some(latest) = wm.latest {
+            current_offsets.push(format!("{TOPIC_PARTITION_CURRENT_OFFSET}{{{labels}}} {latest}"));
+        }
+        if let Some(earliest) = wm.earliest {
+            oldest_offsets.push(format!("{TOPIC_PARTITION_OLDEST_OFFSET}{{{labels}}} {earliest}"));
+        }
+    }
+    append_gauge_headers(TOPIC_PARTITIONS, "Number of partitions for this Topic", res);
+    for (t, count) in partitions_by_topic.iter() {
+        res.push(format!("{TOPIC_PARTITIONS}{{topic=\"{t}\"{}}} {count}", topic_labels[t]));
+    }
+    append_gauge_headers(
+        TOPIC_PARTITION_CURRENT_OFFSET,
+        "Current Offset of a Broker at Topic/Partition",
+        res,
+    );
+    res.append(&mut current_offsets);
+    append_gauge_headers(
+        TOPIC_PARTITION_OLDEST_OFFSET,
+        "Oldest Offset of a Broker at Topic/Partition",
+        res,
+    );
+    res.append(&mut oldest_offsets);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::labels_mapping::Labels;
+    use crate::prometheus_metrics::bespoke::select_consumer_partitions;
+    use crate::prometheus_metrics::{OwnerLabels, SeriesDropPolicy};
+
+    use super::super::{example_lag_by_group, example_watermarks};
+    use super::*;
+
+    fn render(groups: &[&str], partitions_limit: Option<usize>) -> Vec<String> {
+        let lag_by_group = example_lag_by_group(groups);
+        let mapping =
+            LabelsMapping::new(Labels::from([("env".to_string(), "prod".to_string())])).unwrap();
+        let (consumer_partitions, _) = select_consumer_partitions(
+            &lag_by_group,
+            OwnerLabels::None,
+            &mapping,
+            partitions_limit,
+            SeriesDropPolicy::LastByName,
+        );
+
+        let mut res = Vec::new();
+        append_metrics(
+            &consumer_partitions,
+            &lag_by_group,
+            &example_watermarks(),
+            &mapping,
+            &mut res,
+        );
+        res
+    }
+
+    #[test]
+    fn render_metrics() {
+        assert_eq!(
+            render(&["g"], None),
+            [
+                "# HELP kafka_consumergroup_current_offset Current Offset of a ConsumerGroup at Topic/Partition",
+                "# TYPE kafka_consumergroup_current_offset gauge",
+                "kafka_consumergroup_current_offset{consumergroup=\"g\",topic=\"t\",partition=\"0\",env=\"prod\"} 90",
+                "# HELP kafka_consumergroup_current_offset_sum Current Offset of a ConsumerGroup at Topic for all partitions",
+                "# TYPE kafka_consumergroup_current_offset_sum gauge",
+                "kafka_consumergroup_current_offset_sum{consumergroup=\"g\",topic=\"t\",env=\"prod\"} 90",
+                "# HELP kafka_consumergroup_lag Current Approximate Lag of a ConsumerGroup at Topic/Partition",
+                "# TYPE kafka_consumergroup_lag gauge",
+                "kafka_consumergroup_lag{consumergroup=\"g\",topic=\"t\",partition=\"0\",env=\"prod\"} 10",
+                "# HELP kafka_consumergroup_lag_sum Current Approximate Lag of a ConsumerGroup at Topic for all partitions",
+                "# TYPE kafka_consumergroup_lag_sum gauge",
+                "kafka_consumergroup_lag_sum{consumergroup=\"g\",topic=\"t\",env=\"prod\"} 10",
+                "# HELP kafka_consumergroup_members Amount of members in a consumer group",
+                "# TYPE kafka_consumergroup_members gauge",
+                "kafka_consumergroup_members{consumergroup=\"g\",env=\"prod\"} 1",
+                "# HELP kafka_topic_partitions Number of partitions for this Topic",
+                "# TYPE kafka_topic_partitions gauge",
+                "kafka_topic_partitions{topic=\"t\",env=\"prod\"} 2",
+                "# HELP kafka_topic_partition_current_offset Current Offset of a Broker at Topic/Partition",
+                "# TYPE kafka_topic_partition_current_offset gauge",
+                "kafka_topic_partition_current_offset{topic=\"t\",partition=\"0\",env=\"prod\"} 100",
+                "# HELP kafka_topic_partition_oldest_offset Oldest Offset of a Broker at Topic/Partition",
+                "# TYPE kafka_topic_partition_oldest_offset gauge",
+                "kafka_topic_partition_oldest_offset{topic=\"t\",partition=\"0\",env=\"prod\"} 0",
+                "kafka_topic_partition_oldest_offset{topic=\"t\",partition=\"1\",env=\"prod\"} 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn render_within_partitions_limit() {
+        // Partitions beyond the limit are dropped, but still count towards the aggregates
+        let res = render(&["a", "b"], Some(2));
+        let lags: Vec<&String> =
+            res.iter().filter(|l| l.starts_with("kafka_consumergroup_lag{")).collect();
+        assert_eq!(
+            lags,
+            ["kafka_consumergroup_lag{consumergroup=\"a\",topic=\"t\",partition=\"0\",env=\"prod\"} 10"]
+        );
+        assert_eq!(res.iter().filter(|l| l.starts_with("kafka_consumergroup_lag_sum{")).count(), 2);
+    }
+}
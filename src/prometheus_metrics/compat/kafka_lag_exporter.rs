@@ -0,0 +1,237 @@
+//! Metrics compatible with [kafka-lag-exporter](https://github.com/seglo/kafka-lag-exporter).
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::kafka_types::{Member, TopicPartition};
+use crate::labels_mapping::{render_labels, LabelsMapping};
+use crate::lag_register::GroupWithLag;
+
+use super::super::bespoke::ConsumerPartition;
+use super::super::{OwnerLabels, UNKNOWN_VAL};
+use super::{append_gauge_headers, Watermarks};
+
+const GROUP_OFFSET: &str = "kafka_consumergroup_group_offset";
+const GROUP_LAG: &str = "kafka_consumergroup_group_lag";
+const GROUP_LAG_SECONDS: &str = "kafka_consumergroup_group_lag_seconds";
+const GROUP_MAX_LAG: &str = "kafka_consumergroup_group_max_lag";
+const GROUP_MAX_LAG_SECONDS: &str = "kafka_consumergroup_group_max_lag_seconds";
+const GROUP_SUM_LAG: &str = "kafka_consumergroup_group_sum_lag";
+const GROUP_TOPIC_SUM_LAG: &str = "kafka_consumergroup_group_topic_sum_lag";
+const PARTITION_LATEST_OFFSET: &str = "kafka_partition_latest_offset";
+const PARTITION_EARLIEST_OFFSET: &str = "kafka_partition_earliest_offset";
+
+/// Renders the owner labels, following kafka-lag-exporter conventions.
+fn render_owner_labels(opt_owner: Option<&Member>, granularity: OwnerLabels) -> String {
+    let (member_host, consumer_id, client_id) = match opt_owner {
+        Some(o) => (o.client_host.as_str(), o.id.as_str(), o.client_id.as_str()),
+        None => (UNKNOWN_VAL, UNKNOWN_VAL, UNKNOWN_VAL),
+    };
+
+    match granularity {
+        OwnerLabels::Full => format!(
+            ",member_host=\"{member_host}\",consumer_id=\"{consumer_id}\",client_id=\"{client_id}\""
+        ),
+        OwnerLabels::HostClientId => {
+            format!(",member_host=\"{member_host}\",client_id=\"{client_id}\"")
+        },
+        OwnerLabels::None => String::new(),
+    }
+}
+
+/// Append all the kafka-lag-exporter compatible metrics to `res`.
+///
+/// Metrics of each Topic Partition are only appended for the selected `consumer_partitions`
+/// (see [`super::super::bespoke::select_consumer_partitions`]), that must carry no owner labels:
+/// they are rendered following kafka-lag-exporter conventions instead.
+pub fn append_metrics(
+    cluster_id: &str,
+    consumer_partitions: &[ConsumerPartition],
+    lag_by_group: &HashMap<String, GroupWithLag>,
+    watermarks: &BTreeMap<TopicPartition, Watermarks>,
+    owner_labels: OwnerLabels,
+    mapping: &LabelsMapping,
+    res: &mut Vec<String>,
+) {
+    // Consumer Group Topic Partition metrics
+    let mut offsets = Vec::new();
+    let mut lags = Vec::new();
+    let mut lags_seconds = Vec::new();
+    for cp in consumer_partitions {
+        let Some(l) = cp.lwo.lag.as_ref() else {
+            continue;
+        };
+
+        let labels = format!(
+            "cluster_name=\"{cluster_id}\",group=\"{}\",topic=\"{}\",partition=\"{}\"{}{}",
+            cp.group,
+            cp.tp.topic,
+            cp.tp.partition,
+            render_owner_labels(cp.lwo.owner.as_ref(), owner_labels),
+            cp.extra_labels,
+        );
+        let lag_seconds = l.time_lag.num_milliseconds() as f64 / 1000.0;
+
+        offsets.push(format!("{GROUP_OFFSET}{{{labels}}} {}", l.offset));
+        lags.push(format!("{GROUP_LAG}{{{labels}}} {}", l.offset_lag));
+        lags_seconds.push(format!("{GROUP_LAG_SECONDS}{{{labels}}} {lag_seconds}"));
+    }
+    append_gauge_headers(GROUP_OFFSET, "Last group consumed offset of a partition", res);
+    res.append(&mut offsets);
+    append_gauge_headers(GROUP_LAG, "Group offset lag of a partition", res);
+    res.append(&mut lags);
+    append_gauge_headers(GROUP_LAG_SECONDS, "Group time lag of a partition", res);
+    res.append(&mut lags_seconds);
+
+    // Consumer Group metrics
+    let mut max_lags = Vec::new();
+    let mut max_lags_seconds = Vec::new();
+    let mut sum_lags = Vec::new();
+    let mut topic_sum_lags = Vec::new();
+    for (g, gwl) in lag_by_group.iter() {
+        let agg = gwl.aggregate();
+        if agg.partitions_with_lag > 0 {
+            let labels = format!(
+                "cluster_name=\"{cluster_id}\",group=\"{g}\"{}",
+                render_labels(&mapping.group_labels(g))
+            );
+            let max_lag_seconds = agg.time_lag_max.num_milliseconds() as f64 / 1000.0;
+
+            max_lags.push(format!("{GROUP_MAX_LAG}{{{labels}}} {}", agg.offset_lag_max));
+            max_lags_seconds.push(format!("{GROUP_MAX_LAG_SECONDS}{{{labels}}} {max_lag_seconds}"));
+            sum_lags.push(format!("{GROUP_SUM_LAG}{{{labels}}} {}", agg.offset_lag_sum));
+        }
+
+        for (t, agg) in
+            gwl.aggregate_by_topic().iter().filter(|(_, agg)| agg.partitions_with_lag > 0)
+        {
+            let labels = format!(
+                "cluster_name=\"{cluster_id}\",group=\"{g}\",topic=\"{t}\"{}",
+                render_labels(&mapping.group_topic_labels(g, t))
+            );
+            topic_sum_lags
+                .push(format!("{GROUP_TOPIC_SUM_LAG}{{{labels}}} {}", agg.offset_lag_sum));
+        }
+    }
+    append_gauge_headers(GROUP_MAX_LAG, "Max group offset lag", res);
+    res.append(&mut max_lags);
+    append_gauge_headers(GROUP_MAX_LAG_SECONDS, "Max group time lag", res);
+    res.append(&mut max_lags_seconds);
+    append_gauge_headers(GROUP_SUM_LAG, "Sum of group offset lag", res);
+    res.append(&mut sum_lags);
+    append_gauge_headers(
+        GROUP_TOPIC_SUM_LAG,
+        "Sum of group offset lag across topic partitions",
+        res,
+    );
+    res.append(&mut topic_sum_lags);
+
+    // Topic Partition metrics
+    let mut topic_labels: HashMap<&str, String> = HashMap::new();
+    let mut latest_offsets = Vec::new();
+    let mut earliest_offsets = Vec::new();
+    for (tp, wm) in watermarks.iter() {
+        let extra_labels = topic_labels
+            .entry(tp.topic.as_str())
+            .or_insert_with(|| render_labels(&mapping.topic_labels(&tp.topic)));
+        let labels = format!(
+            "cluster_name=\"{cluster_id}\",topic=\"{}\",partition=\"{}\"{extra_labels}",
+            tp.topic, tp.partition
+        );
+
+        if let Some(latest) = wm.latest {
+            latest_offsets.push(format!("{PARTITION_LATEST_OFFSET}{{{labels}}} {latest}"));
+        }
+        if let Some(earliest) = wm.earliest {
+            earliest_offsets.push(format!("{PARTITION_EARLIEST_OFFSET}{{{labels}}} {earliest}"));
+        }
+    }
+    append_gauge_headers(PARTITION_LATEST_OFFSET, "Latest offset of a partition", res);
+    res.append(&mut latest_offsets);
+    append_gauge_headers(PARTITION_EARLIEST_OFFSET, "Earliest offset of a partition", res);
+    res.append(&mut earliest_offsets);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::labels_mapping::Labels;
+    use crate::prometheus_metrics::bespoke::select_consumer_partitions;
+    use crate::prometheus_metrics::SeriesDropPolicy;
+
+    use super::super::{example_lag_by_group, example_watermarks};
+    use super::*;
+
+    fn render(groups: &[&str], partitions_limit: Option<usize>) -> Vec<String> {
+        let lag_by_group = example_lag_by_group(groups);
+        let mapping =
+            LabelsMapping::new(Labels::from([("env".to_string(), "prod".to_string())])).unwrap();
+        let (consumer_partitions, _) = select_consumer_partitions(
+            &lag_by_group,
+            OwnerLabels::None,
+            &mapping,
+            partitions_limit,
+            SeriesDropPolicy::LastByName,
+        );
+
+        let mut res = Vec::new();
+        append_metrics(
+            "c",
+            &consumer_partitions,
+            &lag_by_group,
+            &example_watermarks(),
+            OwnerLabels::HostClientId,
+            &mapping,
+            &mut res,
+        );
+        res
+    }
+
+    #[test]
+    fn render_metrics() {
+        assert_eq!(
+            render(&["g"], None),
+            [
+                "# HELP kafka_consumergroup_group_offset Last group consumed offset of a partition",
+                "# TYPE kafka_consumergroup_group_offset gauge",
+                "kafka_consumergroup_group_offset{cluster_name=\"c\",group=\"g\",topic=\"t\",partition=\"0\",member_host=\"/10.0.0.1\",client_id=\"c-1\",env=\"prod\"} 90",
+                "# HELP kafka_consumergroup_group_lag Group offset lag of a partition",
+                "# TYPE kafka_consumergroup_group_lag gauge",
+                "kafka_consumergroup_group_lag{cluster_name=\"c\",group=\"g\",topic=\"t\",partition=\"0\",member_host=\"/10.0.0.1\",client_id=\"c-1\",env=\"prod\"} 10",
+                "# HELP kafka_consumergroup_group_lag_seconds Group time lag of a partition",
+                "# TYPE kafka_consumergroup_group_lag_seconds gauge",
+                "kafka_consumergroup_group_lag_seconds{cluster_name=\"c\",group=\"g\",topic=\"t\",partition=\"0\",member_host=\"/10.0.0.1\",client_id=\"c-1\",env=\"prod\"} 1.5",
+                "# HELP kafka_consumergroup_group_max_lag Max group offset lag",
+                "# TYPE kafka_consumergroup_group_max_lag gauge",
+                "kafka_consumergroup_group_max_lag{cluster_name=\"c\",group=\"g\",env=\"prod\"} 10",
+                "# HELP kafka_consumergroup_group_max_lag_seconds Max group time lag",
+                "# TYPE kafka_consumergroup_group_max_lag_seconds gauge",
+                "kafka_consumergroup_group_max_lag_seconds{cluster_name=\"c\",group=\"g\",env=\"prod\"} 1.5",
+                "# HELP kafka_consumergroup_group_sum_lag Sum of group offset lag",
+                "# TYPE kafka_consumergroup_group_sum_lag gauge",
+                "kafka_consumergroup_group_sum_lag{cluster_name=\"c\",group=\"g\",env=\"prod\"} 10",
+                "# HELP kafka_consumergroup_group_topic_sum_lag Sum of group offset lag across topic partitions",
+                "# TYPE kafka_consumergroup_group_topic_sum_lag gauge",
+                "kafka_consumergroup_group_topic_sum_lag{cluster_name=\"c\",group=\"g\",topic=\"t\",env=\"prod\"} 10",
+                "# HELP kafka_partition_latest_offset Latest offset of a partition",
+                "# TYPE kafka_partition_latest_offset gauge",
+                "kafka_partition_latest_offset{cluster_name=\"c\",topic=\"t\",partition=\"0\",env=\"prod\"} 100",
+                "# HELP kafka_partition_earliest_offset Earliest offset of a partition",
+                "# TYPE kafka_partition_earliest_offset gauge",
+                "kafka_partition_earliest_offset{cluster_name=\"c\",topic=\"t\",partition=\"0\",env=\"prod\"} 0",
+                "kafka_partition_earliest_offset{cluster_name=\"c\",topic=\"t\",partition=\"1\",env=\"prod\"} 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn render_within_partitions_limit() {
+        let res = render(&["a", "b"], Some(2));
+        let lags: Vec<&String> =
+            res.iter().filter(|l| l.starts_with("kafka_consumergroup_group_lag{")).collect();
+        assert_eq!(lags.len(), 1);
+        assert!(lags[0].contains("group=\"a\""));
+        assert_eq!(
+            res.iter().filter(|l| l.starts_with("kafka_consumergroup_group_sum_lag{")).count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Metrics named and labelled after other Kafka lag monitoring tools, to keep using their dashboards and alerts.
+//!
+//! Each profile renders the same data as the [`super::bespoke`] metrics, but following the conventions
+//! of the tool it's compatible with.
+
+pub mod kafka_exporter;
+pub mod kafka_lag_exporter;
+
+use std::collections::BTreeMap;
+
+use crate::kafka_types::TopicPartition;
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+use super::bespoke::{HEADER_HELP, HEADER_TYPE, TYPE_GAUGE};
+
+// Labels applied by the compatibility profiles, on top of those shared with the native metrics.
+pub const LABEL_CLUSTER_NAME: &str = "cluster_name";
+pub const LABEL_CONSUMER_ID: &str = "consumer_id";
+pub const LABEL_CLIENT_ID: &str = "client_id";
+pub const LABEL_CONSUMERGROUP: &str = "consumergroup";
+
+/// Earliest and latest available offsets of a Topic Partition, if known.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Watermarks {
+    pub earliest: Option<u64>,
+    pub latest: Option<u64>,
+}
+
+/// Collect the [`Watermarks`] of the given Topic Partitions, sorted.
+pub async fn collect_watermarks(
+    po_reg: &PartitionOffsetsRegister,
+    tps: &[TopicPartition],
+) -> BTreeMap<TopicPartition, Watermarks> {
+    let mut res = BTreeMap::new();
+    for tp in tps {
+        let watermarks = Watermarks {
+            earliest: po_reg.get_earliest_available_offset(tp).await.ok(),
+            latest: po_reg.get_latest_available_offset(tp).await.ok(),
+        };
+        res.insert(tp.clone(), watermarks);
+    }
+    res
+}
+
+fn append_gauge_headers(name: &str, help: &str, res: &mut Vec<String>) {
+    res.push(format!("{HEADER_HELP} {name} {help}"));
+    res.push(format!("{HEADER_TYPE} {name} {TYPE_GAUGE}"));
+}
+
+/// Consumer Groups consuming Topic `t`: each Consumer Group in `groups` has partition 0 lagging
+/// by 10 offsets (1.5s), owned by member `m-1`, while partition 1 has no known lag.
+#[cfg(test)]
+fn example_lag_by_group(
+    groups: &[&str],
+) -> std::collections::HashMap<String, crate::lag_register::GroupWithLag> {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::kafka_types::Member;
+    use crate::lag_register::{GroupWithLag, Lag, LagWithOwner};
+
+    groups
+        .iter()
+        .map(|g| {
+            let lagging = LagWithOwner {
+                lag: Some(Lag {
+                    offset: 90,
+                    offset_timestamp: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+                    offset_lag: 10,
+                    time_lag: Duration::milliseconds(1500),
+                }),
+                owner: Some(Member {
+                    id: "m-1".to_string(),
+                    client_id: "c-1".to_string(),
+                    client_host: "/10.0.0.1".to_string(),
+                }),
+                ..Default::default()
+            };
+            let gwl = GroupWithLag {
+                lag_by_topic_partition: std::collections::HashMap::from([
+                    (TopicPartition::new("t".to_string(), 0), lagging),
+                    (TopicPartition::new("t".to_string(), 1), Default::default()),
+                ]),
+                ..Default::default()
+            };
+            (g.to_string(), gwl)
+        })
+        .collect()
+}
+
+/// [`Watermarks`] of partitions 0 and 1 of Topic `t`.
+#[cfg(test)]
+fn example_watermarks() -> BTreeMap<TopicPartition, Watermarks> {
+    BTreeMap::from([
+        (
+            TopicPartition::new("t".to_string(), 0),
+            Watermarks {
+                earliest: Some(0),
+                latest: Some(100),
+            },
+        ),
+        (
+            TopicPartition::new("t".to_string(), 1),
+            Watermarks {
+                earliest: Some(5),
+                latest: None,
+            },
+        ),
+    ])
+}
@@ -0,0 +1,210 @@
+//! Parsing of the Prometheus [text exposition format](https://github.com/prometheus/docs/blob/main/content/docs/instrumenting/exposition_formats.md#text-based-format),
+//! as produced by the bespoke metrics: this lets them be translated to other formats.
+
+use super::bespoke::{HEADER_HELP, HEADER_TYPE};
+
+/// Type of a [`Family`], as declared by its `# TYPE` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FamilyType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+impl From<&str> for FamilyType {
+    fn from(s: &str) -> Self {
+        match s {
+            "counter" => FamilyType::Counter,
+            "gauge" => FamilyType::Gauge,
+            "histogram" => FamilyType::Histogram,
+            "summary" => FamilyType::Summary,
+            _ => FamilyType::Untyped,
+        }
+    }
+}
+
+/// A single sample of a [`Family`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Name of the sample: it differs from the [`Family`] name for `_bucket`, `_sum` and `_count`.
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+    pub timestamp_ms: Option<i64>,
+}
+
+/// A metric family: its metadata and samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Family {
+    pub name: String,
+    pub help: String,
+    pub family_type: FamilyType,
+    pub samples: Vec<Sample>,
+}
+
+impl Family {
+    fn new(name: &str) -> Self {
+        Family {
+            name: name.to_string(),
+            help: String::new(),
+            family_type: FamilyType::Untyped,
+            samples: Vec::new(),
+        }
+    }
+}
+
+/// Parse `lines` of text exposition format into [`Family`]s, in order of appearance.
+///
+/// Lines that can't be parsed are logged and skipped.
+pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<Family> {
+    let mut families: Vec<Family> = Vec::new();
+
+    for line in lines.into_iter().flat_map(str::lines) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix(HEADER_HELP) {
+            let (name, help) = rest.trim_start().split_once(' ').unwrap_or((rest.trim(), ""));
+            family_for(&mut families, name, true).help = help.to_string();
+        } else if let Some(rest) = line.strip_prefix(HEADER_TYPE) {
+            let (name, family_type) =
+                rest.trim_start().split_once(' ').unwrap_or((rest.trim(), ""));
+            family_for(&mut families, name, true).family_type =
+                FamilyType::from(family_type.trim());
+        } else if line.starts_with('#') {
+            continue;
+        } else {
+            match parse_sample(line) {
+                Some(sample) => {
+                    let family = family_for(&mut families, &sample.name, false);
+                    family.samples.push(sample);
+                },
+                None => debug!("Skipping unparsable sample: {line}"),
+            }
+        }
+    }
+
+    families
+}
+
+/// The [`Family`] a metadata line (if `exact`) or a sample named `name` belongs to: the last one,
+/// if it matches, or a new one otherwise.
+fn family_for<'a>(families: &'a mut Vec<Family>, name: &str, exact: bool) -> &'a mut Family {
+    let matches = families.last().is_some_and(|f| {
+        f.name == name
+            || (!exact
+                && ["_bucket", "_sum", "_count"]
+                    .iter()
+                    .any(|suffix| name.strip_suffix(suffix).is_some_and(|n| n == f.name)))
+    });
+    if !matches {
+        families.push(Family::new(name));
+    }
+    families.last_mut().expect("Just ensured there is a last family")
+}
+
+fn parse_sample(line: &str) -> Option<Sample> {
+    let name_end = line.find(|c: char| c == '{' || c.is_whitespace())?;
+    let name = &line[..name_end];
+
+    let mut labels = Vec::new();
+    let mut rest = &line[name_end..];
+    if let Some(after_brace) = rest.strip_prefix('{') {
+        let (parsed, after_labels) = parse_labels(after_brace)?;
+        labels = parsed;
+        rest = after_labels;
+    }
+
+    let mut fields = rest.split_whitespace();
+    let value = fields.next()?.parse::<f64>().ok()?;
+    let timestamp_ms = match fields.next() {
+        Some(ts) => Some(ts.parse::<i64>().ok()?),
+        None => None,
+    };
+
+    Some(Sample {
+        name: name.to_string(),
+        labels,
+        value,
+        timestamp_ms,
+    })
+}
+
+/// Parse `key="value",...}`, returning the labels and what follows the closing brace.
+fn parse_labels(mut s: &str) -> Option<(Vec<(String, String)>, &str)> {
+    let mut labels = Vec::new();
+
+    loop {
+        s = s.trim_start_matches([',', ' ']);
+        if let Some(rest) = s.strip_prefix('}') {
+            return Some((labels, rest));
+        }
+
+        let (key, rest) = s.split_once('=')?;
+        let mut chars = rest.strip_prefix('"')?.char_indices();
+        let mut value = String::new();
+        let end = loop {
+            match chars.next()? {
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    c => value.push(c),
+                },
+                (i, '"') => break i,
+                (_, c) => value.push(c),
+            }
+        };
+
+        labels.push((key.trim().to_string(), value));
+        s = &rest[end + 2..];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_families() {
+        let lines = [
+            "# HELP kmtd_lag The lag.",
+            "# TYPE kmtd_lag gauge",
+            r#"kmtd_lag{cluster_id="c",group="g \"quoted\"",topic="t"} 42 1700000000000"#,
+            r#"kmtd_lag{cluster_id="c",group="g",topic="t",team="a\\b\nc"} -1"#,
+            "# HELP kmtd_write_ms Write time.\n# TYPE kmtd_write_ms histogram",
+            r#"kmtd_write_ms_bucket{le="0.5"} 1"#,
+            r#"kmtd_write_ms_bucket{le="+Inf"} 3"#,
+            "kmtd_write_ms_sum 7.5",
+            "kmtd_write_ms_count 3",
+            "not a sample",
+            "kmtd_untyped NaN",
+        ];
+
+        let families = parse(lines);
+        assert_eq!(families.len(), 3);
+
+        let lag = &families[0];
+        assert_eq!((lag.name.as_str(), lag.help.as_str()), ("kmtd_lag", "The lag."));
+        assert_eq!(lag.family_type, FamilyType::Gauge);
+        assert_eq!(lag.samples.len(), 2);
+        assert_eq!(lag.samples[0].labels[1], ("group".to_string(), "g \"quoted\"".to_string()));
+        assert_eq!(
+            (lag.samples[0].value, lag.samples[0].timestamp_ms),
+            (42.0, Some(1700000000000))
+        );
+        assert_eq!(lag.samples[1].labels[3], ("team".to_string(), "a\\b\nc".to_string()));
+        assert_eq!((lag.samples[1].value, lag.samples[1].timestamp_ms), (-1.0, None));
+
+        let hist = &families[1];
+        assert_eq!(hist.family_type, FamilyType::Histogram);
+        assert_eq!(hist.samples.len(), 4);
+        assert_eq!(hist.samples[1].labels[0].1, "+Inf");
+        assert_eq!(hist.samples[3].name, "kmtd_write_ms_count");
+
+        assert_eq!(families[2].family_type, FamilyType::Untyped);
+        assert!(families[2].samples[0].value.is_nan());
+    }
+}
@@ -1,13 +1,17 @@
 pub mod bespoke;
+pub mod compat;
+pub mod exposition;
 
 use std::collections::HashMap;
 
+use clap::ValueEnum;
 use prometheus::Registry;
 use rdkafka::admin::AdminClient;
 use rdkafka::client::DefaultClientContext;
 use rdkafka::ClientConfig;
 use tokio::time::Duration;
 
+use crate::cli::KVPair;
 use crate::constants::DEFAULT_CLUSTER_ID;
 
 pub const NAMESPACE: &str = "kmtd";
@@ -19,12 +23,67 @@ pub const LABEL_PARTITION: &str = "partition";
 pub const LABEL_MEMBER_ID: &str = "member_id";
 pub const LABEL_MEMBER_HOST: &str = "member_host";
 pub const LABEL_MEMBER_CLIENT_ID: &str = "member_client_id";
+pub const LABEL_SLO: &str = "slo";
+pub const LABEL_WINDOW: &str = "window";
 
 pub const UNKNOWN_VAL: &str = "UNKNOWN";
 
+/// Level of detail at which consumer metrics are produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum ConsumerMetricsLevel {
+    /// For each Topic Partition consumed by each Consumer Group.
+    Partition,
+
+    /// Aggregated for each Topic consumed by each Consumer Group.
+    GroupTopic,
+
+    /// Aggregated for each Consumer Group.
+    Group,
+}
+
+/// Naming and labelling conventions of the metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum MetricsProfile {
+    /// Native Kommitted metrics, under the `kmtd` namespace.
+    Kommitted,
+
+    /// Compatible with kafka-lag-exporter (e.g. `kafka_consumergroup_group_lag`).
+    KafkaLagExporter,
+
+    /// Compatible with kafka_exporter (e.g. `kafka_consumergroup_lag`).
+    KafkaExporter,
+}
+
+/// Granularity of the labels identifying the owner (Member) of a consumed Topic Partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum OwnerLabels {
+    /// Labels `member_id`, `member_host` and `member_client_id`.
+    Full,
+
+    /// Labels `member_host` and `member_client_id`: stable across rebalances.
+    HostClientId,
+
+    /// No owner labels at all.
+    None,
+}
+
+/// Policy to decide which series to drop, when the consumer partitions limit is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum SeriesDropPolicy {
+    /// Drop the Topic Partitions with the lowest (or unknown) offset lag first, and on a tie, the ones sorting last by name.
+    LowestLagFirst,
+
+    /// Drop the Topic Partitions sorting last by group, topic and partition first.
+    LastByName,
+}
+
 const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
 
-pub fn init(client_config: ClientConfig, cluster_id_override: Option<String>) -> Registry {
+pub fn init(
+    client_config: ClientConfig,
+    cluster_id_override: Option<String>,
+    static_labels: Vec<KVPair>,
+) -> Registry {
     let cluster_id = match cluster_id_override {
         Some(cid) => cid,
         None => client_config
@@ -35,7 +94,8 @@ pub fn init(client_config: ClientConfig, cluster_id_override: Option<String>) ->
             .unwrap_or_else(|| DEFAULT_CLUSTER_ID.to_string()),
     };
 
-    let prom_def_labels = HashMap::from([(LABEL_CLUSTER_ID.to_string(), cluster_id)]);
+    let mut prom_def_labels = HashMap::from([(LABEL_CLUSTER_ID.to_string(), cluster_id)]);
+    prom_def_labels.extend(static_labels);
 
     info!("Prometheus Metrics default labels:\n{:#?}", prom_def_labels);
 
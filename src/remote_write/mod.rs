@@ -0,0 +1,132 @@
+//! Push metrics to Prometheus (or compatible storage), via the [remote write](https://prometheus.io/docs/specs/remote_write_spec/) protocol.
+//!
+//! For environments where Prometheus can't reach the HTTP Service to scrape it: every interval,
+//! all the series that `/metrics` exposes are queued, and sent snappy-compressed.
+
+// Inner modules
+mod sender;
+mod series;
+
+#[allow(clippy::all)]
+mod prompb {
+    tonic::include_proto!("prometheus");
+}
+
+// Imports
+use std::time::Duration;
+
+use chrono::Utc;
+use prometheus::{register_int_counter_with_registry, IntCounter, TextEncoder};
+use prost::Message;
+use reqwest::Url;
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::http::{render_bespoke_metrics, HttpServiceState};
+use crate::prometheus_metrics::exposition;
+
+use prompb::WriteRequest;
+use sender::SenderMetrics;
+use series::timeseries;
+
+const MET_SENT_NAME: &str = "remote_write_sent_requests_total";
+const MET_SENT_HELP: &str = "Remote write requests sent successfully";
+const MET_FAILED_NAME: &str = "remote_write_failed_requests_total";
+const MET_FAILED_HELP: &str = "Remote write requests that failed to be sent, after all retries";
+const MET_DROPPED_NAME: &str = "remote_write_dropped_requests_total";
+const MET_DROPPED_HELP: &str =
+    "Remote write requests dropped without sending, as the queue was full";
+
+/// Authentication to the remote write endpoint.
+#[derive(Debug, Clone)]
+pub enum RemoteWriteAuth {
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer(String),
+}
+
+/// Configuration of the remote write.
+#[derive(Debug, Clone)]
+pub struct RemoteWriteConfig {
+    pub url: Url,
+    pub auth: Option<RemoteWriteAuth>,
+    pub interval: Duration,
+
+    /// Amount of requests waiting to be sent: when full, new ones are dropped.
+    pub queue_capacity: usize,
+}
+
+/// Spawn the tasks that periodically queue all the series, and that send them to the remote write endpoint.
+///
+/// The series are the same served by the HTTP Service, so they are built from its `state`.
+pub fn init(
+    config: RemoteWriteConfig,
+    state: HttpServiceState,
+    shutdown_token: CancellationToken,
+) -> (JoinHandle<()>, JoinHandle<()>) {
+    let register = |name: &str, help: &str| -> IntCounter {
+        register_int_counter_with_registry!(name, help, state.metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {name}"))
+    };
+    let sender_metrics = SenderMetrics {
+        sent: register(MET_SENT_NAME, MET_SENT_HELP),
+        failed: register(MET_FAILED_NAME, MET_FAILED_HELP),
+    };
+    let dropped = register(MET_DROPPED_NAME, MET_DROPPED_HELP);
+
+    debug!("Initialized, writing to '{}'", config.url);
+    let (requests_tx, requests_rx) = mpsc::channel(config.queue_capacity);
+    let sender_join = sender::spawn(
+        config.url.clone(),
+        config.auth,
+        requests_rx,
+        sender_metrics,
+        shutdown_token.clone(),
+    );
+
+    let collector_join = tokio::spawn(async move {
+        let mut interval = interval(config.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = shutdown_token.cancelled() => break,
+            }
+
+            let body = collect(&state).await;
+            if let Err(e) = requests_tx.try_send(body) {
+                warn!("Dropping remote write request: {e}");
+                dropped.inc();
+            }
+        }
+
+        info!("Shutting down");
+    });
+
+    (collector_join, sender_join)
+}
+
+/// Collect all the series, as a snappy-compressed [`WriteRequest`].
+async fn collect(state: &HttpServiceState) -> Vec<u8> {
+    let mut lines = render_bespoke_metrics(state).await;
+    match TextEncoder.encode_to_string(&state.metrics.gather()) {
+        Ok(registry) => lines.push(registry),
+        Err(e) => warn!("Failed to encode metrics: {e}"),
+    }
+
+    let families = exposition::parse(lines.iter().map(String::as_str));
+    let request = WriteRequest {
+        timeseries: timeseries(&families, Utc::now().timestamp_millis()),
+    };
+
+    snap::raw::Encoder::new()
+        .compress_vec(&request.encode_to_vec())
+        .expect("Snappy compression of a WriteRequest can't fail")
+}
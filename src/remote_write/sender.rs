@@ -0,0 +1,147 @@
+use prometheus::IntCounter;
+use reqwest::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE, USER_AGENT},
+    Client, StatusCode, Url,
+};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
+use tokio_util::sync::CancellationToken;
+
+use super::RemoteWriteAuth;
+
+/// Attempts to send a request, before giving up on it.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry: it doubles at every retry, up to [`MAX_BACKOFF`].
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const HEADER_REMOTE_WRITE_VERSION: &str = "X-Prometheus-Remote-Write-Version";
+const REMOTE_WRITE_VERSION: &str = "0.1.0";
+
+/// Outcome of a failed attempt to send a request.
+enum Failure {
+    /// Worth retrying: connection error, server error or throttling.
+    Recoverable(String),
+
+    /// Retrying won't help: the request was rejected.
+    Unrecoverable(String),
+}
+
+/// Counters of the outcome of the requests.
+pub(super) struct SenderMetrics {
+    pub sent: IntCounter,
+    pub failed: IntCounter,
+}
+
+/// Spawn the task sending the queued requests (snappy-compressed `WriteRequest`s) to `url`, in order.
+///
+/// Each request that fails recoverably is retried with exponential backoff.
+pub(super) fn spawn(
+    url: Url,
+    auth: Option<RemoteWriteAuth>,
+    mut requests_rx: mpsc::Receiver<Vec<u8>>,
+    metrics: SenderMetrics,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to build remote write HTTP client (fatal)");
+
+        loop {
+            let body = tokio::select! {
+                Some(b) = requests_rx.recv() => b,
+                _ = shutdown_token.cancelled() => break,
+                else => break,
+            };
+
+            tokio::select! {
+                sent = send_with_retries(&client, &url, auth.as_ref(), body) => {
+                    if sent {
+                        metrics.sent.inc();
+                    } else {
+                        metrics.failed.inc();
+                    }
+                },
+                _ = shutdown_token.cancelled() => break,
+            }
+        }
+
+        info!("Shutting down");
+    })
+}
+
+async fn send_with_retries(
+    client: &Client,
+    url: &Url,
+    auth: Option<&RemoteWriteAuth>,
+    body: Vec<u8>,
+) -> bool {
+    let mut backoff = INITIAL_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        match send(client, url, auth, body.clone()).await {
+            Ok(()) => {
+                trace!("Sent remote write request to '{url}'");
+                return true;
+            },
+            Err(Failure::Unrecoverable(e)) => {
+                warn!("Remote write request to '{url}' rejected, dropping it: {e}");
+                return false;
+            },
+            Err(Failure::Recoverable(e)) if attempt < MAX_ATTEMPTS => {
+                debug!("Remote write request to '{url}' failed (attempt {attempt}), retrying in {backoff:?}: {e}");
+                sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            },
+            Err(Failure::Recoverable(e)) => {
+                warn!(
+                    "Remote write request to '{url}' failed {MAX_ATTEMPTS} times, dropping it: {e}"
+                );
+            },
+        }
+    }
+
+    false
+}
+
+async fn send(
+    client: &Client,
+    url: &Url,
+    auth: Option<&RemoteWriteAuth>,
+    body: Vec<u8>,
+) -> Result<(), Failure> {
+    let mut req = client
+        .post(url.clone())
+        .header(CONTENT_ENCODING, "snappy")
+        .header(CONTENT_TYPE, "application/x-protobuf")
+        .header(USER_AGENT, concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+        .header(HEADER_REMOTE_WRITE_VERSION, REMOTE_WRITE_VERSION)
+        .body(body);
+    req = match auth {
+        Some(RemoteWriteAuth::Basic {
+            username,
+            password,
+        }) => req.basic_auth(username, Some(password)),
+        Some(RemoteWriteAuth::Bearer(token)) => req.bearer_auth(token),
+        None => req,
+    };
+
+    let res = req.send().await.map_err(|e| Failure::Recoverable(e.to_string()))?;
+    let status = res.status();
+    if status.is_success() {
+        Ok(())
+    } else if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        Err(Failure::Recoverable(format!("HTTP status {status}")))
+    } else {
+        let text = res.text().await.unwrap_or_default();
+        Err(Failure::Unrecoverable(format!("HTTP status {status}: {text}")))
+    }
+}
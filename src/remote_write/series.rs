@@ -0,0 +1,68 @@
+use crate::prometheus_metrics::exposition::Family;
+
+use super::prompb::{Label, Sample, TimeSeries};
+
+/// Label carrying the name of the metric, in remote write.
+const LABEL_NAME: &str = "__name__";
+
+/// Translate the samples of the metric [`Family`]s into remote write [`TimeSeries`], one per sample.
+///
+/// Samples with a timestamp (e.g. the commit time of a consumer offset) keep it:
+/// the others are timestamped `now_ms`.
+pub fn timeseries(families: &[Family], now_ms: i64) -> Vec<TimeSeries> {
+    families
+        .iter()
+        .flat_map(|f| f.samples.iter())
+        .map(|s| {
+            let mut labels: Vec<Label> = std::iter::once((LABEL_NAME, s.name.as_str()))
+                .chain(s.labels.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+                .map(|(name, value)| Label {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect();
+            labels.sort_by(|a, b| a.name.cmp(&b.name));
+
+            TimeSeries {
+                labels,
+                samples: vec![Sample {
+                    value: s.value,
+                    timestamp: s.timestamp_ms.unwrap_or(now_ms),
+                }],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prometheus_metrics::exposition::parse;
+
+    use super::*;
+
+    #[test]
+    fn samples_to_timeseries() {
+        let families = parse([
+            "# TYPE kmtd_offset gauge",
+            r#"kmtd_offset{topic="t",group="g"} 42 1700000000000"#,
+            "kmtd_write_ms_count 3",
+        ]);
+
+        let ts = timeseries(&families, 5);
+        assert_eq!(ts.len(), 2);
+
+        let names: Vec<&str> = ts[0].labels.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["__name__", "group", "topic"]);
+        assert_eq!(ts[0].labels[0].value, "kmtd_offset");
+        assert_eq!(
+            ts[0].samples,
+            [Sample {
+                value: 42.0,
+                timestamp: 1700000000000
+            }]
+        );
+
+        assert_eq!(ts[1].labels[0].value, "kmtd_write_ms_count");
+        assert_eq!(ts[1].samples[0].timestamp, 5);
+    }
+}
@@ -0,0 +1,48 @@
+//! Recommendation of how many consumers a Consumer Group needs, to keep its time lag under a target.
+//!
+//! It's based on the produce and consume rates estimated by [`crate::consumer_rates`].
+
+// Inner modules
+mod recommendation;
+
+// Exports
+pub use recommendation::ScalingRecommendation;
+
+// Imports
+use std::collections::HashSet;
+
+use chrono::Duration;
+
+use crate::cluster_status::ClusterStatusRegister;
+use crate::consumer_rates;
+use crate::lag_register::GroupWithLag;
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+/// Recommend how many consumers the given [`GroupWithLag`] needs, to keep its time lag under `target_time_lag`.
+///
+/// See [`recommendation::recommend`] for how the recommendation is computed.
+pub async fn recommend_group(
+    gwl: &GroupWithLag,
+    cs_reg: &ClusterStatusRegister,
+    po_reg: &PartitionOffsetsRegister,
+    target_time_lag: Duration,
+) -> ScalingRecommendation {
+    let rates = consumer_rates::evaluate_group(gwl, po_reg).await;
+
+    // Count the Partitions of the consumed Topics, as known to the cluster
+    let topics: HashSet<&str> =
+        gwl.lag_by_topic_partition.keys().map(|tp| tp.topic.as_str()).collect();
+    let mut partitions = 0;
+    for t in topics {
+        partitions +=
+            cs_reg.get_partitions_for_topic(t).await.map(|ps| ps.len()).unwrap_or_default();
+    }
+
+    recommendation::recommend(
+        &gwl.members(),
+        &gwl.aggregate(),
+        partitions.max(gwl.lag_by_topic_partition.len()),
+        &rates,
+        target_time_lag,
+    )
+}
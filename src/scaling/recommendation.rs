@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+use serde::Serialize;
+
+use crate::consumer_rates::PartitionRates;
+use crate::internals::duration_millis;
+use crate::kafka_types::{GroupWithMembers, Member, TopicPartition};
+use crate::lag_register::LagAggregate;
+
+/// Consumption of a Member of a Consumer Group.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberConsumption {
+    #[serde(flatten)]
+    pub member: Member,
+
+    /// Amount of Topic Partitions assigned to the Member.
+    pub partitions: usize,
+
+    /// Offsets consumed per second, across the assigned Topic Partitions, if known.
+    pub consume_rate: Option<f64>,
+}
+
+/// Inputs that a [`ScalingRecommendation`] is based on.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScalingInputs {
+    /// Offsets produced per second, across the consumed Topic Partitions, if known.
+    pub produce_rate: Option<f64>,
+
+    /// Average offsets consumed per second by each Member, if known.
+    pub consume_rate_per_consumer: Option<f64>,
+
+    /// Offsets consumed per second needed to keep up with producers,
+    /// and consume the current lag within the target time lag.
+    pub required_consume_rate: Option<f64>,
+
+    /// Sum of the offset lag, across the consumed Topic Partitions.
+    pub offset_lag: u64,
+
+    /// Maximum time lag, across the consumed Topic Partitions.
+    #[serde(rename = "time_lag_max_ms", with = "duration_millis")]
+    pub time_lag_max: Duration,
+
+    /// Consumption of each Member, sorted by Member identifier.
+    pub members: Vec<MemberConsumption>,
+}
+
+/// Recommended amount of consumers (Members) for a Consumer Group.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScalingRecommendation {
+    pub group: String,
+
+    /// Time lag that the recommendation aims to stay under.
+    #[serde(rename = "target_time_lag_ms", with = "duration_millis")]
+    pub target_time_lag: Duration,
+
+    /// Members currently assigned at least one Topic Partition.
+    pub current_consumers: usize,
+
+    /// Recommended amount of Members, if enough is known to recommend it.
+    pub recommended_consumers: Option<usize>,
+
+    /// Amount of Partitions of the consumed Topics: Members beyond it would be idle.
+    pub max_consumers: usize,
+
+    pub inputs: ScalingInputs,
+}
+
+/// Recommend how many consumers a Consumer Group needs, to keep its time lag under a target.
+///
+/// The required consume rate is the produce rate, plus the rate needed to consume the current
+/// offset lag within `target_time_lag`. Dividing it by the average consume rate of the current
+/// Members gives the recommended amount of consumers, between 1 and `partitions`.
+///
+/// # Arguments
+///
+/// * `gwm` - [`GroupWithMembers`] of the Consumer Group, with their assignments
+/// * `agg` - [`LagAggregate`] of the Consumer Group, across the consumed Topic Partitions
+/// * `partitions` - Amount of Partitions of the Topics consumed by the Consumer Group
+/// * `rates` - [`PartitionRates`] of each consumed Topic Partition
+/// * `target_time_lag` - Time lag to stay under (must be positive)
+pub fn recommend(
+    gwm: &GroupWithMembers,
+    agg: &LagAggregate,
+    partitions: usize,
+    rates: &HashMap<TopicPartition, PartitionRates>,
+    target_time_lag: Duration,
+) -> ScalingRecommendation {
+    let mut members: Vec<MemberConsumption> = gwm
+        .members
+        .values()
+        .map(|mwa| MemberConsumption {
+            member: mwa.member.clone(),
+            partitions: mwa.assignment.len(),
+            consume_rate: sum(mwa.assignment.iter().map(|tp| rates.get(tp)?.consume_rate)),
+        })
+        .collect();
+    members.sort_by(|a, b| a.member.id.cmp(&b.member.id));
+
+    let produce_rate = sum(rates.values().map(|r| r.produce_rate));
+
+    let known_consume_rates: Vec<f64> = members.iter().filter_map(|m| m.consume_rate).collect();
+    let consume_rate_per_consumer = (!known_consume_rates.is_empty())
+        .then(|| known_consume_rates.iter().sum::<f64>() / known_consume_rates.len() as f64);
+
+    let target_secs = target_time_lag.num_milliseconds() as f64 / 1000.0;
+    let required_consume_rate = produce_rate.map(|pr| pr + agg.offset_lag_sum as f64 / target_secs);
+
+    let max_consumers = partitions.max(1);
+    let recommended_consumers = match (required_consume_rate, consume_rate_per_consumer) {
+        (Some(req), Some(per)) if per > 0.0 => {
+            Some(((req / per).ceil() as usize).clamp(1, max_consumers))
+        },
+        _ => None,
+    };
+
+    ScalingRecommendation {
+        group: gwm.group.name.clone(),
+        target_time_lag,
+        current_consumers: members.iter().filter(|m| m.partitions > 0).count(),
+        recommended_consumers,
+        max_consumers,
+        inputs: ScalingInputs {
+            produce_rate,
+            consume_rate_per_consumer,
+            required_consume_rate,
+            offset_lag: agg.offset_lag_sum,
+            time_lag_max: agg.time_lag_max,
+            members,
+        },
+    }
+}
+
+/// Sum the known rates, or `None` if none is known.
+fn sum(rates: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    rates.flatten().fold(None, |acc, r| Some(acc.unwrap_or(0.0) + r))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use chrono::Duration;
+
+    use super::recommend;
+    use crate::consumer_rates::PartitionRates;
+    use crate::kafka_types::{
+        Group, GroupWithMembers, Member, MemberWithAssignment, TopicPartition,
+    };
+    use crate::lag_register::LagAggregate;
+
+    fn tp(partition: u32) -> TopicPartition {
+        TopicPartition::new("t".to_string(), partition)
+    }
+
+    /// 2 Members, each assigned 2 of the 4 Partitions of Topic `t`.
+    fn example_group() -> GroupWithMembers {
+        let member = |id: &str, partitions: [u32; 2]| MemberWithAssignment {
+            member: Member {
+                id: id.to_string(),
+                ..Default::default()
+            },
+            assignment: partitions.into_iter().map(tp).collect::<HashSet<TopicPartition>>(),
+        };
+
+        GroupWithMembers {
+            group: Group {
+                name: "g".to_string(),
+                ..Default::default()
+            },
+            members: HashMap::from([
+                ("m1".to_string(), member("m1", [0, 1])),
+                ("m2".to_string(), member("m2", [2, 3])),
+            ]),
+        }
+    }
+
+    fn rates(produce_rate: f64, consume_rate: f64) -> HashMap<TopicPartition, PartitionRates> {
+        (0..4)
+            .map(|p| {
+                let r = PartitionRates {
+                    produce_rate: Some(produce_rate),
+                    consume_rate: Some(consume_rate),
+                    catch_up_eta: None,
+                };
+                (tp(p), r)
+            })
+            .collect()
+    }
+
+    fn agg(offset_lag_sum: u64) -> LagAggregate {
+        LagAggregate {
+            offset_lag_sum,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keep_up_with_producers() {
+        // Each Member consumes 20/s, and producers produce 40/s: 2 Members are enough
+        let rec = recommend(&example_group(), &agg(0), 4, &rates(10.0, 10.0), Duration::minutes(1));
+
+        assert_eq!(rec.current_consumers, 2);
+        assert_eq!(rec.inputs.produce_rate, Some(40.0));
+        assert_eq!(rec.inputs.consume_rate_per_consumer, Some(20.0));
+        assert_eq!(rec.recommended_consumers, Some(2));
+    }
+
+    #[test]
+    fn consume_lag_within_target() {
+        // Producers produce 40/s, plus 3600 lag to consume in 60s: 100/s, at 20/s per Member
+        let rec =
+            recommend(&example_group(), &agg(3600), 4, &rates(10.0, 10.0), Duration::minutes(1));
+
+        assert_eq!(rec.inputs.required_consume_rate, Some(100.0));
+        assert_eq!(rec.max_consumers, 4);
+        assert_eq!(rec.recommended_consumers, Some(4));
+    }
+
+    #[test]
+    fn ignore_idle_members() {
+        // A 3rd Member, without assignment, neither counts as consumer nor lowers the consume rate
+        let mut group = example_group();
+        let idle = MemberWithAssignment {
+            member: Member {
+                id: "m3".to_string(),
+                ..Default::default()
+            },
+            assignment: HashSet::new(),
+        };
+        group.members.insert("m3".to_string(), idle);
+
+        let rec = recommend(&group, &agg(0), 4, &rates(10.0, 10.0), Duration::minutes(1));
+
+        assert_eq!(rec.current_consumers, 2);
+        assert_eq!(rec.inputs.members.len(), 3);
+        assert_eq!(rec.inputs.consume_rate_per_consumer, Some(20.0));
+        assert_eq!(rec.recommended_consumers, Some(2));
+    }
+
+    #[test]
+    fn not_enough_known() {
+        let rec = recommend(&example_group(), &agg(0), 4, &HashMap::new(), Duration::minutes(1));
+        assert_eq!(rec.inputs.produce_rate, None);
+        assert_eq!(rec.recommended_consumers, None);
+
+        let rec = recommend(&example_group(), &agg(10), 4, &rates(10.0, 0.0), Duration::minutes(1));
+        assert_eq!(rec.recommended_consumers, None);
+    }
+}
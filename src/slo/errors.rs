@@ -0,0 +1,35 @@
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum ObjectivesError {
+    /// Reading the objectives file failed.
+    #[error("SLO objectives file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content of the objectives file could not be parsed.
+    #[error("SLO objectives parsing failed: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    /// An objective has an invalid regular expression.
+    #[error("SLO objective regex is invalid: {0}")]
+    Regex(#[from] regex::Error),
+
+    /// More than one objective has the same name.
+    #[error("SLO objective name '{0}' is not unique")]
+    DuplicateName(String),
+
+    /// An objective must set exactly one of `time_lag` and `offset_lag`.
+    #[error("SLO objective '{0}' must set exactly one of 'time_lag' and 'offset_lag'")]
+    InvalidIndicator(String),
+
+    /// An objective target must be a percentage between 0 and 100 (both excluded).
+    #[error("SLO objective '{0}' has an invalid 'objective': {1}")]
+    InvalidTarget(String, f64),
+
+    /// An objective window must be at least a day.
+    #[error("SLO objective '{0}' has an invalid 'window': it must be at least 1 day")]
+    InvalidWindow(String),
+}
+
+pub type ObjectivesResult<T> = Result<T, ObjectivesError>;
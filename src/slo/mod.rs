@@ -0,0 +1,52 @@
+//! Service Level Objectives on the lag of Consumer Groups.
+//!
+//! The lag of each Consumer Group is periodically sampled: every sample is an SLI event, good if the
+//! lag is within the objective and the Consumer Group is not stalled.
+//! The good and total events are counted, and the error budget and burn rates computed from them.
+
+// Inner modules
+mod errors;
+mod objectives;
+mod register;
+mod sli;
+
+// Exports
+pub use register::{SloRegister, SloSnapshot, SloStatus};
+
+// Imports
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::{sync::watch, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::lag_register::LagRegister;
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+use objectives::Objectives;
+
+/// Create an [`SloRegister`] evaluating the objectives read from `objectives_path` (if any).
+///
+/// # Panics
+///
+/// If the objectives file is given, but it's not valid [`Objectives`].
+pub fn init(
+    objectives_path: Option<PathBuf>,
+    lag_reg: Arc<LagRegister>,
+    po_reg: Arc<PartitionOffsetsRegister>,
+    replay_caught_up: watch::Receiver<bool>,
+    shutdown_token: CancellationToken,
+) -> (SloRegister, Option<JoinHandle<()>>) {
+    let objectives = match objectives_path.as_ref() {
+        Some(p) => {
+            Objectives::read(p).unwrap_or_else(|e| panic!("Failed to load SLO objectives: {e}"))
+        },
+        None => Objectives::default(),
+    };
+    info!("Loaded {} SLO objectives", objectives.objectives.len());
+
+    let (slo_reg, slo_join) =
+        SloRegister::new(objectives, lag_reg, po_reg, replay_caught_up, shutdown_token);
+
+    debug!("Initialized");
+    (slo_reg, slo_join)
+}
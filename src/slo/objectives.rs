@@ -0,0 +1,207 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use chrono::Duration;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::lag_register::Lag;
+
+use super::errors::{ObjectivesError, ObjectivesResult};
+
+/// Default `window` of an [`Objective`], in days.
+const DEFAULT_WINDOW_DAYS: u64 = 28;
+
+/// An objective, as it appears in the objectives file.
+#[derive(Debug, Deserialize)]
+struct RawObjective {
+    name: String,
+    group: Option<String>,
+    topic: Option<String>,
+    time_lag: Option<f64>,
+    offset_lag: Option<u64>,
+    objective: f64,
+    #[serde(default = "default_window_days")]
+    window: u64,
+}
+
+fn default_window_days() -> u64 {
+    DEFAULT_WINDOW_DAYS
+}
+
+/// Content of the objectives file.
+#[derive(Debug, Default, Deserialize)]
+struct RawObjectives {
+    #[serde(default)]
+    slo: Vec<RawObjective>,
+}
+
+/// Service Level Indicator: what makes a [`Lag`] sample "good".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indicator {
+    /// Time lag at most this.
+    TimeLag(Duration),
+
+    /// Offset lag at most this.
+    OffsetLag(u64),
+}
+
+impl Indicator {
+    pub fn is_good(&self, lag: &Lag) -> bool {
+        match self {
+            Indicator::TimeLag(max) => lag.time_lag <= *max,
+            Indicator::OffsetLag(max) => lag.offset_lag <= *max,
+        }
+    }
+}
+
+/// Service Level Objective, evaluated for each Consumer Group it matches.
+#[derive(Debug)]
+pub struct Objective {
+    pub name: String,
+    group: Option<Regex>,
+    topic: Option<Regex>,
+    pub indicator: Indicator,
+
+    /// Ratio (between 0 and 1) of [`Lag`] samples that must be good, over the `window`.
+    pub target: f64,
+    pub window: Duration,
+}
+
+impl TryFrom<RawObjective> for Objective {
+    type Error = ObjectivesError;
+
+    fn try_from(raw: RawObjective) -> ObjectivesResult<Self> {
+        let indicator = match (raw.time_lag, raw.offset_lag) {
+            (Some(tl), None) if tl >= 0.0 => {
+                Indicator::TimeLag(Duration::milliseconds((tl * 1000.0) as i64))
+            },
+            (None, Some(ol)) => Indicator::OffsetLag(ol),
+            _ => return Err(ObjectivesError::InvalidIndicator(raw.name)),
+        };
+        if raw.objective <= 0.0 || raw.objective >= 100.0 {
+            return Err(ObjectivesError::InvalidTarget(raw.name, raw.objective));
+        }
+        if raw.window == 0 {
+            return Err(ObjectivesError::InvalidWindow(raw.name));
+        }
+
+        Ok(Objective {
+            group: raw.group.as_deref().map(Regex::new).transpose()?,
+            topic: raw.topic.as_deref().map(Regex::new).transpose()?,
+            name: raw.name,
+            indicator,
+            target: raw.objective / 100.0,
+            window: Duration::days(raw.window as i64),
+        })
+    }
+}
+
+impl Objective {
+    /// Does the objective apply to the given Topic, consumed by the given Consumer Group?
+    pub fn matches(&self, group: &str, topic: &str) -> bool {
+        self.group.as_ref().is_none_or(|r| r.is_match(group))
+            && self.topic.as_ref().is_none_or(|r| r.is_match(topic))
+    }
+}
+
+/// Service Level Objectives, defined in TOML as an array of objectives:
+///
+/// ```toml
+/// [[slo]]
+/// name = "payments-time-lag"
+/// group = "^payments-"
+/// time_lag = 30
+/// objective = 99.9
+/// window = 28
+/// ```
+///
+/// `group` and `topic` are regular expressions, matching all names when omitted.
+/// Exactly one of `time_lag` (seconds) and `offset_lag` must be set; `objective` is a percentage,
+/// and `window` is in days (default `28`).
+#[derive(Debug, Default)]
+pub struct Objectives {
+    pub objectives: Vec<Objective>,
+}
+
+impl Objectives {
+    /// Parse [`Objectives`] from their TOML definition.
+    pub fn parse(toml_str: &str) -> ObjectivesResult<Self> {
+        let raw: RawObjectives = toml::from_str(toml_str)?;
+
+        let mut names = HashSet::new();
+        for o in raw.slo.iter() {
+            if !names.insert(o.name.as_str()) {
+                return Err(ObjectivesError::DuplicateName(o.name.clone()));
+            }
+        }
+
+        Ok(Objectives {
+            objectives: raw.slo.into_iter().map(Objective::try_from).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Read and parse [`Objectives`] from the file at `path`.
+    pub fn read(path: &Path) -> ObjectivesResult<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_objectives() {
+        let objectives = Objectives::parse(
+            r#"
+            [[slo]]
+            name = "payments-time-lag"
+            group = "^payments-"
+            time_lag = 30
+            objective = 99.9
+
+            [[slo]]
+            name = "orders-offset-lag"
+            topic = "^orders$"
+            offset_lag = 1000
+            objective = 99
+            window = 7
+            "#,
+        )
+        .unwrap()
+        .objectives;
+
+        assert_eq!(objectives[0].indicator, Indicator::TimeLag(Duration::seconds(30)));
+        assert!((objectives[0].target - 0.999).abs() < 1e-9);
+        assert_eq!(objectives[0].window, Duration::days(28));
+        assert!(objectives[0].matches("payments-eu", "anything"));
+        assert!(!objectives[0].matches("billing", "anything"));
+
+        assert_eq!(objectives[1].indicator, Indicator::OffsetLag(1000));
+        assert_eq!(objectives[1].window, Duration::days(7));
+        assert!(objectives[1].matches("anything", "orders"));
+        assert!(!objectives[1].matches("anything", "orders.dlq"));
+    }
+
+    #[test]
+    fn should_reject_invalid_objectives() {
+        assert!(matches!(
+            Objectives::parse("[[slo]]\nname = \"a\"\nobjective = 99"),
+            Err(ObjectivesError::InvalidIndicator(_))
+        ));
+        assert!(matches!(
+            Objectives::parse(
+                "[[slo]]\nname = \"a\"\ntime_lag = 1\noffset_lag = 1\nobjective = 99"
+            ),
+            Err(ObjectivesError::InvalidIndicator(_))
+        ));
+        assert!(matches!(
+            Objectives::parse("[[slo]]\nname = \"a\"\ntime_lag = 1\nobjective = 100"),
+            Err(ObjectivesError::InvalidTarget(_, _))
+        ));
+        assert!(matches!(
+            Objectives::parse("[[slo]]\nname = \"a\"\ntime_lag = 1\nobjective = 99\nwindow = 0"),
+            Err(ObjectivesError::InvalidWindow(_))
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{watch, RwLock},
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::lag_register::{Lag, LagRegister};
+use crate::partition_offsets::PartitionOffsetsRegister;
+
+use super::objectives::{Indicator, Objectives};
+use super::sli::{burn_rate, SliSeries};
+
+/// Windows over which burn rates are computed, as labelled in the metrics.
+pub const BURN_RATE_WINDOWS: [(&str, Duration); 7] = [
+    ("5m", Duration::minutes(5)),
+    ("30m", Duration::minutes(30)),
+    ("1h", Duration::hours(1)),
+    ("2h", Duration::hours(2)),
+    ("6h", Duration::hours(6)),
+    ("1d", Duration::days(1)),
+    ("3d", Duration::days(3)),
+];
+
+/// How often the lag of the Consumer Groups is sampled, as SLI events.
+const SAMPLING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// A Consumer Group that is lagging, and hasn't committed for longer than this, is not consuming:
+/// its samples are bad SLI events, whatever the lag.
+const MAX_COMMIT_AGE: Duration = Duration::minutes(1);
+
+/// Status of a Service Level Objective, for a Consumer Group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloStatus {
+    pub slo: String,
+    pub group: String,
+
+    /// Ratio (between 0 and 1) of SLI events that must be good.
+    pub target: f64,
+    pub good_total: u64,
+    pub total: u64,
+
+    /// Ratio of the error budget not yet consumed over the SLO window:
+    /// `1` if untouched, negative once exhausted.
+    pub error_budget_remaining: f64,
+
+    /// Burn rate over each of the [`BURN_RATE_WINDOWS`] (up to the SLO window) with any SLI events.
+    pub burn_rates: Vec<(&'static str, f64)>,
+}
+
+/// [`SliSeries`] of a Consumer Group for a Service Level Objective, as saved in a snapshot.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SloSnapshot {
+    pub slo: String,
+    pub group: String,
+
+    #[serde(flatten)]
+    pub series: SliSeries,
+}
+
+/// Lag of a Consumer Group on a Topic Partition, sampled at an instant.
+#[derive(Debug)]
+struct Sample {
+    group: String,
+    topic: String,
+
+    /// [`Lag`] as of the latest commit, with the offset and time lag re-estimated at the instant.
+    lag: Lag,
+}
+
+/// Whether a [`Sample`] taken at `now` is a good SLI event for the `indicator`.
+///
+/// Once caught up, a Consumer Group is good. Otherwise, it's bad if it hasn't committed
+/// in over [`MAX_COMMIT_AGE`] (i.e. it stalled), or if its lag is beyond the `indicator`.
+fn is_good(indicator: &Indicator, lag: &Lag, now: DateTime<Utc>) -> bool {
+    if lag.offset_lag == 0 {
+        return true;
+    }
+
+    now - lag.offset_timestamp <= MAX_COMMIT_AGE && indicator.is_good(lag)
+}
+
+/// Record the [`Sample`]s taken at `now` as SLI events, for every objective matching them.
+fn record(
+    series: &mut HashMap<(String, String), SliSeries>,
+    objectives: &Objectives,
+    samples: &[Sample],
+    now: DateTime<Utc>,
+) {
+    for s in samples {
+        for o in objectives.objectives.iter().filter(|o| o.matches(&s.group, &s.topic)) {
+            series.entry((o.name.clone(), s.group.clone())).or_default().record(
+                is_good(&o.indicator, &s.lag, now),
+                now,
+                o.window,
+            );
+        }
+    }
+}
+
+/// Sample the current [`Lag`] of every Topic Partition consumed by a Consumer Group,
+/// re-estimated at `now` from the latest committed offset.
+async fn sample(
+    lag_reg: &LagRegister,
+    po_reg: &PartitionOffsetsRegister,
+    now: DateTime<Utc>,
+) -> Vec<Sample> {
+    let mut samples = Vec::new();
+
+    for (group, gwl) in lag_reg.lag_by_group.read().await.iter() {
+        for (tp, lwo) in gwl.lag_by_topic_partition.iter() {
+            let Some(lag) = lwo.lag.as_ref() else {
+                continue;
+            };
+
+            let offset_lag = po_reg.estimate_offset_lag(tp, lag.offset).await;
+            let time_lag = po_reg.estimate_time_lag(tp, lag.offset, now).await;
+            samples.push(Sample {
+                group: group.clone(),
+                topic: tp.topic.clone(),
+                lag: Lag {
+                    offset_lag: offset_lag.unwrap_or(lag.offset_lag),
+                    time_lag: time_lag.unwrap_or(lag.time_lag),
+                    ..lag.clone()
+                },
+            });
+        }
+    }
+
+    samples
+}
+
+/// Evaluates Service Level Objectives on the lag of the Consumer Groups they match.
+///
+/// Every [`SAMPLING_INTERVAL`], the lag of each Topic Partition consumed by a matching
+/// Consumer Group is sampled: each sample is an SLI event, either good or bad (see [`is_good`]).
+pub struct SloRegister {
+    objectives: Arc<Objectives>,
+
+    /// [`SliSeries`] by Service Level Objective and Consumer Group.
+    series: Arc<RwLock<HashMap<(String, String), SliSeries>>>,
+}
+
+impl SloRegister {
+    /// Create a new [`Self`] and, if there is any objective, spawn the task that periodically
+    /// samples the lag to evaluate them.
+    ///
+    /// Until the replay of `__consumer_offsets` has caught up (`replay_caught_up`), committed offsets
+    /// can be stale, superseded by commits yet to be replayed: nothing is sampled.
+    ///
+    /// # Arguments
+    ///
+    /// * `objectives` - The [`Objectives`] to evaluate
+    /// * `lag_reg` - The [`LagRegister`] providing the committed offsets
+    /// * `po_reg` - The [`PartitionOffsetsRegister`] to estimate the current lag with
+    /// * `replay_caught_up` - Whether the replay of `__consumer_offsets` has caught up
+    /// * `shutdown_token` - A [`CancellationToken`] that, when cancelled, will make the evaluation task terminate
+    pub fn new(
+        objectives: Objectives,
+        lag_reg: Arc<LagRegister>,
+        po_reg: Arc<PartitionOffsetsRegister>,
+        replay_caught_up: watch::Receiver<bool>,
+        shutdown_token: CancellationToken,
+    ) -> (Self, Option<JoinHandle<()>>) {
+        let sr = Self {
+            objectives: Arc::new(objectives),
+            series: Arc::new(RwLock::new(HashMap::new())),
+        };
+        if sr.objectives.objectives.is_empty() {
+            return (sr, None);
+        }
+
+        let objectives_clone = sr.objectives.clone();
+        let series_clone = sr.series.clone();
+        let join_handle = tokio::spawn(async move {
+            let mut interval = interval(SAMPLING_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if !*replay_caught_up.borrow() {
+                            continue;
+                        }
+
+                        let now = Utc::now();
+                        let samples = sample(&lag_reg, &po_reg, now).await;
+                        record(&mut *series_clone.write().await, &objectives_clone, &samples, now);
+                    },
+                    _ = shutdown_token.cancelled() => {
+                        info!("Shutting down");
+                        break;
+                    },
+                }
+            }
+        });
+
+        (sr, Some(join_handle))
+    }
+
+    /// The [`SloStatus`] of each Consumer Group, for each Service Level Objective that matched it,
+    /// sorted by objective and Consumer Group.
+    pub async fn status(&self, now: DateTime<Utc>) -> Vec<SloStatus> {
+        let r_guard = self.series.read().await;
+
+        let mut res = Vec::with_capacity(r_guard.len());
+        for o in self.objectives.objectives.iter() {
+            for ((slo, group), sli) in r_guard.iter().filter(|((slo, _), _)| *slo == o.name) {
+                let burn_rates = BURN_RATE_WINDOWS
+                    .iter()
+                    .filter(|(_, w)| *w <= o.window)
+                    .filter_map(|(label, w)| {
+                        burn_rate(sli.over(*w, now), o.target).map(|br| (*label, br))
+                    })
+                    .collect();
+
+                res.push(SloStatus {
+                    slo: slo.clone(),
+                    group: group.clone(),
+                    target: o.target,
+                    good_total: sli.good_total,
+                    total: sli.total,
+                    error_budget_remaining: 1.0
+                        - burn_rate(sli.over(o.window, now), o.target).unwrap_or_default(),
+                    burn_rates,
+                });
+            }
+        }
+        res.sort_by(|a, b| (&a.slo, &a.group).cmp(&(&b.slo, &b.group)));
+        res
+    }
+
+    /// Export the [`SliSeries`] of all the Consumer Groups, to be saved in a snapshot.
+    pub async fn export(&self) -> Vec<SloSnapshot> {
+        self.series
+            .read()
+            .await
+            .iter()
+            .map(|((slo, group), series)| SloSnapshot {
+                slo: slo.clone(),
+                group: group.clone(),
+                series: series.clone(),
+            })
+            .collect()
+    }
+
+    /// Restore previously exported [`SliSeries`].
+    ///
+    /// Those of objectives no longer defined are discarded.
+    pub async fn restore(&self, snapshots: Vec<SloSnapshot>) -> usize {
+        let mut w_guard = self.series.write().await;
+
+        let mut restored = 0;
+        for s in snapshots {
+            if self.objectives.objectives.iter().any(|o| o.name == s.slo) {
+                w_guard.insert((s.slo, s.group), s.series);
+                restored += 1;
+            }
+        }
+
+        restored
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn objectives() -> Objectives {
+        Objectives::parse(
+            "[[slo]]\nname = \"fast\"\ngroup = \"^payments\"\ntime_lag = 30\nobjective = 99",
+        )
+        .unwrap()
+    }
+
+    fn sample(group: &str, committed_ago_s: i64, offset_lag: u64, time_lag_s: i64) -> Sample {
+        Sample {
+            group: group.to_string(),
+            topic: "orders".to_string(),
+            lag: Lag {
+                offset: 100,
+                offset_timestamp: Utc::now() - Duration::seconds(committed_ago_s),
+                offset_lag,
+                time_lag: Duration::seconds(time_lag_s),
+            },
+        }
+    }
+
+    fn recorded(samples: &[Sample]) -> (u64, u64) {
+        let mut series = HashMap::new();
+        record(&mut series, &objectives(), samples, Utc::now());
+        series
+            .get(&("fast".to_string(), "payments".to_string()))
+            .map_or((0, 0), |s| (s.good_total, s.total))
+    }
+
+    #[test]
+    fn lag_within_objective_is_good() {
+        assert_eq!(recorded(&[sample("payments", 5, 10, 10)]), (1, 1));
+        assert_eq!(recorded(&[sample("payments", 5, 10, 60)]), (0, 1));
+        assert_eq!(recorded(&[sample("billing", 5, 10, 10)]), (0, 0));
+    }
+
+    #[test]
+    fn stalled_consumer_is_bad() {
+        // Lag still within the objective as of the latest commit, but no commit for 5 minutes
+        assert_eq!(recorded(&[sample("payments", 300, 10, 10)]), (0, 1));
+
+        // Sampled on every tick, the stalled consumer keeps burning the error budget
+        let stalled = sample("payments", 300, 10, 10);
+        let mut series = HashMap::new();
+        for _ in 0..3 {
+            record(&mut series, &objectives(), std::slice::from_ref(&stalled), Utc::now());
+        }
+        let sli = &series[&("fast".to_string(), "payments".to_string())];
+        assert_eq!((sli.good_total, sli.total), (0, 3));
+    }
+
+    #[test]
+    fn idle_caught_up_consumer_is_good() {
+        assert_eq!(recorded(&[sample("payments", 300, 0, 600)]), (1, 1));
+    }
+}
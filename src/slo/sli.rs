@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Resolution of the fine-grained buckets, used for the shorter burn rate windows.
+const FINE_RESOLUTION: Duration = Duration::minutes(1);
+
+/// How long the fine-grained buckets are kept: the longest burn rate window they serve.
+const FINE_RETENTION: Duration = Duration::hours(6);
+
+/// Resolution of the coarse-grained buckets, used for the longer windows (up to the SLO window).
+const COARSE_RESOLUTION: Duration = Duration::hours(1);
+
+/// Amount of good and total SLI events, in the time bucket beginning at `start`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Bucket {
+    start: DateTime<Utc>,
+    good: u64,
+    total: u64,
+}
+
+/// Good and total SLI events of a Consumer Group, for a Service Level Objective.
+///
+/// Alongside the counters since the beginning, events are kept in time buckets,
+/// to compute the ratio of good events over a time window.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SliSeries {
+    pub good_total: u64,
+    pub total: u64,
+    fine: VecDeque<Bucket>,
+    coarse: VecDeque<Bucket>,
+}
+
+impl SliSeries {
+    /// Record an SLI event that happened `at`, keeping the coarse-grained buckets for `window`.
+    pub fn record(&mut self, good: bool, at: DateTime<Utc>, window: Duration) {
+        self.total += 1;
+        self.good_total += good as u64;
+
+        record_in(&mut self.fine, good, at, FINE_RESOLUTION, FINE_RETENTION);
+        record_in(&mut self.coarse, good, at, COARSE_RESOLUTION, window);
+    }
+
+    /// Amount of good and total SLI events, over the `window` preceding `now`.
+    ///
+    /// Windows up to [`FINE_RETENTION`] have a resolution of a minute, longer ones of an hour.
+    pub fn over(&self, window: Duration, now: DateTime<Utc>) -> (u64, u64) {
+        let buckets = if window <= FINE_RETENTION {
+            &self.fine
+        } else {
+            &self.coarse
+        };
+
+        buckets
+            .iter()
+            .rev()
+            .take_while(|b| b.start > now - window)
+            .fold((0, 0), |(good, total), b| (good + b.good, total + b.total))
+    }
+}
+
+fn record_in(
+    buckets: &mut VecDeque<Bucket>,
+    good: bool,
+    at: DateTime<Utc>,
+    resolution: Duration,
+    retention: Duration,
+) {
+    let start = at.duration_trunc(resolution).unwrap_or(at);
+    match buckets.back_mut() {
+        Some(b) if b.start >= start => {
+            b.good += good as u64;
+            b.total += 1;
+        },
+        _ => buckets.push_back(Bucket {
+            start,
+            good: good as u64,
+            total: 1,
+        }),
+    }
+
+    while buckets.front().is_some_and(|b| b.start + resolution <= at - retention) {
+        buckets.pop_front();
+    }
+}
+
+/// How fast the error budget is being consumed, given the good and total SLI events in a window:
+/// `1` means it would be exactly consumed by the end of the SLO window.
+///
+/// Returns `None` if there were no events.
+pub fn burn_rate((good, total): (u64, u64), target: f64) -> Option<f64> {
+    (total > 0).then(|| ((total - good) as f64 / total as f64) / (1.0 - target))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, DurationRound, Utc};
+
+    use super::{burn_rate, SliSeries};
+
+    #[test]
+    fn events_over_windows() {
+        let window = Duration::days(1);
+        let t0 = Utc::now().duration_trunc(Duration::hours(1)).unwrap();
+        let mut sli = SliSeries::default();
+
+        // 10 events 2 hours ago (1 bad), 10 events in the latest minute (5 bad)
+        for i in 0..10 {
+            sli.record(i != 0, t0, window);
+        }
+        let now = t0 + Duration::hours(2);
+        for i in 0..10 {
+            sli.record(i % 2 == 0, now, window);
+        }
+
+        assert_eq!((sli.good_total, sli.total), (14, 20));
+        assert_eq!(sli.over(Duration::minutes(5), now), (5, 10));
+        assert_eq!(sli.over(Duration::hours(6), now), (14, 20));
+        assert_eq!(sli.over(Duration::days(1), now), (14, 20));
+
+        // After the window has passed, only the counters remain
+        let later = now + Duration::days(2);
+        sli.record(true, later, window);
+        assert_eq!(sli.over(Duration::days(1), later), (1, 1));
+        assert_eq!(sli.over(Duration::hours(6), later), (1, 1));
+        assert_eq!((sli.good_total, sli.total), (15, 21));
+    }
+
+    #[test]
+    fn burn_rates() {
+        assert_eq!(burn_rate((0, 0), 0.99), None);
+        assert_eq!(burn_rate((100, 100), 0.99), Some(0.0));
+        assert!((burn_rate((99, 100), 0.99).unwrap() - 1.0).abs() < 1e-9);
+        assert!((burn_rate((90, 100), 0.99).unwrap() - 10.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// Possible errors from the [`super`] module.
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    /// Reading or writing the snapshot file failed.
+    #[error("Snapshot file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content of the snapshot file could not be (de)serialized.
+    #[error("Snapshot (de)serialization failed: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    /// The snapshot file was written with a format version that this build does not understand.
+    #[error("Snapshot format version {0} is not supported (expected {1})")]
+    UnsupportedVersion(u32, u32),
+
+    /// The snapshot is too old to be trusted.
+    #[error("Snapshot taken at '{0}' is older than the maximum age of {1}s")]
+    Stale(DateTime<Utc>, u64),
+}
+
+pub type SnapshotResult<T> = Result<T, SnapshotError>;
@@ -0,0 +1,246 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::errors::{SnapshotError, SnapshotResult};
+
+use crate::cluster_status::ClusterStatus;
+use crate::kafka_types::{Group, TopicPartition};
+use crate::lag_register::{GroupWithLag, LagWithOwner};
+use crate::partition_offsets::PartitionOffsetsHistory;
+use crate::slo::SloSnapshot;
+
+/// Version of the format of [`Snapshot`] files.
+///
+/// Bump this every time [`Snapshot`] (or any of the types it contains) changes in
+/// a way that makes older files impossible to deserialize.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// A point-in-time copy of the internal state of the service.
+///
+/// It contains all that is needed to restore the registers after a restart,
+/// without having to wait for them to be populated from scratch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// See [`SNAPSHOT_FORMAT_VERSION`].
+    pub version: u32,
+
+    /// [`DateTime<Utc>`] when this snapshot was taken.
+    pub taken_at: DateTime<Utc>,
+
+    /// Latest [`ClusterStatus`], if any.
+    pub cluster_status: Option<ClusterStatus>,
+
+    /// Offsets history of each Topic Partition.
+    pub partition_offsets: Vec<PartitionOffsetsHistory>,
+
+    /// Lag of each Consumer Group.
+    pub groups: Vec<GroupLagSnapshot>,
+
+    /// SLI events of each Consumer Group, for each Service Level Objective.
+    #[serde(default)]
+    pub slos: Vec<SloSnapshot>,
+}
+
+/// Serializable version of [`GroupWithLag`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupLagSnapshot {
+    pub group: Group,
+    pub partitions: Vec<PartitionLagSnapshot>,
+}
+
+/// Serializable version of a `TopicPartition -> LagWithOwner` entry of [`GroupWithLag`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PartitionLagSnapshot {
+    #[serde(flatten)]
+    pub topic_partition: TopicPartition,
+    #[serde(flatten)]
+    pub lag_with_owner: LagWithOwner,
+}
+
+impl From<&GroupWithLag> for GroupLagSnapshot {
+    fn from(gwl: &GroupWithLag) -> Self {
+        Self {
+            group: gwl.group.clone(),
+            partitions: gwl
+                .lag_by_topic_partition
+                .iter()
+                .map(|(tp, lwo)| PartitionLagSnapshot {
+                    topic_partition: tp.clone(),
+                    lag_with_owner: lwo.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<GroupLagSnapshot> for GroupWithLag {
+    fn from(gls: GroupLagSnapshot) -> Self {
+        Self {
+            group: gls.group,
+            lag_by_topic_partition: gls
+                .partitions
+                .into_iter()
+                .map(|pls| (pls.topic_partition, pls.lag_with_owner))
+                .collect(),
+        }
+    }
+}
+
+/// Just enough of a [`Snapshot`] to decide if the rest can be deserialized.
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+impl Snapshot {
+    /// Write the [`Snapshot`] to the given `path`, atomically.
+    ///
+    /// The content is first written (and synced) to a temporary file next to `path`,
+    /// and then renamed to `path`: readers will either see the previous snapshot,
+    /// or the new one, but never a partially written file.
+    ///
+    /// Returns the size of the written file, in bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to write the snapshot to
+    pub fn write_atomically(&self, path: &Path) -> SnapshotResult<usize> {
+        let bytes = serde_json::to_vec(self)?;
+
+        let tmp_path = tmp_path_for(path);
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(&bytes)?;
+        tmp_file.sync_all()?;
+        drop(tmp_file);
+
+        fs::rename(&tmp_path, path)?;
+
+        Ok(bytes.len())
+    }
+
+    /// Read a [`Snapshot`] from the given `path`.
+    ///
+    /// Fails if the snapshot format version is not [`SNAPSHOT_FORMAT_VERSION`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to read the snapshot from
+    pub fn read(path: &Path) -> SnapshotResult<Self> {
+        let bytes = fs::read(path)?;
+
+        let header: SnapshotHeader = serde_json::from_slice(&bytes)?;
+        if header.version != SNAPSHOT_FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(header.version, SNAPSHOT_FORMAT_VERSION));
+        }
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, fs, path::PathBuf};
+
+    use chrono::{Duration, Utc};
+
+    use crate::kafka_types::{Group, Member, TopicPartition};
+    use crate::lag_register::{GroupWithLag, Lag, LagWithOwner};
+    use crate::partition_offsets::{PartitionOffsetsHistory, TrackedOffset};
+    use crate::snapshot::errors::SnapshotError;
+    use crate::snapshot::file::{GroupLagSnapshot, Snapshot, SNAPSHOT_FORMAT_VERSION};
+
+    fn test_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("kommitted-{}-{name}.json", std::process::id()))
+    }
+
+    fn example_snapshot() -> Snapshot {
+        let tp = TopicPartition::new("topic".to_string(), 3);
+        let gwl = GroupWithLag {
+            group: Group {
+                name: "group".to_string(),
+                ..Default::default()
+            },
+            lag_by_topic_partition: HashMap::from([(
+                tp.clone(),
+                LagWithOwner {
+                    lag: Some(Lag {
+                        offset: 100,
+                        offset_timestamp: Utc::now(),
+                        offset_lag: 10,
+                        time_lag: Duration::milliseconds(1500),
+                    }),
+                    owner: Some(Member {
+                        id: "member".to_string(),
+                        client_id: "client".to_string(),
+                        client_host: "/127.0.0.1".to_string(),
+                    }),
+                    ..Default::default()
+                },
+            )]),
+        };
+
+        Snapshot {
+            version: SNAPSHOT_FORMAT_VERSION,
+            taken_at: Utc::now(),
+            cluster_status: None,
+            partition_offsets: vec![PartitionOffsetsHistory {
+                topic_partition: tp,
+                earliest_available_offset: Some(5),
+                tracked_offsets: vec![TrackedOffset {
+                    offset: 110,
+                    at: Utc::now(),
+                }],
+            }],
+            groups: vec![GroupLagSnapshot::from(&gwl)],
+            slos: vec![],
+        }
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let path = test_path("roundtrip");
+        let snapshot = example_snapshot();
+
+        let size = snapshot.write_atomically(&path).unwrap();
+        assert_eq!(size as u64, fs::metadata(&path).unwrap().len());
+
+        let read = Snapshot::read(&path).unwrap();
+        assert_eq!(read.taken_at, snapshot.taken_at);
+        assert_eq!(read.partition_offsets, snapshot.partition_offsets);
+
+        let gwl = GroupWithLag::from(read.groups.into_iter().next().unwrap());
+        let lwo = gwl.lag_by_topic_partition.get(&TopicPartition::new("topic".to_string(), 3));
+        assert_eq!(lwo.unwrap().lag.as_ref().unwrap().time_lag, Duration::milliseconds(1500));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reject_unsupported_version() {
+        let path = test_path("version");
+        let snapshot = Snapshot {
+            version: SNAPSHOT_FORMAT_VERSION + 1,
+            ..example_snapshot()
+        };
+        snapshot.write_atomically(&path).unwrap();
+
+        assert!(matches!(
+            Snapshot::read(&path),
+            Err(SnapshotError::UnsupportedVersion(v, _)) if v == SNAPSHOT_FORMAT_VERSION + 1
+        ));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+//! Periodic snapshots of the internal state, to restore it after a restart.
+//!
+//! Without this, every restart means replaying `__consumer_offsets` and waiting
+//! for the offsets history to fill up again, before lag can be estimated.
+
+// Inner modules
+mod errors;
+mod file;
+
+// Exports
+pub use errors::SnapshotError;
+pub use file::{Snapshot, SNAPSHOT_FORMAT_VERSION};
+
+// Imports
+use std::{path::PathBuf, sync::Arc};
+
+use chrono::Utc;
+use prometheus::{
+    register_histogram_with_registry, register_int_gauge_with_registry, Histogram, IntGauge,
+    Registry,
+};
+use tokio::{
+    task::JoinHandle,
+    time::{interval, Duration, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::cluster_status::ClusterStatusRegister;
+use crate::lag_register::LagRegister;
+use crate::partition_offsets::PartitionOffsetsRegister;
+use crate::slo::SloRegister;
+
+use file::GroupLagSnapshot;
+
+const MET_WRITE_NAME: &str = "snapshot_write_time_milliseconds";
+const MET_WRITE_HELP: &str = "Time (ms) taken to take and write a snapshot of the internal state";
+const MET_SIZE_NAME: &str = "snapshot_size_bytes";
+const MET_SIZE_HELP: &str = "Size (bytes) of the latest snapshot of the internal state written";
+
+/// Load the [`Snapshot`] at the given `path`, if it is usable.
+///
+/// A missing, unreadable, incompatible or stale snapshot is logged and ignored:
+/// the service will start from scratch, as if there was no snapshot at all.
+///
+/// # Arguments
+///
+/// * `path` - Path of the snapshot file
+/// * `max_age` - Snapshots older than this are considered stale
+pub fn load(path: &PathBuf, max_age: Duration) -> Option<Snapshot> {
+    if !path.exists() {
+        info!("No snapshot found at {path:?}: starting from scratch");
+        return None;
+    }
+
+    let res = Snapshot::read(path).and_then(|s| {
+        let age = (Utc::now() - s.taken_at).to_std().unwrap_or_default();
+        if age > max_age {
+            Err(SnapshotError::Stale(s.taken_at, max_age.as_secs()))
+        } else {
+            Ok(s)
+        }
+    });
+
+    match res {
+        Ok(s) => {
+            info!("Loaded snapshot taken at '{}' from {path:?}", s.taken_at);
+            Some(s)
+        },
+        Err(e) => {
+            warn!("Ignoring snapshot at {path:?}: {e}");
+            None
+        },
+    }
+}
+
+impl Snapshot {
+    /// Restore the [`ClusterStatusRegister`] from the content of this [`Snapshot`].
+    pub async fn restore_cluster_status(&mut self, cs_reg: &ClusterStatusRegister) {
+        if let Some(cs) = self.cluster_status.take() {
+            if cs_reg.restore_status(cs).await {
+                info!("Restored cluster status from snapshot");
+            }
+        }
+    }
+
+    /// Restore the [`PartitionOffsetsRegister`] from the content of this [`Snapshot`].
+    pub async fn restore_partition_offsets(&mut self, po_reg: &PartitionOffsetsRegister) {
+        let restored = po_reg.restore_history(std::mem::take(&mut self.partition_offsets)).await;
+        info!("Restored offsets history of {restored} partitions from snapshot");
+    }
+
+    /// Restore the [`LagRegister`] from the content of this [`Snapshot`].
+    pub async fn restore_lag(&mut self, lag_reg: &LagRegister) {
+        let groups = std::mem::take(&mut self.groups).into_iter().map(Into::into).collect();
+        let restored = lag_reg.restore(groups).await;
+        info!("Restored lag of {restored} groups from snapshot");
+    }
+
+    /// Restore the [`SloRegister`] from the content of this [`Snapshot`].
+    pub async fn restore_slos(&mut self, slo_reg: &SloRegister) {
+        let restored = slo_reg.restore(std::mem::take(&mut self.slos)).await;
+        info!("Restored SLI events of {restored} groups objectives from snapshot");
+    }
+}
+
+/// Take a [`Snapshot`] of the current content of the registers.
+async fn take(
+    cs_reg: &ClusterStatusRegister,
+    po_reg: &PartitionOffsetsRegister,
+    lag_reg: &LagRegister,
+    slo_reg: &SloRegister,
+) -> Snapshot {
+    Snapshot {
+        version: SNAPSHOT_FORMAT_VERSION,
+        taken_at: Utc::now(),
+        cluster_status: cs_reg.get_status().await,
+        partition_offsets: po_reg.export_history().await,
+        groups: lag_reg.lag_by_group.read().await.values().map(GroupLagSnapshot::from).collect(),
+        slos: slo_reg.export().await,
+    }
+}
+
+/// Spawn a task that periodically writes a [`Snapshot`] of the registers to `path`.
+///
+/// A last snapshot is written when the provided [`CancellationToken`] is cancelled,
+/// right before the task terminates.
+#[allow(clippy::too_many_arguments)] //< one per register to snapshot
+pub fn init(
+    path: PathBuf,
+    snapshot_interval: Duration,
+    cs_reg: Arc<ClusterStatusRegister>,
+    po_reg: Arc<PartitionOffsetsRegister>,
+    lag_reg: Arc<LagRegister>,
+    slo_reg: Arc<SloRegister>,
+    shutdown_token: CancellationToken,
+    metrics: Arc<Registry>,
+) -> JoinHandle<()> {
+    let metric_write: Histogram =
+        register_histogram_with_registry!(MET_WRITE_NAME, MET_WRITE_HELP, metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {MET_WRITE_NAME}"));
+    let metric_size: IntGauge =
+        register_int_gauge_with_registry!(MET_SIZE_NAME, MET_SIZE_HELP, metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {MET_SIZE_NAME}"));
+
+    let join_handle = tokio::spawn(async move {
+        let mut interval = interval(snapshot_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // The first tick completes immediately: no point in snapshotting right away
+        interval.tick().await;
+
+        loop {
+            let shutting_down = tokio::select! {
+                _ = interval.tick() => false,
+                _ = shutdown_token.cancelled() => true,
+            };
+
+            let timer = metric_write.start_timer();
+            let snapshot = take(&cs_reg, &po_reg, &lag_reg, &slo_reg).await;
+            let snapshot_path = path.clone();
+            let res =
+                tokio::task::spawn_blocking(move || snapshot.write_atomically(&snapshot_path))
+                    .await
+                    .expect("Snapshot writing task panicked (fatal)");
+            timer.observe_duration();
+
+            match res {
+                Ok(size) => {
+                    debug!("Written snapshot of {size} bytes to {path:?}");
+                    metric_size.set(size as i64);
+                },
+                Err(e) => {
+                    error!("Failed to write snapshot to {path:?}: {e}");
+                },
+            }
+
+            if shutting_down {
+                info!("Shutting down");
+                break;
+            }
+        }
+    });
+
+    debug!("Initialized");
+    join_handle
+}
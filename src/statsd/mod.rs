@@ -0,0 +1,139 @@
+//! Emit lag metrics as [StatsD](https://github.com/statsd/statsd) gauges over UDP,
+//! optionally with [DogStatsD](https://docs.datadoghq.com/developers/dogstatsd/) tags.
+//!
+//! For environments where an agent (e.g. Datadog's) collects metrics, instead of scraping the HTTP Service:
+//! every interval, the per-partition and aggregated lag is flushed, packed in as few datagrams as fit.
+
+// Inner modules
+mod packet;
+
+// Imports
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use clap::ValueEnum;
+use const_format::formatcp;
+use prometheus::{register_int_counter_with_registry, IntCounter};
+use tokio::{
+    net::{lookup_host, UdpSocket},
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::http::{render_bespoke_metrics, HttpServiceState};
+use crate::prometheus_metrics::{exposition, NAMESPACE};
+
+use packet::{gauge_lines, pack};
+
+/// Families of the bespoke metrics that carry lag, and are emitted as gauges.
+const LAG_FAMILIES: [&str; 6] = [
+    formatcp!("{NAMESPACE}_kafka_consumer_partition_lag_offset"),
+    formatcp!("{NAMESPACE}_kafka_consumer_partition_lag_milliseconds"),
+    formatcp!("{NAMESPACE}_kafka_consumer_group_topic_lag_offset_sum"),
+    formatcp!("{NAMESPACE}_kafka_consumer_group_topic_lag_milliseconds_max"),
+    formatcp!("{NAMESPACE}_kafka_consumer_group_lag_offset_sum"),
+    formatcp!("{NAMESPACE}_kafka_consumer_group_lag_milliseconds_max"),
+];
+
+const MET_SENT_NAME: &str = "statsd_sent_packets_total";
+const MET_SENT_HELP: &str = "StatsD packets sent";
+const MET_FAILED_NAME: &str = "statsd_failed_packets_total";
+const MET_FAILED_HELP: &str = "StatsD packets that failed to be sent";
+
+/// Line format of the emitted gauges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum StatsdFormat {
+    /// DogStatsD: labels are sent as tags (e.g. '|#group:G,topic:T').
+    Dogstatsd,
+
+    /// Plain StatsD: label values are appended to the metric name (e.g. '.G.T').
+    Statsd,
+}
+
+/// Configuration of the StatsD emission.
+#[derive(Debug, Clone)]
+pub struct StatsdConfig {
+    /// Address of the StatsD server (`HOST:PORT`): resolved at every flush, to follow DNS changes.
+    pub address: String,
+    pub format: StatsdFormat,
+    pub prefix: String,
+    pub interval: Duration,
+    pub max_packet_size: usize,
+}
+
+/// Spawn the task that periodically emits the lag gauges to the StatsD server.
+///
+/// The gauges and their tags are the same served by the HTTP Service, so they are built from its `state`.
+pub fn init(
+    config: StatsdConfig,
+    state: HttpServiceState,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    let register = |name: &str, help: &str| -> IntCounter {
+        register_int_counter_with_registry!(name, help, state.metrics)
+            .unwrap_or_else(|_| panic!("Failed to create metric: {name}"))
+    };
+    let sent = register(MET_SENT_NAME, MET_SENT_HELP);
+    let failed = register(MET_FAILED_NAME, MET_FAILED_HELP);
+
+    debug!("Initialized, emitting to '{}' as {:?}", config.address, config.format);
+    tokio::spawn(async move {
+        let mut interval = interval(config.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = shutdown_token.cancelled() => break,
+            }
+
+            let bespoke = render_bespoke_metrics(&state).await;
+            let families: Vec<_> = exposition::parse(bespoke.iter().map(String::as_str))
+                .into_iter()
+                .filter(|f| LAG_FAMILIES.contains(&f.name.as_str()))
+                .collect();
+            let lines = gauge_lines(&families, &config.prefix, config.format);
+            let packets = pack(&lines, config.max_packet_size);
+
+            let socket = match connect(&config.address).await {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("Failed to reach StatsD server '{}': {e}", config.address);
+                    failed.inc_by(packets.len() as u64);
+                    continue;
+                },
+            };
+            for p in packets {
+                match socket.send(p.as_bytes()).await {
+                    Ok(_) => sent.inc(),
+                    Err(e) => {
+                        debug!("Failed to send StatsD packet to '{}': {e}", config.address);
+                        failed.inc();
+                    },
+                }
+            }
+            trace!("Emitted {} gauges to '{}'", lines.len(), config.address);
+        }
+
+        info!("Shutting down");
+    })
+}
+
+/// Resolve `address`, and connect a UDP socket of the matching IP version to it.
+async fn connect(address: &str) -> std::io::Result<UdpSocket> {
+    let remote = lookup_host(address).await?.next().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "address resolved to nothing")
+    })?;
+    let local: SocketAddr = if remote.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(remote).await?;
+    Ok(socket)
+}
@@ -0,0 +1,113 @@
+use crate::prometheus_metrics::exposition::Family;
+use crate::prometheus_metrics::NAMESPACE;
+
+use super::StatsdFormat;
+
+/// Characters with a special meaning in the StatsD line format, replaced when found in names and tags.
+const RESERVED: [char; 6] = [':', '|', '@', '#', ',', '\n'];
+
+/// Separator of the segments of a plain StatsD name, replaced when found in label values.
+const SEGMENT_SEPARATOR: char = '.';
+
+/// Format the samples of the metric [`Family`]s as StatsD gauges, one line per sample.
+///
+/// The namespace is stripped from the family name and replaced by `prefix`. Samples with a negative
+/// value are skipped, as the bespoke metrics use `-1` to mean "unknown".
+pub fn gauge_lines(families: &[Family], prefix: &str, format: StatsdFormat) -> Vec<String> {
+    families
+        .iter()
+        .flat_map(|f| f.samples.iter())
+        .filter(|s| s.value >= 0.0)
+        .map(|s| {
+            let name = s.name.strip_prefix(NAMESPACE).unwrap_or(&s.name).trim_start_matches('_');
+            let name = sanitize(&format!("{prefix}.{name}"));
+
+            match format {
+                StatsdFormat::Dogstatsd => {
+                    let tags = s
+                        .labels
+                        .iter()
+                        .map(|(k, v)| format!("{}:{}", sanitize(k), sanitize(v)))
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    format!("{name}:{}|g|#{tags}", s.value)
+                },
+                StatsdFormat::Statsd => {
+                    let segments: String =
+                        s.labels.iter().map(|(_, v)| format!(".{}", sanitize_segment(v))).collect();
+                    format!("{name}{segments}:{}|g", s.value)
+                },
+            }
+        })
+        .collect()
+}
+
+/// Pack `lines` into newline-separated packets, each at most `max_size` bytes.
+///
+/// A line longer than `max_size` goes into a packet of its own: better sent and maybe truncated,
+/// than silently lost.
+pub fn pack(lines: &[String], max_size: usize) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        if !current.is_empty() && current.len() + 1 + line.len() > max_size {
+            packets.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        packets.push(current);
+    }
+
+    packets
+}
+
+fn sanitize(s: &str) -> String {
+    s.replace(RESERVED, "_")
+}
+
+fn sanitize_segment(s: &str) -> String {
+    sanitize(s).replace(SEGMENT_SEPARATOR, "_")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prometheus_metrics::exposition::parse;
+
+    use super::*;
+
+    fn families() -> Vec<Family> {
+        parse([
+            r#"kmtd_kafka_consumer_partition_lag_offset{cluster_id="c",group="g:1",topic="t.v1",partition="0"} 42 1700000000000"#,
+            r#"kmtd_kafka_consumer_partition_lag_offset{cluster_id="c",group="g:1",topic="t.v1",partition="1"} -1"#,
+        ])
+    }
+
+    #[test]
+    fn dogstatsd_lines() {
+        let lines = gauge_lines(&families(), "kommitted", StatsdFormat::Dogstatsd);
+        assert_eq!(
+            lines,
+            ["kommitted.kafka_consumer_partition_lag_offset:42|g|#cluster_id:c,group:g_1,topic:t.v1,partition:0"]
+        );
+    }
+
+    #[test]
+    fn statsd_lines() {
+        let lines = gauge_lines(&families(), "kmt", StatsdFormat::Statsd);
+        // Dots in label values would add segments to the name
+        assert_eq!(lines, ["kmt.kafka_consumer_partition_lag_offset.c.g_1.t_v1.0:42|g"]);
+    }
+
+    #[test]
+    fn pack_lines() {
+        let lines: Vec<String> = ["aaaa", "bbbb", "cccc", "dddddddddddd"].map(String::from).into();
+        assert_eq!(pack(&lines, 9), ["aaaa\nbbbb", "cccc", "dddddddddddd"]);
+        assert_eq!(pack(&lines, 100), ["aaaa\nbbbb\ncccc\ndddddddddddd"]);
+        assert!(pack(&[], 10).is_empty());
+    }
+}